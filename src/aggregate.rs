@@ -0,0 +1,782 @@
+use std::{cmp::Ordering, collections::HashMap, iter::Peekable, str::CharIndices, time::Duration};
+
+use crate::cell_value::{compare_cells, format_duration, CellValue};
+use crate::table::{Table, TableError};
+
+/// Arithmetic over the cells of a row: columns, numbers, `+ - * /` and
+/// parentheses
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expression {
+    Column(String),
+    Number(f64),
+    Negate(Box<Expression>),
+    Binary(Box<Expression>, char, Box<Expression>),
+}
+
+/// A value an expression takes on one row
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Value<'a> {
+    /// A bare column keeps its cell, so `max(date)` compares cells
+    Cell(&'a str),
+    Number(f64),
+}
+
+impl Expression {
+    /// Parses an expression such as `price * qty` or `"unit price" / 100`
+    ///
+    /// Column names are letters, digits and `_`, or any text in double
+    /// quotes or backticks.
+    pub fn parse(text: &str) -> Result<Self, TableError> {
+        let mut parser = Parser {
+            text,
+            chars: text.char_indices().peekable(),
+        };
+        let expression = parser.sum()?;
+        parser.skip_whitespace();
+        match parser.chars.next() {
+            None => Ok(expression),
+            Some((_, c)) => Err(parser.error(&format!("unexpected '{}'", c))),
+        }
+    }
+
+    /// Columns the expression reads
+    fn columns(&self) -> Vec<&str> {
+        match self {
+            Expression::Column(column) => vec![column],
+            Expression::Number(_) => Vec::new(),
+            Expression::Negate(operand) => operand.columns(),
+            Expression::Binary(left, _, right) => [left.columns(), right.columns()].concat(),
+        }
+    }
+
+    /// Evaluates the expression on a row; `None` when a cell it needs is
+    /// empty
+    fn evaluate<'a>(
+        &self,
+        table: &Table,
+        row: &'a [String],
+        row_index: usize,
+    ) -> Result<Option<Value<'a>>, TableError> {
+        if let Expression::Column(column) = self {
+            let cell = &row[table
+                .column_index(column)
+                .ok_or_else(|| TableError::MissingColumn(column.clone()))?];
+            return Ok(match CellValue::parse(cell) {
+                CellValue::Empty => None,
+                _ => Some(Value::Cell(cell)),
+            });
+        }
+        self.number(table, row, row_index)
+            .map(|number| number.map(Value::Number))
+    }
+
+    fn number(
+        &self,
+        table: &Table,
+        row: &[String],
+        row_index: usize,
+    ) -> Result<Option<f64>, TableError> {
+        Ok(match self {
+            Expression::Column(column) => match self.evaluate(table, row, row_index)? {
+                None => None,
+                Some(value) => Some(as_number(value).ok_or_else(|| TableError::InvalidCell {
+                    row_index,
+                    column: column.clone(),
+                })?),
+            },
+            Expression::Number(number) => Some(*number),
+            Expression::Negate(operand) => {
+                operand.number(table, row, row_index)?.map(|number| -number)
+            }
+            Expression::Binary(left, operator, right) => {
+                let left = left.number(table, row, row_index)?;
+                let right = right.number(table, row, row_index)?;
+                left.zip(right).map(|(left, right)| match operator {
+                    '+' => left + right,
+                    '-' => left - right,
+                    '*' => left * right,
+                    _ => left / right,
+                })
+            }
+        })
+    }
+}
+
+fn as_number(value: Value) -> Option<f64> {
+    match value {
+        Value::Number(number) => Some(number),
+        Value::Cell(cell) => match CellValue::parse(cell) {
+            CellValue::Number(number) => Some(number),
+            _ => None,
+        },
+    }
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    chars: Peekable<CharIndices<'a>>,
+}
+
+impl Parser<'_> {
+    fn error(&self, reason: &str) -> TableError {
+        TableError::InvalidArgument(format!("invalid expression '{}': {}", self.text, reason))
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.chars.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+    }
+
+    fn next_operator(&mut self, operators: &[char]) -> Option<char> {
+        self.skip_whitespace();
+        self.chars
+            .next_if(|(_, c)| operators.contains(c))
+            .map(|(_, c)| c)
+    }
+
+    fn sum(&mut self) -> Result<Expression, TableError> {
+        let mut expression = self.product()?;
+        while let Some(operator) = self.next_operator(&['+', '-']) {
+            expression =
+                Expression::Binary(Box::new(expression), operator, Box::new(self.product()?));
+        }
+        Ok(expression)
+    }
+
+    fn product(&mut self) -> Result<Expression, TableError> {
+        let mut expression = self.operand()?;
+        while let Some(operator) = self.next_operator(&['*', '/']) {
+            expression =
+                Expression::Binary(Box::new(expression), operator, Box::new(self.operand()?));
+        }
+        Ok(expression)
+    }
+
+    fn operand(&mut self) -> Result<Expression, TableError> {
+        self.skip_whitespace();
+        let Some(&(start, c)) = self.chars.peek() else {
+            return Err(self.error("unexpected end"));
+        };
+        match c {
+            '-' => {
+                self.chars.next();
+                Ok(Expression::Negate(Box::new(self.operand()?)))
+            }
+            '(' => {
+                self.chars.next();
+                let expression = self.sum()?;
+                self.next_operator(&[')'])
+                    .ok_or_else(|| self.error("missing ')'"))?;
+                Ok(expression)
+            }
+            '"' | '`' => {
+                self.chars.next();
+                let name: String = self
+                    .chars
+                    .by_ref()
+                    .map(|(_, c)| c)
+                    .take_while(|quoted| *quoted != c)
+                    .collect();
+                Ok(Expression::Column(name))
+            }
+            c if c.is_ascii_digit() || c == '.' => {
+                let end = self.take_while(|c| c.is_ascii_alphanumeric() || c == '.');
+                let literal = &self.text[start..end];
+                literal
+                    .parse()
+                    .map(Expression::Number)
+                    .map_err(|_| self.error(&format!("invalid number '{}'", literal)))
+            }
+            c if c.is_alphanumeric() || c == '_' => {
+                let end = self.take_while(|c| c.is_alphanumeric() || c == '_');
+                Ok(Expression::Column(self.text[start..end].to_string()))
+            }
+            c => Err(self.error(&format!("unexpected '{}'", c))),
+        }
+    }
+
+    /// Consumes characters while `accept` holds, returning the end offset
+    fn take_while(&mut self, accept: impl Fn(char) -> bool) -> usize {
+        while self.chars.next_if(|(_, c)| accept(*c)).is_some() {}
+        self.chars.peek().map_or(self.text.len(), |(end, _)| *end)
+    }
+}
+
+/// How an aggregate combines the values of a group
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Function {
+    Count,
+    Sum,
+    Avg,
+    Min,
+    Max,
+}
+
+/// An aggregate column such as `sum(price*qty) as revenue`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Aggregate {
+    function: Function,
+    /// `None` for `count(*)`
+    expression: Option<Expression>,
+    /// Output column name, the aggregate's text without `as`
+    name: String,
+}
+
+impl Aggregate {
+    /// Parses `function(expression)` with an optional `as name`
+    ///
+    /// Functions are `count`, `sum`, `avg`, `min` and `max`; `count(*)`
+    /// counts rows. `sum` and `avg` of a column of durations like `1h30m`
+    /// add up the durations. `min` and `max` of a bare column compare cells
+    /// like `sort` does, so they also work on dates, versions and text.
+    pub fn parse(text: &str) -> Result<Self, TableError> {
+        let invalid = |reason: &str| {
+            TableError::InvalidArgument(format!("invalid aggregate '{}': {}", text, reason))
+        };
+        let text = text.trim();
+        let (call, name) = match split_alias(text) {
+            Some((call, name)) => (call.trim(), name.trim().to_string()),
+            None => (text, text.to_string()),
+        };
+        let (function, argument) = call
+            .strip_suffix(')')
+            .and_then(|call| call.split_once('('))
+            .ok_or_else(|| invalid("expected function(expression) [as name]"))?;
+        let function = match function.trim().to_lowercase().as_str() {
+            "count" => Function::Count,
+            "sum" => Function::Sum,
+            "avg" | "mean" => Function::Avg,
+            "min" => Function::Min,
+            "max" => Function::Max,
+            other => {
+                return Err(invalid(&format!(
+                    "unknown function '{}', expected count, sum, avg, min or max",
+                    other
+                )))
+            }
+        };
+        let expression = match argument.trim() {
+            "*" if function == Function::Count => None,
+            argument => Some(Expression::parse(argument)?),
+        };
+        if name.is_empty() {
+            return Err(invalid("empty name after 'as'"));
+        }
+        Ok(Aggregate {
+            function,
+            expression,
+            name,
+        })
+    }
+
+    /// Combines the values of a group's rows into one cell; empty values
+    /// are skipped, and an aggregate of no values is empty, except counts
+    fn apply(&self, table: &Table, rows: &[usize]) -> Result<String, TableError> {
+        let Some(expression) = &self.expression else {
+            return Ok(rows.len().to_string());
+        };
+        let mut values = Vec::with_capacity(rows.len());
+        for row_index in rows {
+            if let Some(value) =
+                expression.evaluate(table, &table.rows()[*row_index], *row_index)?
+            {
+                values.push((*row_index, value));
+            }
+        }
+
+        let numbers = || {
+            values
+                .iter()
+                .map(|(row_index, value)| {
+                    as_number(*value).ok_or_else(|| TableError::InvalidCell {
+                        row_index: *row_index,
+                        column: self.name.clone(),
+                    })
+                })
+                .collect::<Result<Vec<f64>, TableError>>()
+        };
+        // `None` unless every value is a duration cell
+        let durations = || {
+            values
+                .iter()
+                .map(|(_, value)| match value {
+                    Value::Cell(cell) => match CellValue::parse(cell) {
+                        CellValue::Duration(duration) => Some(duration),
+                        _ => None,
+                    },
+                    Value::Number(_) => None,
+                })
+                .collect::<Option<Vec<Duration>>>()
+        };
+        let extreme = |wanted: Ordering| {
+            values
+                .iter()
+                .map(|(_, value)| *value)
+                .reduce(|best, value| {
+                    if compare_values(value, best) == wanted {
+                        value
+                    } else {
+                        best
+                    }
+                })
+                .map_or(String::new(), format_value)
+        };
+        Ok(match self.function {
+            Function::Count => values.len().to_string(),
+            Function::Sum if values.is_empty() => String::new(),
+            Function::Sum => match durations() {
+                Some(durations) => format_duration(durations.iter().sum()),
+                None => format_number(numbers()?.iter().sum()),
+            },
+            Function::Avg if values.is_empty() => String::new(),
+            Function::Avg => match durations() {
+                Some(durations) => format_duration(
+                    durations
+                        .iter()
+                        .sum::<Duration>()
+                        .div_f64(durations.len() as f64),
+                ),
+                None => format_number(numbers()?.iter().sum::<f64>() / values.len() as f64),
+            },
+            Function::Min => extreme(Ordering::Less),
+            Function::Max => extreme(Ordering::Greater),
+        })
+    }
+}
+
+/// Splits `call as name` at the last ` as ` outside quoted column names,
+/// so `max("a as b")` has no alias
+fn split_alias(text: &str) -> Option<(&str, &str)> {
+    let mut quote = None;
+    let mut alias = None;
+    for (index, c) in text.char_indices() {
+        match quote {
+            Some(open) if c == open => quote = None,
+            Some(_) => {}
+            None if c == '"' || c == '`' => quote = Some(c),
+            None if text[index..].starts_with(" as ") => alias = Some(index),
+            None => {}
+        }
+    }
+    alias.map(|index| (&text[..index], &text[index + " as ".len()..]))
+}
+
+fn compare_values(left: Value, right: Value) -> Ordering {
+    match (left, right) {
+        (Value::Number(left), Value::Number(right)) => left.total_cmp(&right),
+        (left, right) => compare_cells(&format_value(left), &format_value(right)),
+    }
+}
+
+fn format_value(value: Value) -> String {
+    match value {
+        Value::Cell(cell) => cell.to_string(),
+        Value::Number(number) => format_number(number),
+    }
+}
+
+/// Formats a computed number without a trailing `.0` and without `-0`
+pub fn format_number(number: f64) -> String {
+    (number + 0.0).to_string()
+}
+
+/// Groups rows by the `by` columns and computes aggregates per group
+///
+/// Groups come in order of first appearance. Without `by` columns the
+/// whole table is one group.
+///
+/// # Arguments
+/// * `table` - Table to aggregate
+/// * `by` - Columns whose values identify a group
+/// * `aggregates` - Columns to compute for each group
+///
+/// # Returns
+/// * `Table` - One row per group: the `by` columns, then the aggregates
+pub fn group_rows(
+    table: &Table,
+    by: &[String],
+    aggregates: &[Aggregate],
+) -> Result<Table, TableError> {
+    let by_indices = by
+        .iter()
+        .map(|column| {
+            table
+                .column_index(column)
+                .ok_or_else(|| TableError::MissingColumn(column.clone()))
+        })
+        .collect::<Result<Vec<usize>, TableError>>()?;
+    check_columns(table, aggregates)?;
+
+    let mut groups: Vec<(Vec<&str>, Vec<usize>)> = Vec::new();
+    let mut group_positions: HashMap<Vec<&str>, usize> = HashMap::new();
+    for (row_index, row) in table.rows().iter().enumerate() {
+        let key: Vec<&str> = by_indices
+            .iter()
+            .map(|index| row[*index].as_str())
+            .collect();
+        let position = *group_positions.entry(key.clone()).or_insert_with(|| {
+            groups.push((key, Vec::new()));
+            groups.len() - 1
+        });
+        groups[position].1.push(row_index);
+    }
+    if by.is_empty() && groups.is_empty() {
+        groups.push((Vec::new(), Vec::new()));
+    }
+
+    let mut header = by.to_vec();
+    header.extend(aggregates.iter().map(|aggregate| aggregate.name.clone()));
+    let rows = groups
+        .iter()
+        .map(|(key, rows)| {
+            let mut row: Vec<String> = key.iter().map(|cell| cell.to_string()).collect();
+            for aggregate in aggregates {
+                row.push(aggregate.apply(table, rows)?);
+            }
+            Ok(row)
+        })
+        .collect::<Result<Vec<Vec<String>>, TableError>>()?;
+    Table::with_header_and_data(header, rows)
+}
+
+/// Computes aggregates over all rows of a table for a footer row
+///
+/// Each cell reads `name = value`. An aggregate of a bare column sits
+/// under that column; the others fill the free cells from the left.
+///
+/// # Arguments
+/// * `table` - Table whose rows are aggregated
+/// * `aggregates` - Aggregates to show in the footer
+///
+/// # Returns
+/// * `Vec<String>` - One cell per column of `table`
+pub fn footer_row(table: &Table, aggregates: &[Aggregate]) -> Result<Vec<String>, TableError> {
+    check_columns(table, aggregates)?;
+    let rows: Vec<usize> = (0..table.row_count()).collect();
+    summary_row(
+        table,
+        &rows,
+        aggregates,
+        vec![String::new(); table.column_count()],
+    )
+}
+
+/// Computes a subtotal row after each run of rows sharing a value of the
+/// `by` column, and a total row
+///
+/// Runs are consecutive rows, so a table sorted by `by` gets one subtotal
+/// per value. Subtotal rows are labelled `subtotal <value>` in the `by`
+/// column and the total row `total`; aggregates are placed as in
+/// `footer_row`.
+///
+/// # Arguments
+/// * `table` - Table whose rows are aggregated
+/// * `by` - Column whose runs of equal values form the groups
+/// * `aggregates` - Aggregates to show in each subtotal row
+///
+/// # Returns
+/// * `Vec<(usize, Vec<String>)>` - Each row with the number of data rows
+///   it follows, the total row last
+pub fn subtotal_rows(
+    table: &Table,
+    by: &str,
+    aggregates: &[Aggregate],
+) -> Result<Vec<(usize, Vec<String>)>, TableError> {
+    let by_index = table
+        .column_index(by)
+        .ok_or_else(|| TableError::MissingColumn(by.to_string()))?;
+    check_columns(table, aggregates)?;
+    let labelled = |label: String| {
+        let mut row = vec![String::new(); table.column_count()];
+        row[by_index] = label;
+        row
+    };
+
+    let rows = table.rows();
+    let mut subtotals = Vec::new();
+    let mut start = 0;
+    for end in 1..=rows.len() {
+        if end == rows.len() || rows[end][by_index] != rows[start][by_index] {
+            let group: Vec<usize> = (start..end).collect();
+            let label = labelled(format!("subtotal {}", rows[start][by_index]));
+            subtotals.push((end, summary_row(table, &group, aggregates, label)?));
+            start = end;
+        }
+    }
+    let all: Vec<usize> = (0..rows.len()).collect();
+    let total = summary_row(table, &all, aggregates, labelled("total".to_string()))?;
+    subtotals.push((rows.len(), total));
+    Ok(subtotals)
+}
+
+/// Fills the empty cells of `row` with aggregates over `rows`, each
+/// reading `name = value`
+///
+/// An aggregate of a bare column goes under that column if its cell is
+/// free; the others fill the free cells from the left.
+fn summary_row(
+    table: &Table,
+    rows: &[usize],
+    aggregates: &[Aggregate],
+    mut row: Vec<String>,
+) -> Result<Vec<String>, TableError> {
+    let mut unplaced = Vec::new();
+    for aggregate in aggregates {
+        let cell = format!("{} = {}", aggregate.name, aggregate.apply(table, rows)?);
+        let column = match &aggregate.expression {
+            Some(Expression::Column(column)) => table.column_index(column),
+            _ => None,
+        };
+        match column {
+            Some(index) if row[index].is_empty() => row[index] = cell,
+            _ => unplaced.push((&aggregate.name, cell)),
+        }
+    }
+    for (name, cell) in unplaced {
+        let free = row.iter().position(String::is_empty).ok_or_else(|| {
+            TableError::InvalidArgument(format!("no free column left to show '{}' in", name))
+        })?;
+        row[free] = cell;
+    }
+    Ok(row)
+}
+
+/// Fails on the first column an aggregate reads that `table` lacks
+fn check_columns(table: &Table, aggregates: &[Aggregate]) -> Result<(), TableError> {
+    match aggregates
+        .iter()
+        .filter_map(|aggregate| aggregate.expression.as_ref())
+        .flat_map(Expression::columns)
+        .find(|column| table.column_index(column).is_none())
+    {
+        Some(missing) => Err(TableError::MissingColumn(missing.to_string())),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sales() -> Table {
+        Table::with_header_and_data(
+            ["region", "price", "qty", "date"]
+                .iter()
+                .map(|column| column.to_string())
+                .collect(),
+            [
+                ["north", "2.5", "4", "2024-03-01"],
+                ["south", "1", "3", "2024-01-15"],
+                ["north", "10", "", "2024-11-30"],
+                ["north", "1", "2", "2024-02-10"],
+            ]
+            .iter()
+            .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+            .collect(),
+        )
+        .unwrap()
+    }
+
+    fn aggregates(texts: &[&str]) -> Vec<Aggregate> {
+        texts
+            .iter()
+            .map(|text| Aggregate::parse(text).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_group_rows() {
+        let aggregates = aggregates(&[
+            "sum(price*qty) as revenue",
+            "max(date) as last_seen",
+            "count(*)",
+            "avg(-(price - 1) / 2)",
+        ]);
+
+        let grouped = group_rows(&sales(), &["region".to_string()], &aggregates).unwrap();
+        assert_eq!(
+            grouped.header(),
+            vec![
+                "region",
+                "revenue",
+                "last_seen",
+                "count(*)",
+                "avg(-(price - 1) / 2)"
+            ]
+        );
+        assert_eq!(
+            grouped.rows()[0],
+            ["north", "12", "2024-11-30", "3", "-1.75"]
+        );
+        assert_eq!(grouped.rows()[1], ["south", "3", "2024-01-15", "1", "0"]);
+    }
+
+    #[test]
+    fn test_group_rows_without_columns() {
+        let total = group_rows(&sales(), &[], &aggregates(&["sum(price*qty)"])).unwrap();
+        assert_eq!(total.rows(), [vec!["15".to_string()]]);
+    }
+
+    #[test]
+    fn test_unknown_aggregate_function() {
+        assert!(matches!(
+            Aggregate::parse("median(price)"),
+            Err(TableError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn test_unbalanced_expression() {
+        assert!(matches!(
+            Expression::parse("price * (qty"),
+            Err(TableError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn test_group_rows_needs_numbers() {
+        assert!(matches!(
+            group_rows(&sales(), &[], &aggregates(&["sum(date)"])),
+            Err(TableError::InvalidCell { row_index: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn test_group_by_unknown_column() {
+        assert!(matches!(
+            group_rows(&sales(), &["city".to_string()], &aggregates(&["count(*)"])),
+            Err(TableError::MissingColumn(column)) if column == "city"
+        ));
+    }
+
+    #[test]
+    fn test_aggregate_unknown_column() {
+        assert!(matches!(
+            group_rows(&sales(), &[], &aggregates(&["sum(cost)"])),
+            Err(TableError::MissingColumn(column)) if column == "cost"
+        ));
+    }
+
+    #[test]
+    fn test_aggregate_durations() {
+        let table = Table::with_header_and_data(
+            vec!["task".to_string(), "time".to_string()],
+            [
+                ["a", "1h 30m"],
+                ["a", "PT45M"],
+                ["b", "00:00:30"],
+                ["a", ""],
+            ]
+            .iter()
+            .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+            .collect(),
+        )
+        .unwrap();
+        let aggregates: Vec<Aggregate> = ["sum(time)", "avg(time)", "max(time)"]
+            .iter()
+            .map(|text| Aggregate::parse(text).unwrap())
+            .collect();
+
+        let grouped = group_rows(&table, &["task".to_string()], &aggregates).unwrap();
+        assert_eq!(grouped.rows()[0], ["a", "2h15m", "1h7m30s", "1h 30m"]);
+        assert_eq!(grouped.rows()[1], ["b", "30s", "30s", "00:00:30"]);
+    }
+
+    fn orders() -> Table {
+        Table::with_header_and_data(
+            vec!["item".to_string(), "amount".to_string(), "qty".to_string()],
+            [["tea", "2.5", "4"], ["cake", "4", "1"]]
+                .iter()
+                .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+                .collect(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_footer_row() {
+        assert_eq!(
+            footer_row(
+                &orders(),
+                &aggregates(&["count(*)", "sum(amount) as total", "max(qty)"])
+            )
+            .unwrap(),
+            ["count(*) = 2", "total = 6.5", "max(qty) = 4"]
+        );
+    }
+
+    #[test]
+    fn test_footer_row_too_many_aggregates() {
+        let crowded = aggregates(&["count(*)", "sum(amount)", "max(qty)", "min(qty)"]);
+        assert!(matches!(
+            footer_row(&orders(), &crowded),
+            Err(TableError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn test_footer_row_unknown_column() {
+        assert!(matches!(
+            footer_row(&orders(), &aggregates(&["sum(price)"])),
+            Err(TableError::MissingColumn(column)) if column == "price"
+        ));
+    }
+
+    fn amounts() -> Table {
+        Table::with_header_and_data(
+            vec!["region".to_string(), "amount".to_string()],
+            [
+                ["north", "1"],
+                ["north", "2"],
+                ["south", "5"],
+                ["north", "4"],
+            ]
+            .iter()
+            .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+            .collect(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_subtotal_rows() {
+        let subtotals = subtotal_rows(&amounts(), "region", &aggregates(&["sum(amount)"])).unwrap();
+        let expected: Vec<(usize, [&str; 2])> = vec![
+            (2, ["subtotal north", "sum(amount) = 3"]),
+            (3, ["subtotal south", "sum(amount) = 5"]),
+            (4, ["subtotal north", "sum(amount) = 4"]),
+            (4, ["total", "sum(amount) = 12"]),
+        ];
+        assert_eq!(
+            subtotals,
+            expected
+                .iter()
+                .map(|(end, row)| (*end, row.iter().map(|cell| cell.to_string()).collect()))
+                .collect::<Vec<(usize, Vec<String>)>>()
+        );
+    }
+
+    #[test]
+    fn test_subtotal_rows_unknown_column() {
+        assert!(matches!(
+            subtotal_rows(&amounts(), "city", &aggregates(&["sum(amount)"])),
+            Err(TableError::MissingColumn(column)) if column == "city"
+        ));
+    }
+
+    #[test]
+    fn test_alias_outside_quotes() {
+        let quoted = Aggregate::parse("sum(\"price as listed\") as total").unwrap();
+        assert_eq!(quoted.name, "total");
+        assert_eq!(
+            quoted.expression,
+            Some(Expression::Column("price as listed".to_string()))
+        );
+
+        let unaliased = Aggregate::parse("max(`a as b`)").unwrap();
+        assert_eq!(unaliased.name, "max(`a as b`)");
+        assert_eq!(
+            unaliased.expression,
+            Some(Expression::Column("a as b".to_string()))
+        );
+    }
+}
@@ -0,0 +1,148 @@
+use std::{collections::HashMap, sync::Arc};
+
+use arrow_array::{ArrayRef, BooleanArray, Float64Array, Int64Array, RecordBatch, StringArray};
+use arrow_ipc::writer::StreamWriter;
+use arrow_schema::{ArrowError, DataType, Field, Schema};
+
+use crate::explain::infer_column_type;
+use crate::table::{Table, TableError};
+
+/// Renders a table in the Arrow IPC streaming format
+///
+/// Column types follow the types `--explain` infers: integer columns
+/// become `Int64`, float columns `Float64`, boolean columns `Boolean`
+/// and everything else `Utf8`. Empty cells are nulls. The stream holds
+/// one record batch.
+///
+/// # Arguments
+/// * `table` - Table to render
+/// * `footer` - Footer cells, kept as the schema's `footer` metadata, one
+///   non-empty cell per line
+///
+/// # Returns
+/// * `Vec<u8>` - The IPC stream, schema message first
+pub fn write_arrow_stream(table: &Table, footer: Option<&[String]>) -> Result<Vec<u8>, TableError> {
+    let header = table.header();
+    let mut fields = Vec::with_capacity(table.column_count());
+    let mut columns: Vec<ArrayRef> = Vec::with_capacity(table.column_count());
+
+    for column_index in 0..table.column_count() {
+        let name = header
+            .get(column_index)
+            .map_or_else(|| format!("#{}", column_index + 1), |name| name.to_string());
+        let cells = table.rows().iter().map(|row| {
+            row.get(column_index)
+                .map(|cell| cell.trim())
+                .filter(|cell| !cell.is_empty())
+        });
+
+        let (data_type, column): (DataType, ArrayRef) = match infer_column_type(table, column_index)
+        {
+            "integer" => (
+                DataType::Int64,
+                Arc::new(
+                    cells
+                        .map(|cell| cell.and_then(|cell| cell.parse().ok()))
+                        .collect::<Int64Array>(),
+                ),
+            ),
+            "float" => (
+                DataType::Float64,
+                Arc::new(
+                    cells
+                        .map(|cell| cell.and_then(|cell| cell.parse().ok()))
+                        .collect::<Float64Array>(),
+                ),
+            ),
+            "boolean" => (
+                DataType::Boolean,
+                Arc::new(
+                    cells
+                        .map(|cell| cell.map(|cell| cell.eq_ignore_ascii_case("true")))
+                        .collect::<BooleanArray>(),
+                ),
+            ),
+            _ => (DataType::Utf8, Arc::new(cells.collect::<StringArray>())),
+        };
+        fields.push(Field::new(name, data_type, true));
+        columns.push(column);
+    }
+
+    let mut schema = Schema::new(fields);
+    if let Some(footer) = footer {
+        let cells: Vec<&str> = footer
+            .iter()
+            .map(String::as_str)
+            .filter(|cell| !cell.is_empty())
+            .collect();
+        schema = schema.with_metadata(HashMap::from([("footer".to_string(), cells.join("\n"))]));
+    }
+    let schema = Arc::new(schema);
+    let batch = RecordBatch::try_new(schema.clone(), columns).map_err(arrow_error)?;
+
+    let mut stream = StreamWriter::try_new(Vec::new(), &schema).map_err(arrow_error)?;
+    stream.write(&batch).map_err(arrow_error)?;
+    stream.into_inner().map_err(arrow_error)
+}
+
+fn arrow_error(error: ArrowError) -> TableError {
+    TableError::InvalidArgument(format!("cannot write Arrow stream: {}", error))
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow_array::cast::AsArray;
+    use arrow_array::types::Int64Type;
+    use arrow_ipc::reader::StreamReader;
+
+    use super::*;
+
+    #[test]
+    fn test_arrow_stream_round_trip() {
+        let table = Table::with_header_and_data(
+            vec!["id".to_string(), "price".to_string(), "name".to_string()],
+            vec![
+                vec!["1".to_string(), "9.5".to_string(), "tea".to_string()],
+                vec!["2".to_string(), "".to_string(), "".to_string()],
+            ],
+        )
+        .unwrap();
+
+        let stream = write_arrow_stream(&table, None).unwrap();
+        let batches: Vec<RecordBatch> = StreamReader::try_new(stream.as_slice(), None)
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        let batch = &batches[0];
+        let types: Vec<&DataType> = batch
+            .schema_ref()
+            .fields()
+            .iter()
+            .map(|field| field.data_type())
+            .collect();
+        assert_eq!(
+            types,
+            vec![&DataType::Int64, &DataType::Float64, &DataType::Utf8]
+        );
+        assert_eq!(batch.column(0).as_primitive::<Int64Type>().value(1), 2);
+        assert!(batch.column(1).is_null(1));
+        assert_eq!(batch.column(2).as_string::<i32>().value(0), "tea");
+    }
+
+    #[test]
+    fn test_arrow_stream_footer() {
+        let table = Table::with_header_and_data(
+            vec!["id".to_string(), "price".to_string()],
+            vec![vec!["1".to_string(), "9.5".to_string()]],
+        )
+        .unwrap();
+        let footer = ["count(*) = 1".to_string(), "".to_string()];
+
+        let stream = write_arrow_stream(&table, Some(&footer)).unwrap();
+        let reader = StreamReader::try_new(stream.as_slice(), None).unwrap();
+        assert_eq!(reader.schema().metadata()["footer"], "count(*) = 1");
+        let rows: usize = reader.map(|batch| batch.unwrap().num_rows()).sum();
+        assert_eq!(rows, 1);
+    }
+}
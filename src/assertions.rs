@@ -0,0 +1,223 @@
+use std::{collections::HashSet, fmt};
+
+use crate::cell_value::CellValue;
+use crate::table::{Table, TableError};
+
+/// Expectations a table is checked against by the `assert` command
+#[derive(Debug, Clone, Default)]
+pub struct TableAssertions {
+    /// Columns that must be present, in any order
+    pub columns: Vec<String>,
+    pub min_rows: Option<usize>,
+    pub max_rows: Option<usize>,
+    /// Columns that must not have empty cells
+    pub no_nulls: Vec<String>,
+}
+
+/// An expectation the table does not meet
+#[derive(Debug, Clone, PartialEq)]
+pub enum Violation {
+    MissingColumn(String),
+    TooFewRows {
+        rows: usize,
+        min: usize,
+    },
+    TooManyRows {
+        rows: usize,
+        max: usize,
+    },
+    /// `first_row` is 1-based, like row numbers in diff reports
+    EmptyCells {
+        column: String,
+        count: usize,
+        first_row: usize,
+    },
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Violation::MissingColumn(column) => write!(f, "missing column '{}'", column),
+            Violation::TooFewRows { rows, min } => {
+                write!(f, "{} rows, expected at least {}", rows, min)
+            }
+            Violation::TooManyRows { rows, max } => {
+                write!(f, "{} rows, expected at most {}", rows, max)
+            }
+            Violation::EmptyCells {
+                column,
+                count,
+                first_row,
+            } => write!(
+                f,
+                "column '{}' has {} empty cells, the first in row {}",
+                column, count, first_row
+            ),
+        }
+    }
+}
+
+impl TableAssertions {
+    /// Checks a table, returning every violation found
+    ///
+    /// A `--no-nulls` column that is missing is reported once, as missing.
+    /// Cells holding only whitespace count as empty.
+    pub fn check(&self, table: &Table) -> Vec<Violation> {
+        let mut reported = HashSet::new();
+        let mut violations: Vec<Violation> = self
+            .columns
+            .iter()
+            .chain(&self.no_nulls)
+            .filter(|column| table.column_index(column).is_none() && reported.insert(*column))
+            .map(|column| Violation::MissingColumn(column.clone()))
+            .collect();
+
+        let rows = table.row_count();
+        if let Some(min) = self.min_rows.filter(|min| rows < *min) {
+            violations.push(Violation::TooFewRows { rows, min });
+        }
+        if let Some(max) = self.max_rows.filter(|max| rows > *max) {
+            violations.push(Violation::TooManyRows { rows, max });
+        }
+
+        for column in &self.no_nulls {
+            let Some(column_index) = table.column_index(column) else {
+                continue;
+            };
+            let empty: Vec<usize> = table
+                .rows()
+                .iter()
+                .enumerate()
+                .filter(|(_, row)| {
+                    matches!(
+                        row.get(column_index).map(|cell| CellValue::parse(cell)),
+                        None | Some(CellValue::Empty)
+                    )
+                })
+                .map(|(row_index, _)| row_index)
+                .collect();
+            if let Some(first) = empty.first() {
+                violations.push(Violation::EmptyCells {
+                    column: column.clone(),
+                    count: empty.len(),
+                    first_row: first + 1,
+                });
+            }
+        }
+        violations
+    }
+}
+
+/// Parses a row count, also in scientific notation such as `1e6`
+pub fn parse_row_count(value: &str) -> Result<usize, TableError> {
+    let invalid = || {
+        TableError::InvalidArgument(format!(
+            "invalid row count '{}', expected a whole number such as 100 or 1e6",
+            value
+        ))
+    };
+    if let Ok(count) = value.parse() {
+        return Ok(count);
+    }
+    let count: f64 = value.parse().map_err(|_| invalid())?;
+    if count.is_finite() && count >= 0.0 && count.fract() == 0.0 && count <= usize::MAX as f64 {
+        Ok(count as usize)
+    } else {
+        Err(invalid())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table() -> Table {
+        Table::with_header_and_data(
+            vec!["id".to_string(), "name".to_string()],
+            vec![
+                vec!["1".to_string(), "tea".to_string()],
+                vec![" ".to_string(), "".to_string()],
+                vec!["".to_string(), "milk".to_string()],
+            ],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_met_assertions() {
+        let assertions = TableAssertions {
+            columns: vec!["name".to_string(), "id".to_string()],
+            min_rows: Some(3),
+            max_rows: Some(3),
+            no_nulls: Vec::new(),
+        };
+        assert!(assertions.check(&table()).is_empty());
+    }
+
+    #[test]
+    fn test_missing_columns_reported_once() {
+        let assertions = TableAssertions {
+            columns: vec!["amount".to_string()],
+            no_nulls: vec!["amount".to_string()],
+            ..TableAssertions::default()
+        };
+        assert_eq!(
+            assertions.check(&table()),
+            vec![Violation::MissingColumn("amount".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_row_count_bounds() {
+        let too_few = TableAssertions {
+            min_rows: Some(100),
+            ..TableAssertions::default()
+        };
+        assert_eq!(
+            too_few.check(&table()),
+            vec![Violation::TooFewRows { rows: 3, min: 100 }]
+        );
+        let too_many = TableAssertions {
+            max_rows: Some(2),
+            ..TableAssertions::default()
+        };
+        assert_eq!(
+            too_many.check(&table()),
+            vec![Violation::TooManyRows { rows: 3, max: 2 }]
+        );
+    }
+
+    #[test]
+    fn test_empty_cells() {
+        let assertions = TableAssertions {
+            no_nulls: vec!["id".to_string(), "name".to_string()],
+            ..TableAssertions::default()
+        };
+        let violations = assertions.check(&table());
+        assert_eq!(
+            violations[0],
+            Violation::EmptyCells {
+                column: "id".to_string(),
+                count: 2,
+                first_row: 2
+            }
+        );
+        assert_eq!(
+            violations[1].to_string(),
+            "column 'name' has 1 empty cells, the first in row 2"
+        );
+    }
+
+    #[test]
+    fn test_parse_row_count() {
+        assert_eq!(parse_row_count("100").unwrap(), 100);
+        assert_eq!(parse_row_count("1e2").unwrap(), 100);
+    }
+
+    #[test]
+    fn test_invalid_row_counts() {
+        for invalid in ["1.5", "-1", "many", "1e400"] {
+            assert!(parse_row_count(invalid).is_err(), "{}", invalid);
+        }
+    }
+}
@@ -0,0 +1,173 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process,
+    sync::Mutex,
+};
+
+/// Temp files being written, removed by `remove_staged` on Ctrl-C
+static STAGED: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+/// Writes files so readers see either the old or the new contents
+///
+/// Contents go to a temp file that is fsynced and then renamed over the
+/// target, so an interrupted run never leaves a truncated file behind.
+/// Temp files are named `.<target name>.<pid>.tmp`, so leftovers from a
+/// killed run can be traced back to their target. Temp files still being
+/// written are removed on Ctrl-C, see `remove_staged`.
+#[derive(Debug, Default, Clone)]
+pub struct AtomicWriter {
+    temp_dir: Option<PathBuf>,
+}
+
+impl AtomicWriter {
+    /// Creates a writer staging temp files in `temp_dir`, or next to each
+    /// target when `None`
+    pub fn new(temp_dir: Option<PathBuf>) -> Self {
+        AtomicWriter { temp_dir }
+    }
+
+    /// Replaces the contents of a file atomically
+    ///
+    /// A temp dir on another filesystem can't be renamed from, so the
+    /// staged file is then copied next to the target and renamed from
+    /// there. An existing target keeps its permissions.
+    ///
+    /// # Arguments
+    /// * `path` - File to create or replace
+    /// * `contents` - New contents
+    pub fn write(&self, path: &Path, contents: impl AsRef<[u8]>) -> io::Result<()> {
+        let target_dir = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        let stage_dir = self.temp_dir.as_deref().unwrap_or(target_dir);
+
+        let staged = temp_path(stage_dir, path);
+        track(&staged);
+        let result = stage(&staged, path, contents.as_ref()).and_then(|()| {
+            match fs::rename(&staged, path) {
+                Err(error)
+                    if self.temp_dir.is_some() && error.kind() == io::ErrorKind::CrossesDevices =>
+                {
+                    let sibling = temp_path(target_dir, path);
+                    track(&sibling);
+                    let result = fs::copy(&staged, &sibling)
+                        .and_then(|_| File::open(&sibling)?.sync_all())
+                        .and_then(|()| fs::rename(&sibling, path));
+                    if result.is_err() {
+                        let _ = fs::remove_file(&sibling);
+                    }
+                    untrack(&sibling);
+                    result
+                }
+                result => result,
+            }
+        });
+        if result.is_err() || staged.exists() {
+            let _ = fs::remove_file(&staged);
+        }
+        untrack(&staged);
+        result?;
+
+        // Persist the rename itself; not every platform can sync directories.
+        if let Ok(dir) = File::open(target_dir) {
+            let _ = dir.sync_all();
+        }
+        Ok(())
+    }
+}
+
+/// Writes contents to a fresh temp file and flushes it to disk.
+fn stage(staged: &Path, target: &Path, contents: &[u8]) -> io::Result<()> {
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(staged)?;
+    if let Ok(metadata) = fs::metadata(target) {
+        file.set_permissions(metadata.permissions())?;
+    }
+    file.write_all(contents)?;
+    file.sync_all()
+}
+
+/// Removes the temp files of writes still in progress
+///
+/// Called from the Ctrl-C handler, so an interrupted run leaves neither
+/// a truncated target nor a stray temp file behind.
+pub fn remove_staged() {
+    let mut staged = STAGED.lock().unwrap_or_else(|error| error.into_inner());
+    for path in staged.drain(..) {
+        let _ = fs::remove_file(path);
+    }
+}
+
+fn track(staged: &Path) {
+    if let Ok(mut tracked) = STAGED.lock() {
+        tracked.push(staged.to_path_buf());
+    }
+}
+
+fn untrack(staged: &Path) {
+    if let Ok(mut tracked) = STAGED.lock() {
+        tracked.retain(|path| path != staged);
+    }
+}
+
+fn temp_path(dir: &Path, target: &Path) -> PathBuf {
+    let name = target
+        .file_name()
+        .map_or("output".into(), |name| name.to_string_lossy());
+    dir.join(format!(".{}.{}.tmp", name, process::id()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // `remove_staged` would remove the temp files of a write running in
+    // parallel
+    static SERIAL: Mutex<()> = Mutex::new(());
+
+    #[test]
+    fn test_write_replaces_contents_without_leftovers() {
+        let _serial = SERIAL.lock().unwrap();
+        let dir = std::env::temp_dir().join(format!("tables-atomic-{}", process::id()));
+        let temp_dir = dir.join("staging");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let target = dir.join("out.csv");
+
+        AtomicWriter::default().write(&target, "a\n1\n").unwrap();
+        AtomicWriter::new(Some(temp_dir.clone()))
+            .write(&target, "a\n2\n")
+            .unwrap();
+
+        assert_eq!(fs::read_to_string(&target).unwrap(), "a\n2\n");
+        let mut entries: Vec<_> = fs::read_dir(&dir)
+            .unwrap()
+            .chain(fs::read_dir(&temp_dir).unwrap())
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        entries.sort();
+        assert_eq!(entries, vec!["out.csv", "staging"]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_remove_staged_cleans_up_aborted_write() {
+        let _serial = SERIAL.lock().unwrap();
+        let dir = std::env::temp_dir().join(format!("tables-abort-{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let staged = temp_path(&dir, &dir.join("out.csv"));
+        track(&staged);
+        fs::write(&staged, "a\n").unwrap();
+
+        remove_staged();
+
+        assert!(!staged.exists());
+        assert!(STAGED.lock().unwrap().is_empty());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,145 @@
+use std::{
+    fs::OpenOptions,
+    io::{self, Write},
+    path::Path,
+};
+
+use chrono::{SecondsFormat, Utc};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+/// What one run read and wrote, appended to the `--audit-log` file
+#[derive(Debug, Serialize)]
+pub struct AuditRecord {
+    /// Start of the run, UTC, RFC 3339
+    pub timestamp: String,
+    pub command: String,
+    pub inputs: Vec<AuditInput>,
+    pub rows_in: usize,
+    pub rows_out: usize,
+    pub outputs: Vec<AuditOutput>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AuditInput {
+    pub path: String,
+    pub rows: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AuditOutput {
+    /// Written file, `-` for stdout
+    pub path: String,
+    pub sha256: String,
+}
+
+impl AuditRecord {
+    /// Starts the record of a run of `command`
+    pub fn new(command: &str) -> Self {
+        AuditRecord {
+            timestamp: Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+            command: command.to_string(),
+            inputs: Vec::new(),
+            rows_in: 0,
+            rows_out: 0,
+            outputs: Vec::new(),
+        }
+    }
+
+    /// Records a loaded table
+    pub fn add_input(&mut self, path: &Path, rows: usize) {
+        self.inputs.push(AuditInput {
+            path: path.display().to_string(),
+            rows,
+        });
+        self.rows_in += rows;
+    }
+
+    /// Records written output by its SHA-256, `None` meaning stdout
+    pub fn add_output(&mut self, path: Option<&Path>, contents: &[u8]) {
+        let sha256 = Sha256::digest(contents)
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect();
+        self.outputs.push(AuditOutput {
+            path: path.map_or("-".to_string(), |path| path.display().to_string()),
+            sha256,
+        });
+    }
+
+    /// Appends the record as one JSON line, creating the log if needed
+    ///
+    /// Earlier records are never rewritten; the line goes out in a single
+    /// write so concurrent runs don't interleave.
+    pub fn append_to(&self, log: &Path) -> io::Result<()> {
+        let mut line = serde_json::to_vec(self)?;
+        line.push(b'\n');
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(log)?
+            .write_all(&line)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use serde_json::Value;
+
+    use super::*;
+
+    #[test]
+    fn test_inputs_add_up() {
+        let mut record = AuditRecord::new("convert");
+        record.add_input(Path::new("a.csv"), 3);
+        record.add_input(Path::new("b.csv"), 2);
+        assert_eq!(record.rows_in, 5);
+        assert_eq!(record.inputs[1].path, "b.csv");
+    }
+
+    #[test]
+    fn test_outputs_by_digest() {
+        let mut record = AuditRecord::new("convert");
+        record.add_output(None, b"");
+        record.add_output(Some(Path::new("out.csv")), b"");
+        assert_eq!(record.outputs[0].path, "-");
+        assert_eq!(record.outputs[1].path, "out.csv");
+        assert_eq!(
+            record.outputs[0].sha256,
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+
+    #[test]
+    fn test_append_records() {
+        let log = std::env::temp_dir().join(format!("tables-audit-{}.jsonl", std::process::id()));
+        let _ = fs::remove_file(&log);
+
+        let mut record = AuditRecord::new("convert");
+        record.rows_out = 5;
+        record.append_to(&log).unwrap();
+        AuditRecord::new("diff").append_to(&log).unwrap();
+
+        let contents = fs::read_to_string(&log).unwrap();
+        fs::remove_file(&log).unwrap();
+        let records: Vec<Value> = contents
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0]["command"], "convert");
+        assert_eq!(records[0]["rows_out"], 5);
+        assert_eq!(records[1]["command"], "diff");
+    }
+
+    #[test]
+    fn test_append_to_missing_directory() {
+        let log = std::env::temp_dir()
+            .join(format!("tables-audit-missing-{}", std::process::id()))
+            .join("audit.jsonl");
+        assert!(AuditRecord::new("diff").append_to(&log).is_err());
+    }
+}
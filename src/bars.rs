@@ -0,0 +1,195 @@
+use crate::cell_value::CellValue;
+use crate::table::{Table, TableError};
+
+/// Width of a full bar, in characters
+const BAR_WIDTH: usize = 20;
+
+/// Blocks filling one character by eighths, from one eighth to full
+const BAR_BLOCKS: [char; 8] = ['▏', '▎', '▍', '▌', '▋', '▊', '▉', '█'];
+
+/// Sparkline levels, from lowest to highest
+const SPARK_BLOCKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Appends a column of bars proportional to a numeric column's values
+///
+/// The largest magnitude fills `BAR_WIDTH` characters, and bars show the
+/// magnitude of negative values too. Empty cells get an empty bar.
+///
+/// # Arguments
+/// * `table` - Table to extend
+/// * `column` - Numeric column to draw
+///
+/// # Returns
+/// * `Table` - The table with a `<column> bar` column appended
+pub fn add_bar(table: &Table, column: &str) -> Result<Table, TableError> {
+    let column_index = table
+        .column_index(column)
+        .ok_or_else(|| TableError::MissingColumn(column.to_string()))?;
+    let values = table
+        .rows()
+        .iter()
+        .enumerate()
+        .map(|(row_index, row)| number(row, row_index, column_index, column))
+        .collect::<Result<Vec<Option<f64>>, TableError>>()?;
+    let largest = values
+        .iter()
+        .flatten()
+        .fold(0.0, |largest: f64, value| largest.max(value.abs()));
+
+    let bars = values
+        .into_iter()
+        .map(|value| match value {
+            Some(value) if largest > 0.0 => bar(value.abs() / largest),
+            _ => String::new(),
+        })
+        .collect();
+    table.append_column(&format!("{} bar", column), bars)
+}
+
+/// Appends a column of sparklines, one per row, over numeric columns
+///
+/// Each sparkline has one character per column, scaled between the row's
+/// smallest and largest value. Empty cells show as spaces.
+///
+/// # Arguments
+/// * `table` - Table to extend
+/// * `columns` - Numeric columns to draw, in order
+///
+/// # Returns
+/// * `Table` - The table with a `spark` column appended
+pub fn add_sparkline(table: &Table, columns: &[String]) -> Result<Table, TableError> {
+    let column_indices = columns
+        .iter()
+        .map(|column| {
+            table
+                .column_index(column)
+                .ok_or_else(|| TableError::MissingColumn(column.clone()))
+        })
+        .collect::<Result<Vec<usize>, TableError>>()?;
+
+    let sparklines = table
+        .rows()
+        .iter()
+        .enumerate()
+        .map(|(row_index, row)| {
+            let values = column_indices
+                .iter()
+                .zip(columns)
+                .map(|(index, column)| number(row, row_index, *index, column))
+                .collect::<Result<Vec<Option<f64>>, TableError>>()?;
+            Ok(sparkline(&values))
+        })
+        .collect::<Result<Vec<String>, TableError>>()?;
+    table.append_column("spark", sparklines)
+}
+
+/// The number in a cell, or `None` if it's empty
+fn number(
+    row: &[String],
+    row_index: usize,
+    column_index: usize,
+    column: &str,
+) -> Result<Option<f64>, TableError> {
+    match CellValue::parse(&row[column_index]) {
+        CellValue::Empty => Ok(None),
+        CellValue::Number(number) => Ok(Some(number)),
+        _ => Err(TableError::InvalidCell {
+            row_index,
+            column: column.to_string(),
+        }),
+    }
+}
+
+/// A bar filling `fraction` of `BAR_WIDTH`, to the nearest eighth
+fn bar(fraction: f64) -> String {
+    let eighths = (fraction * (BAR_WIDTH * 8) as f64).round() as usize;
+    let mut bar = BAR_BLOCKS[7].to_string().repeat(eighths / 8);
+    if !eighths.is_multiple_of(8) {
+        bar.push(BAR_BLOCKS[eighths % 8 - 1]);
+    }
+    bar
+}
+
+/// A sparkline of values, with a space for each missing one
+///
+/// A row whose values are all equal draws a flat line at the middle.
+fn sparkline(values: &[Option<f64>]) -> String {
+    let present = values.iter().flatten();
+    let lowest = present
+        .clone()
+        .fold(f64::INFINITY, |lowest, value| lowest.min(*value));
+    let highest = present.fold(f64::NEG_INFINITY, |highest, value| highest.max(*value));
+    values
+        .iter()
+        .map(|value| match value {
+            None => ' ',
+            Some(_) if highest == lowest => SPARK_BLOCKS[3],
+            Some(value) => {
+                let level = (value - lowest) / (highest - lowest) * 7.0;
+                SPARK_BLOCKS[level.round() as usize]
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table() -> Table {
+        Table::with_header_and_data(
+            vec!["name".to_string(), "q1".to_string(), "q2".to_string()],
+            vec![
+                vec!["a".to_string(), "10".to_string(), "-5".to_string()],
+                vec!["b".to_string(), "5".to_string(), "".to_string()],
+                vec!["c".to_string(), "".to_string(), "7".to_string()],
+            ],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_bars() {
+        let table = add_bar(&table(), "q1").unwrap();
+        assert_eq!(table.header()[3], "q1 bar");
+        assert_eq!(table.rows()[0][3], "█".repeat(BAR_WIDTH));
+        assert_eq!(table.rows()[1][3], "█".repeat(BAR_WIDTH / 2));
+        assert_eq!(table.rows()[2][3], "");
+    }
+
+    #[test]
+    fn test_bar_eighths() {
+        assert_eq!(bar(0.0), "");
+        assert_eq!(bar(1.0 / 160.0), "▏");
+        assert_eq!(bar(0.1 + 4.0 / 160.0), "██▌");
+    }
+
+    #[test]
+    fn test_bar_of_negative_values() {
+        let table = add_bar(&table(), "q2").unwrap();
+        assert_eq!(table.rows()[0][3].chars().count(), 15);
+        assert_eq!(table.rows()[2][3], "█".repeat(BAR_WIDTH));
+    }
+
+    #[test]
+    fn test_sparklines() {
+        let columns = vec!["q1".to_string(), "q2".to_string()];
+        let table = add_sparkline(&table(), &columns).unwrap();
+        assert_eq!(table.header()[3], "spark");
+        assert_eq!(table.rows()[0][3], "█▁");
+        assert_eq!(table.rows()[1][3], "▄ ");
+        assert_eq!(table.rows()[2][3], " ▄");
+    }
+
+    #[test]
+    fn test_bars_need_numbers() {
+        assert!(matches!(
+            add_bar(&table(), "name"),
+            Err(TableError::InvalidCell { row_index: 0, .. })
+        ));
+        assert!(matches!(
+            add_sparkline(&table(), &["q1".to_string(), "q3".to_string()]),
+            Err(TableError::MissingColumn(column)) if column == "q3"
+        ));
+    }
+}
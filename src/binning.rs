@@ -0,0 +1,167 @@
+use crate::cell_value::CellValue;
+use crate::table::{Table, TableError};
+
+/// How a numeric column is cut into buckets
+#[derive(Debug, Clone, PartialEq)]
+pub enum Bins {
+    /// Intervals between increasing edges, each including its lower edge,
+    /// the last also its upper edge
+    Edges(Vec<f64>),
+    /// This many buckets of about equal row counts, `Q1` holding the
+    /// smallest values
+    Quantiles(usize),
+}
+
+/// Appends the bucket of each row's value
+///
+/// Edge buckets are labelled like `[18, 35)`, quantile buckets `Q1`,
+/// `Q2`, and so on; equal values always share a quantile bucket. Empty
+/// cells and values outside all edges get an empty label.
+///
+/// # Arguments
+/// * `table` - Table to extend
+/// * `column` - Numeric column to bin
+/// * `bins` - Edges or number of quantiles
+/// * `into` - Name of the label column
+///
+/// # Returns
+/// * `Table` - The table with the label column appended
+pub fn add_bins(table: &Table, column: &str, bins: &Bins, into: &str) -> Result<Table, TableError> {
+    let column_index = table
+        .column_index(column)
+        .ok_or_else(|| TableError::MissingColumn(column.to_string()))?;
+    let values = table
+        .rows()
+        .iter()
+        .enumerate()
+        .map(
+            |(row_index, row)| match CellValue::parse(&row[column_index]) {
+                CellValue::Empty => Ok(None),
+                CellValue::Number(number) => Ok(Some(number)),
+                _ => Err(TableError::InvalidCell {
+                    row_index,
+                    column: column.to_string(),
+                }),
+            },
+        )
+        .collect::<Result<Vec<Option<f64>>, TableError>>()?;
+
+    let labels: Vec<String> = match bins {
+        Bins::Edges(edges) => {
+            if edges.len() < 2 || edges.windows(2).any(|pair| pair[0] >= pair[1]) {
+                return Err(TableError::InvalidArgument(
+                    "bin edges must be at least two increasing numbers".to_string(),
+                ));
+            }
+            values
+                .iter()
+                .map(|value| value.map_or(String::new(), |value| edge_label(edges, value)))
+                .collect()
+        }
+        Bins::Quantiles(count) => {
+            let mut sorted: Vec<f64> = values.iter().flatten().copied().collect();
+            sorted.sort_by(f64::total_cmp);
+            values
+                .iter()
+                .map(|value| {
+                    value.map_or(String::new(), |value| {
+                        let below = sorted.partition_point(|other| *other < value);
+                        format!("Q{}", below * count / sorted.len() + 1)
+                    })
+                })
+                .collect()
+        }
+    };
+
+    table.append_column(into, labels)
+}
+
+fn edge_label(edges: &[f64], value: f64) -> String {
+    let last = edges.len() - 2;
+    let bucket = edges.windows(2).enumerate().position(|(bucket, pair)| {
+        pair[0] <= value && (value < pair[1] || bucket == last && value == pair[1])
+    });
+    match bucket {
+        Some(bucket) if bucket == last => format!("[{}, {}]", edges[bucket], edges[bucket + 1]),
+        Some(bucket) => format!("[{}, {})", edges[bucket], edges[bucket + 1]),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ages() -> Table {
+        Table::with_header_and_data(
+            vec!["age".to_string()],
+            ["5", "18", "40", "", "120", "130", "40", "70"]
+                .iter()
+                .map(|age| vec![age.to_string()])
+                .collect(),
+        )
+        .unwrap()
+    }
+
+    fn labels(bins: &Bins) -> Vec<String> {
+        add_bins(&ages(), "age", bins, "group")
+            .unwrap()
+            .rows()
+            .iter()
+            .map(|row| row[1].clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_edge_bins() {
+        assert_eq!(
+            labels(&Bins::Edges(vec![0.0, 18.0, 35.0, 60.0, 120.0])),
+            [
+                "[0, 18)",
+                "[18, 35)",
+                "[35, 60)",
+                "",
+                "[60, 120]",
+                "",
+                "[35, 60)",
+                "[60, 120]"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_quantile_bins() {
+        assert_eq!(
+            labels(&Bins::Quantiles(4)),
+            ["Q1", "Q1", "Q2", "", "Q3", "Q4", "Q2", "Q3"]
+        );
+    }
+
+    #[test]
+    fn test_decreasing_edges() {
+        assert!(matches!(
+            add_bins(&ages(), "age", &Bins::Edges(vec![10.0, 5.0]), "group"),
+            Err(TableError::InvalidArgument(_))
+        ));
+        assert!(add_bins(&ages(), "age", &Bins::Edges(vec![10.0]), "group").is_err());
+    }
+
+    #[test]
+    fn test_bins_need_numbers() {
+        let table =
+            Table::with_header_and_data(vec!["age".to_string()], vec![vec!["old".to_string()]])
+                .unwrap();
+        assert!(matches!(
+            add_bins(&table, "age", &Bins::Quantiles(2), "group"),
+            Err(TableError::InvalidCell { row_index: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn test_bins_unknown_column() {
+        assert!(matches!(
+            add_bins(&ages(), "height", &Bins::Quantiles(2), "group"),
+            Err(TableError::MissingColumn(column)) if column == "height"
+        ));
+    }
+}
@@ -0,0 +1,129 @@
+use std::cmp::Ordering;
+
+use crate::table::{Table, TableError};
+
+/// Brings a table into a deterministic canonical form
+///
+/// Columns are ordered alphabetically, cells are stripped of trailing
+/// whitespace and rows are sorted by the key columns, with the remaining
+/// columns as tie-breakers. Headerless tables keep their column order.
+/// Rendering the result with `write_csv` yields byte-stable output.
+///
+/// # Arguments
+/// * `table` - Table to canonicalize
+/// * `key_columns` - Columns to sort rows by, in priority order
+///
+/// # Returns
+/// * `Table` - A new table in canonical form
+pub fn canonicalize(table: &Table, key_columns: &[String]) -> Result<Table, TableError> {
+    let header = table.header();
+
+    let mut column_order: Vec<usize> = (0..table.column_count()).collect();
+    if !header.is_empty() {
+        column_order.sort_by_key(|index| header[*index]);
+    }
+
+    let key_order: Vec<usize> = key_columns
+        .iter()
+        .map(|column| {
+            let index = table
+                .column_index(column)
+                .ok_or_else(|| TableError::MissingColumn(column.clone()))?;
+            Ok(column_order.iter().position(|i| *i == index).unwrap())
+        })
+        .collect::<Result<_, TableError>>()?;
+
+    let mut rows: Vec<Vec<String>> = table
+        .rows()
+        .iter()
+        .map(|row| {
+            column_order
+                .iter()
+                .map(|index| {
+                    row.get(*index)
+                        .map_or("", |cell| cell.trim_end())
+                        .to_string()
+                })
+                .collect()
+        })
+        .collect();
+
+    rows.sort_by(|left, right| {
+        key_order
+            .iter()
+            .map(|index| left[*index].cmp(&right[*index]))
+            .find(|ordering| *ordering != Ordering::Equal)
+            .unwrap_or_else(|| left.cmp(right))
+    });
+
+    if header.is_empty() {
+        return Table::with_data(rows);
+    }
+
+    let header = column_order
+        .iter()
+        .map(|index| header[*index].to_string())
+        .collect();
+    Table::with_header_and_data(header, rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::table_writer::write_csv;
+
+    fn strings(cells: &[&str]) -> Vec<String> {
+        cells.iter().map(|c| c.to_string()).collect()
+    }
+
+    fn table() -> Table {
+        Table::with_header_and_data(
+            strings(&["name", "id", "city"]),
+            vec![
+                strings(&["bob", "2", "Oslo  "]),
+                strings(&["alice", "1", "Paris, FR"]),
+                strings(&["carol", "1", "Berlin"]),
+            ],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_canonicalize_orders_columns_and_rows() {
+        let canonical = canonicalize(&table(), &["id".to_string()]).unwrap();
+
+        assert_eq!(
+            write_csv(&canonical),
+            "city,id,name\nBerlin,1,carol\n\"Paris, FR\",1,alice\nOslo,2,bob\n"
+        );
+    }
+
+    #[test]
+    fn test_rows_sorted_by_all_columns_without_keys() {
+        let canonical = canonicalize(&table(), &[]).unwrap();
+        assert_eq!(canonical.rows()[0], strings(&["Berlin", "1", "carol"]));
+        assert_eq!(canonical.rows()[2], strings(&["Paris, FR", "1", "alice"]));
+    }
+
+    #[test]
+    fn test_trailing_whitespace_stripped() {
+        let canonical = canonicalize(&table(), &[]).unwrap();
+        assert_eq!(canonical.get_value(1, "city").unwrap(), "Oslo");
+    }
+
+    #[test]
+    fn test_headerless_keeps_column_order() {
+        let table = Table::with_data(vec![strings(&["b", "1"]), strings(&["a", "2"])]).unwrap();
+        let canonical = canonicalize(&table, &[]).unwrap();
+        assert!(canonical.header().is_empty());
+        assert_eq!(canonical.rows()[0], strings(&["a", "2"]));
+    }
+
+    #[test]
+    fn test_unknown_key_column() {
+        assert!(matches!(
+            canonicalize(&table(), &["country".to_string()]),
+            Err(TableError::MissingColumn(column)) if column == "country"
+        ));
+    }
+}
@@ -0,0 +1,305 @@
+use std::{cmp::Ordering, net::IpAddr, sync::LazyLock, time::Duration};
+
+use regex::Regex;
+use semver::Version;
+
+// One part of a duration like `1h 23m`, e.g. `1h` or `1.5 h`
+static DURATION_PART: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(\d+(?:\.\d+)?)\s*(ms|d|h|m|s)\s*").unwrap());
+// ISO 8601 durations without years and months, e.g. `PT90M` or `P1DT2H`
+static ISO_DURATION: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(&format!(
+        r"^P(?:{n}W)?(?:{n}D)?(?:T(?:{n}H)?(?:{n}M)?(?:{n}S)?)?$",
+        n = r"(\d+(?:\.\d+)?)"
+    ))
+    .unwrap()
+});
+// `01:30:00` or `1:30:00.250`, hours first
+static CLOCK_DURATION: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(\d+):([0-5]\d):([0-5]\d(?:\.\d+)?)$").unwrap());
+
+/// A cell read as the most specific type it parses as
+#[derive(Debug, Clone, PartialEq)]
+pub enum CellValue<'a> {
+    Empty,
+    Number(f64),
+    Duration(Duration),
+    Ip(IpAddr),
+    Version(Version),
+    Text(&'a str),
+}
+
+impl<'a> CellValue<'a> {
+    /// Reads a cell, trying number, duration, IPv4/IPv6 address, semantic
+    /// version (with an optional `v` prefix), then text
+    pub fn parse(cell: &'a str) -> Self {
+        let cell = cell.trim();
+        if cell.is_empty() {
+            return CellValue::Empty;
+        }
+        if let Ok(number) = cell.parse::<f64>() {
+            // Rust also parses "inf" and "NaN", which are words in tables.
+            if number.is_finite() {
+                return CellValue::Number(number);
+            }
+        }
+        if let Some(duration) = parse_duration(cell) {
+            return CellValue::Duration(duration);
+        }
+        if let Ok(ip) = cell.parse::<IpAddr>() {
+            return CellValue::Ip(ip);
+        }
+        if let Ok(version) = Version::parse(cell.strip_prefix('v').unwrap_or(cell)) {
+            return CellValue::Version(version);
+        }
+        CellValue::Text(cell)
+    }
+
+    /// Reads a cell and a value it is tested against, e.g. by `--where`
+    ///
+    /// A number tested against a version, or a version against a number,
+    /// is read as a version too, so `2.0` and `2` stand for `2.0.0`. Two
+    /// number-like cells stay numbers, so `1.10` is still below `1.9`.
+    /// Sorting keeps to `compare_cells`, since mixing both readings would
+    /// not be a total order.
+    pub fn parse_pair(left: &'a str, right: &'a str) -> (Self, Self) {
+        let (left_value, right_value) = (CellValue::parse(left), CellValue::parse(right));
+        match (&left_value, &right_value) {
+            (CellValue::Number(_), CellValue::Version(_)) => match partial_version(left) {
+                Some(version) => (CellValue::Version(version), right_value),
+                None => (left_value, right_value),
+            },
+            (CellValue::Version(_), CellValue::Number(_)) => match partial_version(right) {
+                Some(version) => (left_value, CellValue::Version(version)),
+                None => (left_value, right_value),
+            },
+            _ => (left_value, right_value),
+        }
+    }
+
+    /// Compares two values of the same type, or returns `None`
+    pub fn compare(&self, other: &CellValue) -> Option<Ordering> {
+        match (self, other) {
+            (CellValue::Empty, CellValue::Empty) => Some(Ordering::Equal),
+            (CellValue::Number(left), CellValue::Number(right)) => Some(left.total_cmp(right)),
+            (CellValue::Duration(left), CellValue::Duration(right)) => Some(left.cmp(right)),
+            (CellValue::Ip(left), CellValue::Ip(right)) => Some(left.cmp(right)),
+            (CellValue::Version(left), CellValue::Version(right)) => {
+                Some(left.cmp_precedence(right))
+            }
+            (CellValue::Text(left), CellValue::Text(right)) => Some(left.cmp(right)),
+            _ => None,
+        }
+    }
+
+    fn rank(&self) -> u8 {
+        match self {
+            CellValue::Empty => 0,
+            CellValue::Number(_) => 1,
+            CellValue::Duration(_) => 2,
+            CellValue::Ip(_) => 3,
+            CellValue::Version(_) => 4,
+            CellValue::Text(_) => 5,
+        }
+    }
+}
+
+/// Orders two cells by their typed values
+///
+/// Numbers compare numerically, durations by length, IP addresses by
+/// address with IPv4 before IPv6, and semantic versions by precedence, so
+/// `90s` sorts before `1h`, `10.0.0.9` before `10.0.0.10` and `1.2.9`
+/// before `1.2.10`. Cells of different types are ordered empty, number,
+/// duration, IP address, version, text, which keeps the order total for
+/// mixed columns. Numbers therefore sort before every
+/// version, including two-part versions like `1.10`, which are numbers.
+pub fn compare_cells(left: &str, right: &str) -> Ordering {
+    let (left, right) = (CellValue::parse(left), CellValue::parse(right));
+    left.compare(&right)
+        .unwrap_or_else(|| left.rank().cmp(&right.rank()))
+}
+
+/// Reads a duration written with units (`1h 23m`, `450ms`), in ISO 8601
+/// (`PT90M`, `P1DT2H`) or as a clock (`01:30:00`)
+///
+/// A bare number is not a duration, since its unit is unknown.
+pub fn parse_duration(cell: &str) -> Option<Duration> {
+    let cell = cell.trim();
+    let seconds = if let Some(captures) = ISO_DURATION.captures(cell) {
+        sum_parts(&captures, &[604_800.0, 86_400.0, 3_600.0, 60.0, 1.0])?
+    } else if let Some(captures) = CLOCK_DURATION.captures(cell) {
+        sum_parts(&captures, &[3_600.0, 60.0, 1.0])?
+    } else {
+        // Units must come largest first, each at most once.
+        const UNITS: [(&str, f64); 5] = [
+            ("d", 86_400.0),
+            ("h", 3_600.0),
+            ("m", 60.0),
+            ("s", 1.0),
+            ("ms", 0.001),
+        ];
+        let (mut seconds, mut end, mut next_unit) = (0.0, 0, 0);
+        for captures in DURATION_PART.captures_iter(cell) {
+            let part = captures.get(0)?;
+            let unit = UNITS.iter().position(|(unit, _)| *unit == &captures[2])?;
+            if part.start() != end || unit < next_unit {
+                return None;
+            }
+            seconds += captures[1].parse::<f64>().ok()? * UNITS[unit].1;
+            (end, next_unit) = (part.end(), unit + 1);
+        }
+        if end == 0 || end != cell.len() {
+            return None;
+        }
+        seconds
+    };
+    Duration::try_from_secs_f64(seconds).ok()
+}
+
+/// Formats a duration with units, e.g. `1h30m`, `2d4h`, `1m2.5s` or
+/// `450ms`, which `parse_duration` reads back
+pub fn format_duration(duration: Duration) -> String {
+    if duration < Duration::from_secs(1) {
+        let millis = (duration.as_secs_f64() * 1e6).round() / 1e3;
+        return format!("{}ms", millis);
+    }
+    let seconds = duration.as_secs();
+    let mut text: String = [
+        (seconds / 86_400, "d"),
+        (seconds / 3_600 % 24, "h"),
+        (seconds / 60 % 60, "m"),
+    ]
+    .iter()
+    .filter(|(count, _)| *count > 0)
+    .map(|(count, unit)| format!("{}{}", count, unit))
+    .collect();
+    let rest = (seconds % 60) as f64 + f64::from(duration.subsec_millis()) / 1000.0;
+    if rest > 0.0 {
+        text.push_str(&format!("{}s", rest));
+    }
+    text
+}
+
+/// Adds up the captured parts of a duration, each times its unit in
+/// seconds; `None` when no part is given
+fn sum_parts(captures: &regex::Captures, units: &[f64]) -> Option<f64> {
+    let mut parts = captures
+        .iter()
+        .skip(1)
+        .zip(units)
+        .filter_map(|(part, unit)| Some((part?, unit)))
+        .peekable();
+    parts.peek()?;
+    parts
+        .map(|(part, unit)| part.as_str().parse::<f64>().ok().map(|part| part * unit))
+        .sum()
+}
+
+/// Reads a number of one to three dot-separated parts, like `2` or `2.0`,
+/// as a version padded with zeros
+fn partial_version(cell: &str) -> Option<Version> {
+    let cell = cell.trim();
+    let parts = cell
+        .strip_prefix('v')
+        .unwrap_or(cell)
+        .split('.')
+        .map(|part| part.parse::<u64>().ok())
+        .collect::<Option<Vec<u64>>>()?;
+    match parts[..] {
+        [major] => Some(Version::new(major, 0, 0)),
+        [major, minor] => Some(Version::new(major, minor, 0)),
+        [major, minor, patch] => Some(Version::new(major, minor, patch)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare_cells_by_type() {
+        let mut cells = vec![
+            "10.0.0.10",
+            "b",
+            "9",
+            "",
+            "::1",
+            "10.0.0.9",
+            "10",
+            "a",
+            "NaN",
+        ];
+        cells.sort_by(|left, right| compare_cells(left, right));
+
+        assert_eq!(
+            cells,
+            vec![
+                "",
+                "9",
+                "10",
+                "10.0.0.9",
+                "10.0.0.10",
+                "::1",
+                "NaN",
+                "a",
+                "b"
+            ]
+        );
+        assert!(matches!(CellValue::parse(" fe80::1 "), CellValue::Ip(_)));
+    }
+
+    #[test]
+    fn test_compare_versions() {
+        let mut versions = vec!["1.2.10", "v1.2.9", "2.0.0", "2.0.0-rc.1", "1.10.0"];
+        versions.sort_by(|left, right| compare_cells(left, right));
+
+        assert_eq!(
+            versions,
+            vec!["v1.2.9", "1.2.10", "1.10.0", "2.0.0-rc.1", "2.0.0"]
+        );
+        assert_eq!(compare_cells("1.0.0+build.1", "1.0.0"), Ordering::Equal);
+
+        let compare = |left, right| {
+            let (left, right) = CellValue::parse_pair(left, right);
+            left.compare(&right)
+        };
+        assert_eq!(compare("2.10.1", "2.0"), Some(Ordering::Greater));
+        assert_eq!(compare("2", "v2.0.0"), Some(Ordering::Equal));
+        // Two-part versions on both sides are numbers.
+        assert_eq!(compare("1.10", "1.9"), Some(Ordering::Less));
+    }
+
+    #[test]
+    fn test_parse_durations() {
+        let seconds = |cell| parse_duration(cell).map(|duration| duration.as_secs_f64());
+        assert_eq!(seconds("1h 23m"), Some(4_980.0));
+        assert_eq!(seconds("1h30m"), Some(5_400.0));
+        assert_eq!(seconds("450ms"), Some(0.45));
+        assert_eq!(seconds("PT90M"), Some(5_400.0));
+        assert_eq!(seconds("P1DT2H"), Some(93_600.0));
+        assert_eq!(seconds("01:30:00"), Some(5_400.0));
+        assert_eq!(seconds("0:00:01.5"), Some(1.5));
+
+        assert_eq!(seconds("90"), None);
+        assert_eq!(seconds("30m 1h"), None);
+        assert_eq!(seconds("1h1h"), None);
+        assert_eq!(seconds("PT"), None);
+        assert_eq!(seconds("12:75:00"), None);
+        assert_eq!(seconds("5 miles"), None);
+    }
+
+    #[test]
+    fn test_durations_compare_and_format() {
+        let mut cells = vec!["1h", "PT90M", "45m", "3", "00:30:00", "450ms"];
+        cells.sort_by(|left, right| compare_cells(left, right));
+        assert_eq!(cells, vec!["3", "450ms", "00:30:00", "45m", "1h", "PT90M"]);
+        assert_eq!(compare_cells("1h30m", "01:30:00"), Ordering::Equal);
+
+        assert_eq!(format_duration(Duration::from_secs(5_400)), "1h30m");
+        assert_eq!(format_duration(Duration::from_secs(93_662)), "1d2h1m2s");
+        assert_eq!(format_duration(Duration::from_millis(62_500)), "1m2.5s");
+        assert_eq!(format_duration(Duration::from_millis(450)), "450ms");
+        assert_eq!(format_duration(Duration::ZERO), "0ms");
+    }
+}
@@ -0,0 +1,155 @@
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+
+/// Suffix appended to an input path to find its metadata sidecar
+pub const SIDECAR_SUFFIX: &str = ".meta.toml";
+
+/// Descriptive information about a column
+///
+/// Loaded from a sidecar TOML file with one section per column:
+///
+/// ```toml
+/// [latency]
+/// unit = "ms"
+/// description = "Time to first byte"
+/// format = "{:.1}"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ColumnMetadata {
+    pub unit: Option<String>,
+    pub description: Option<String>,
+    /// Display format, `{}` or `{:.N}` standing for the value
+    pub format: Option<String>,
+}
+
+impl ColumnMetadata {
+    /// Returns the column name with its unit, e.g. `latency (ms)`
+    pub fn display_name(&self, column_name: &str) -> String {
+        match &self.unit {
+            Some(unit) => format!("{} ({})", column_name, unit),
+            None => column_name.to_string(),
+        }
+    }
+
+    /// Renders a value through the display format
+    ///
+    /// `{:.N}` rounds numeric values to N decimals; non-numeric values are
+    /// substituted as they are. Without a format the value is unchanged.
+    pub fn format_value(&self, value: &str) -> String {
+        let Some(format) = &self.format else {
+            return value.to_string();
+        };
+
+        let Some(start) = format.find('{') else {
+            return format.clone();
+        };
+        let Some(length) = format[start..].find('}') else {
+            return format.clone();
+        };
+        let placeholder = &format[start + 1..start + length];
+
+        let rendered = match (placeholder.strip_prefix(":."), value.trim().parse::<f64>()) {
+            (Some(decimals), Ok(number)) => match decimals.parse::<usize>() {
+                Ok(decimals) => format!("{:.*}", decimals, number),
+                Err(_) => value.to_string(),
+            },
+            _ => value.to_string(),
+        };
+
+        format!(
+            "{}{}{}",
+            &format[..start],
+            rendered,
+            &format[start + length + 1..]
+        )
+    }
+}
+
+/// Reads per-column metadata from a TOML file
+pub fn load_metadata(path: &Path) -> io::Result<HashMap<String, ColumnMetadata>> {
+    let contents = fs::read_to_string(path)?;
+    toml::from_str(&contents)
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error.message()))
+}
+
+/// Returns the sidecar metadata path for an input, e.g. `sales.csv.meta.toml`
+pub fn sidecar_path(input: &Path) -> PathBuf {
+    let mut path = input.as_os_str().to_owned();
+    path.push(SIDECAR_SUFFIX);
+    PathBuf::from(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metadata(unit: Option<&str>, format: Option<&str>) -> ColumnMetadata {
+        ColumnMetadata {
+            unit: unit.map(String::from),
+            description: None,
+            format: format.map(String::from),
+        }
+    }
+
+    #[test]
+    fn test_parse_sections() {
+        let sections: HashMap<String, ColumnMetadata> = toml::from_str(
+            r#"
+            [latency]
+            unit = "ms"
+            format = "~{:.1}"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(sections["latency"], metadata(Some("ms"), Some("~{:.1}")));
+    }
+
+    #[test]
+    fn test_unknown_fields_rejected() {
+        let parsed: Result<HashMap<String, ColumnMetadata>, _> =
+            toml::from_str("[latency]\nunits = \"ms\"\n");
+        assert!(parsed.is_err());
+    }
+
+    #[test]
+    fn test_display_name() {
+        assert_eq!(
+            metadata(Some("ms"), None).display_name("latency"),
+            "latency (ms)"
+        );
+        assert_eq!(ColumnMetadata::default().display_name("latency"), "latency");
+    }
+
+    #[test]
+    fn test_format_rounds_numbers() {
+        let latency = metadata(None, Some("~{:.1}"));
+        assert_eq!(latency.format_value("12.345"), "~12.3");
+        assert_eq!(latency.format_value("n/a"), "~n/a");
+    }
+
+    #[test]
+    fn test_format_without_placeholder() {
+        assert_eq!(metadata(None, Some("{}")).format_value("7"), "7");
+        assert_eq!(metadata(None, Some("n/a")).format_value("7"), "n/a");
+        assert_eq!(ColumnMetadata::default().format_value("7"), "7");
+    }
+
+    #[test]
+    fn test_load_missing_file() {
+        assert!(load_metadata(Path::new("/nonexistent/columns.meta.toml")).is_err());
+    }
+
+    #[test]
+    fn test_sidecar_path() {
+        assert_eq!(
+            sidecar_path(Path::new("data/sales.csv")),
+            PathBuf::from("data/sales.csv.meta.toml")
+        );
+    }
+}
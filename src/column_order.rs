@@ -0,0 +1,114 @@
+use std::{fs, io, path::Path};
+
+use crate::table::{Table, TableError};
+
+/// Marks where columns not named in the template go
+pub const WILDCARD: &str = "*";
+
+/// An agreed column order for output
+///
+/// Read from a text file with one column name per line. A `*` line stands
+/// for every column the template doesn't name, in source order; without
+/// it those columns go last. Blank lines and lines starting with `#` are
+/// ignored, and named columns missing from a table are skipped.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnOrder {
+    entries: Vec<String>,
+}
+
+impl ColumnOrder {
+    /// Reads a column order template from a file
+    pub fn load(path: &Path) -> io::Result<Self> {
+        Ok(Self::parse(&fs::read_to_string(path)?))
+    }
+
+    /// Parses a column order template
+    pub fn parse(spec: &str) -> Self {
+        let entries = spec
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(String::from)
+            .collect();
+        ColumnOrder { entries }
+    }
+
+    /// Returns a copy of the table with its columns in template order
+    pub fn apply(&self, table: &Table) -> Result<Table, TableError> {
+        let named: Vec<usize> = self
+            .entries
+            .iter()
+            .filter_map(|entry| table.column_index(entry))
+            .collect();
+        let rest: Vec<usize> = (0..table.column_count())
+            .filter(|index| !named.contains(index))
+            .collect();
+
+        let mut order = Vec::with_capacity(table.column_count());
+        let mut rest_placed = false;
+        for entry in &self.entries {
+            if entry == WILDCARD {
+                if !rest_placed {
+                    order.extend(&rest);
+                    rest_placed = true;
+                }
+            } else if let Some(index) = table.column_index(entry) {
+                if !order.contains(&index) {
+                    order.push(index);
+                }
+            }
+        }
+        if !rest_placed {
+            order.extend(&rest);
+        }
+
+        table.select_columns(&order)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table() -> Table {
+        Table::with_header_and_data(
+            ["b", "id", "a", "total"].map(String::from).to_vec(),
+            vec![["2", "1", "3", "4"].map(String::from).to_vec()],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_column_order_with_wildcard() {
+        let ordered = ColumnOrder::parse("id\n*\ntotal\n")
+            .apply(&table())
+            .unwrap();
+        assert_eq!(ordered.header(), vec!["id", "b", "a", "total"]);
+        assert_eq!(ordered.rows()[0], vec!["1", "2", "3", "4"]);
+    }
+
+    #[test]
+    fn test_unnamed_columns_go_last() {
+        let without_wildcard = ColumnOrder::parse("total\n").apply(&table()).unwrap();
+        assert_eq!(without_wildcard.header(), vec!["total", "b", "id", "a"]);
+    }
+
+    #[test]
+    fn test_comments_and_missing_columns_skipped() {
+        let ordered = ColumnOrder::parse("# report layout\n\nmissing\n  a  \n")
+            .apply(&table())
+            .unwrap();
+        assert_eq!(ordered.header(), vec!["a", "b", "id", "total"]);
+    }
+
+    #[test]
+    fn test_repeated_names_placed_once() {
+        let ordered = ColumnOrder::parse("a\n*\na\n*\n").apply(&table()).unwrap();
+        assert_eq!(ordered.header(), vec!["a", "b", "id", "total"]);
+    }
+
+    #[test]
+    fn test_load_missing_file() {
+        assert!(ColumnOrder::load(Path::new("/nonexistent/order.txt")).is_err());
+    }
+}
@@ -0,0 +1,1599 @@
+use std::{
+    collections::HashSet,
+    fs,
+    io::{self, IsTerminal},
+    num::NonZeroUsize,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use chrono_tz::Tz;
+use clap::{Args, CommandFactory, Subcommand};
+use clap_complete::Shell;
+use uuid::Uuid;
+
+use crate::aggregate::{group_rows, Aggregate};
+use crate::assertions::{parse_row_count, TableAssertions};
+use crate::binning::{add_bins, Bins};
+use crate::canonicalize::canonicalize;
+use crate::checksum::{add_checksum, HashAlgorithm};
+use crate::context::Context;
+use crate::diff::{self, CellComparison, Tolerance};
+use crate::dupes::{self, MergeStrategy};
+use crate::filter::{filter_rows, Condition};
+use crate::geo::{self, Circle, CoordinateColumns, DistanceTarget};
+use crate::history::value_history;
+use crate::input;
+use crate::json_schema::{json_schema, SchemaFormat};
+use crate::man_page;
+use crate::repair::repair_delimited;
+use crate::reshape;
+use crate::share::add_share;
+use crate::sort::sort_rows;
+use crate::split::{self, SampleSize};
+use crate::stats;
+use crate::table::{Table, TableError};
+use crate::timezone::{convert_timezone, parse_zone, AmbiguousPolicy, NonexistentPolicy};
+use crate::transform::{derive_column, Transform};
+use crate::uuid_column::{add_uuid_column, parse_namespace, UuidKind};
+use crate::Cli;
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Compare two tables row by row
+    Diff(DiffArgs),
+    /// Rewrite a table in the --to format
+    Convert(ConvertArgs),
+    /// Rewrite a table in a byte-stable canonical form
+    Canonicalize(CanonicalizeArgs),
+    /// Split a table into several files
+    Split(SplitArgs),
+    /// Randomly split a table into parts, e.g. train and test sets
+    SplitRandom(SplitRandomArgs),
+    /// Keep a random sample of rows, optionally per value of a column
+    Sample(SampleArgs),
+    /// Find duplicate and near-duplicate rows
+    Dupes(DupesArgs),
+    /// Fix common structural damage in a delimited file
+    Repair(RepairArgs),
+    /// Restore a file edited with --in-place from its --backup copy
+    Undo(UndoArgs),
+    /// Trace how values change per key across snapshots
+    History(HistoryArgs),
+    /// Convert a timestamp column between time zones
+    Tz(TzArgs),
+    /// Keep the rows matching conditions
+    Filter(FilterArgs),
+    /// Sort rows by typed column values: numbers, IP addresses, versions
+    Sort(SortArgs),
+    /// Move columns into rows under a key column, e.g. one row per year
+    Stack(StackArgs),
+    /// Move a key column's values out into columns, reversing stack
+    Unstack(UnstackArgs),
+    /// Aggregate rows per group, e.g. `sum(price*qty) as revenue` by region
+    Group(GroupArgs),
+    /// Add the distance between coordinates, or keep rows near a point
+    GeoDist(GeoDistArgs),
+    /// Append a column computed from another, e.g. the domain of an email
+    Derive(DeriveArgs),
+    /// Append a digest of selected fields, e.g. for join keys over PII
+    Hash(HashArgs),
+    /// Append each row's percentage of the column total, e.g. per region
+    Share(ShareArgs),
+    /// Append the bucket of a numeric column, by edges or quantiles
+    Bin(BinArgs),
+    /// Append a column of generated values, e.g. UUID primary keys
+    AddCol(AddColArgs),
+    /// Describe a row of the table as a schema, e.g. JSON Schema
+    Schema(SchemaArgs),
+    /// Summarize each column, optionally against a baseline version
+    Stats(StatsArgs),
+    /// Check columns, row count and empty cells, exiting 1 on violations
+    Assert(AssertArgs),
+    /// Diff driver for git, e.g. `diff.tables.command = tables git-diff`
+    /// with `*.csv diff=tables` in .gitattributes
+    GitDiff(GitDiffArgs),
+    /// Print a shell completion script
+    Completions(CompletionsArgs),
+    /// Print the man page, or write one page per command to a directory
+    #[command(hide = true)]
+    Man(ManArgs),
+}
+
+impl Command {
+    /// Table files the command reads, `-` standing for stdin
+    pub fn inputs(&self) -> Vec<PathBuf> {
+        match self {
+            Command::Diff(args) => match args.paths() {
+                Some((left, right)) => vec![left, right],
+                None => args.left.iter().chain(&args.right).cloned().collect(),
+            },
+            Command::Convert(args) => {
+                input::expand_globs(&args.inputs).unwrap_or_else(|_| args.inputs.clone())
+            }
+            Command::Canonicalize(CanonicalizeArgs { input, .. })
+            | Command::Split(SplitArgs { input, .. })
+            | Command::SplitRandom(SplitRandomArgs { input, .. })
+            | Command::Sample(SampleArgs { input, .. })
+            | Command::Dupes(DupesArgs { input, .. })
+            | Command::Repair(RepairArgs { input, .. })
+            | Command::Tz(TzArgs { input, .. })
+            | Command::Filter(FilterArgs { input, .. })
+            | Command::Sort(SortArgs { input, .. })
+            | Command::Stack(StackArgs { input, .. })
+            | Command::Unstack(UnstackArgs { input, .. })
+            | Command::Group(GroupArgs { input, .. })
+            | Command::GeoDist(GeoDistArgs { input, .. })
+            | Command::Derive(DeriveArgs { input, .. })
+            | Command::Hash(HashArgs { input, .. })
+            | Command::Share(ShareArgs { input, .. })
+            | Command::Bin(BinArgs { input, .. })
+            | Command::AddCol(AddColArgs { input, .. })
+            | Command::Schema(SchemaArgs { input, .. })
+            | Command::Assert(AssertArgs { input, .. }) => vec![input.clone()],
+            Command::Stats(args) => std::iter::once(&args.input)
+                .chain(&args.baseline)
+                .cloned()
+                .collect(),
+            Command::History(args) => args.snapshots.clone(),
+            Command::GitDiff(args) => [&args.old_file, &args.new_file]
+                .into_iter()
+                .filter(|file| *file != Path::new(GitDiffArgs::NULL_FILE))
+                .cloned()
+                .collect(),
+            Command::Undo(_) | Command::Completions(_) | Command::Man(_) => Vec::new(),
+        }
+    }
+
+    /// The one input a command rewrites with `--in-place`, or `None` for
+    /// commands that don't write a single table derived from one input
+    pub fn in_place_input(&self) -> Option<&Path> {
+        match self {
+            Command::Convert(ConvertArgs {
+                inputs,
+                out_dir: None,
+            }) if inputs.len() == 1 => Some(&inputs[0]),
+            Command::Canonicalize(CanonicalizeArgs { input, .. })
+            | Command::Dupes(DupesArgs {
+                input,
+                merge: Some(_),
+                ..
+            })
+            | Command::Repair(RepairArgs { input, .. })
+            | Command::Sample(SampleArgs { input, .. })
+            | Command::Tz(TzArgs { input, .. })
+            | Command::Filter(FilterArgs { input, .. })
+            | Command::Sort(SortArgs { input, .. })
+            | Command::Stack(StackArgs { input, .. })
+            | Command::Unstack(UnstackArgs { input, .. })
+            | Command::Group(GroupArgs { input, .. })
+            | Command::GeoDist(GeoDistArgs { input, .. })
+            | Command::Derive(DeriveArgs { input, .. })
+            | Command::Hash(HashArgs { input, .. })
+            | Command::Share(ShareArgs { input, .. })
+            | Command::Bin(BinArgs { input, .. })
+            | Command::AddCol(AddColArgs { input, .. }) => Some(input),
+            Command::Diff(_)
+            | Command::Convert(_)
+            | Command::Dupes(_)
+            | Command::Split(_)
+            | Command::SplitRandom(_)
+            | Command::History(_)
+            | Command::Schema(_)
+            | Command::Stats(_)
+            | Command::Assert(_)
+            | Command::GitDiff(_)
+            | Command::Undo(_)
+            | Command::Completions(_)
+            | Command::Man(_) => None,
+        }
+    }
+
+    /// Runs the command; failures exit with status 2 through `Context::fail`
+    pub fn run(&self, context: &mut Context) -> Outcome {
+        match self {
+            Command::Diff(args) => return args.run(context),
+            Command::Assert(args) => return args.run(context),
+            Command::Convert(args) => args.run(context),
+            Command::Canonicalize(args) => args.run(context),
+            Command::Split(args) => args.run(context),
+            Command::SplitRandom(args) => args.run(context),
+            Command::Sample(args) => args.run(context),
+            Command::Dupes(args) => args.run(context),
+            Command::Repair(args) => args.run(context),
+            Command::Undo(args) => args.run(context),
+            Command::History(args) => args.run(context),
+            Command::Tz(args) => args.run(context),
+            Command::Filter(args) => args.run(context),
+            Command::Sort(args) => args.run(context),
+            Command::Stack(args) => args.run(context),
+            Command::Unstack(args) => args.run(context),
+            Command::Group(args) => args.run(context),
+            Command::GeoDist(args) => args.run(context),
+            Command::Derive(args) => args.run(context),
+            Command::Hash(args) => args.run(context),
+            Command::Share(args) => args.run(context),
+            Command::Bin(args) => args.run(context),
+            Command::AddCol(args) => args.run(context),
+            Command::Schema(args) => args.run(context),
+            Command::Stats(args) => args.run(context),
+            Command::GitDiff(args) => args.run(context),
+            Command::Completions(args) => args.run(context),
+            Command::Man(args) => args.run(context),
+        }
+        Outcome::Success
+    }
+}
+
+/// How a command that ran to completion ended, which sets the exit status
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Outcome {
+    /// Exit status 0
+    Success,
+    /// The compared tables differ, exit status 1
+    Differences,
+    /// The table fails its assertions, exit status 1
+    Violations,
+}
+
+impl From<Outcome> for ExitCode {
+    fn from(outcome: Outcome) -> Self {
+        match outcome {
+            Outcome::Success => ExitCode::SUCCESS,
+            Outcome::Differences | Outcome::Violations => ExitCode::from(1),
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct DiffArgs {
+    /// First table file path
+    #[arg(
+        index = 1,
+        help = "Path to the first table file, '-' for stdin (the default when stdin is piped and only one path is given)"
+    )]
+    pub left: Option<PathBuf>,
+
+    /// Second table file path
+    #[arg(index = 2, help = "Path to the second table file")]
+    pub right: Option<PathBuf>,
+
+    /// Columns matching rows between the tables
+    #[arg(
+        long,
+        value_delimiter = ',',
+        help = "Match rows by these columns' values instead of by position; repeat or separate with commas for a composite key"
+    )]
+    pub key: Vec<String>,
+
+    /// Compare rows regardless of their order
+    #[arg(
+        long,
+        visible_alias = "unordered",
+        conflicts_with = "key",
+        help = "Compare the tables as multisets of rows, so reordered rows aren't differences"
+    )]
+    pub ignore_order: bool,
+
+    /// Absolute tolerances for numeric cells
+    #[arg(
+        long,
+        value_parser = Tolerance::parse,
+        help = "Count numbers differing by at most this much as equal; 'column=0.01' sets one column's tolerance and can be repeated, with columns named #1, #2, ... when a table has no header"
+    )]
+    pub tolerance: Vec<Tolerance>,
+
+    /// Relative tolerance for numeric cells
+    #[arg(
+        long,
+        default_value_t = 0.0,
+        value_parser = diff::parse_tolerance,
+        help = "Count numbers differing by at most this fraction of the larger one as equal, e.g. 1e-9"
+    )]
+    pub rel_tolerance: f64,
+
+    /// Columns left out of the comparison
+    #[arg(
+        long,
+        value_delimiter = ',',
+        help = "Comma-separated columns to leave out of the comparison, e.g. volatile timestamps, named #1, #2, ... when a table has no header; reported rows still show them"
+    )]
+    pub ignore_columns: Vec<String>,
+
+    /// Compare cells regardless of letter case
+    #[arg(
+        long,
+        help = "Compare cells regardless of letter case; reported cells keep theirs"
+    )]
+    pub ignore_case: bool,
+
+    /// Compare cells regardless of padding and repeated whitespace
+    #[arg(
+        long,
+        help = "Compare cells without leading and trailing whitespace and with runs of whitespace as one space"
+    )]
+    pub ignore_whitespace: bool,
+
+    /// Summarize changed cells per column
+    #[arg(
+        long,
+        help = "End the report with the number of changed cells per column and, for numeric columns, how large the changes were"
+    )]
+    pub by_column: bool,
+}
+
+impl DiffArgs {
+    /// Paths of both tables; a single path is compared against piped stdin
+    fn paths(&self) -> Option<(PathBuf, PathBuf)> {
+        match (&self.left, &self.right) {
+            (Some(left), Some(right)) => Some((left.clone(), right.clone())),
+            (Some(right), None) if !io::stdin().is_terminal() => {
+                Some((PathBuf::from(input::STDIN_PATH), right.clone()))
+            }
+            _ => None,
+        }
+    }
+
+    fn run(&self, context: &mut Context) -> Outcome {
+        let Some((left_path, right_path)) = self.paths() else {
+            let error = TableError::InvalidArgument("two tables are needed to diff".to_string());
+            context.fail(None, &error);
+        };
+
+        let left = context.load(&left_path);
+        let right = context.load(&right_path);
+        let mut comparison = CellComparison::new(&self.tolerance, self.rel_tolerance);
+        comparison.ignored_columns = self.ignore_columns.iter().cloned().collect();
+        comparison.ignore_case = self.ignore_case;
+        comparison.ignore_whitespace = self.ignore_whitespace;
+        if context.args.strict {
+            comparison
+                .check_strict()
+                .unwrap_or_else(|error| context.fail(None, &error));
+        }
+        let named = comparison
+            .column_tolerances
+            .keys()
+            .chain(&comparison.ignored_columns);
+        for column in named {
+            if !diff::has_column(&left, &right, column) {
+                context.fail(None, &TableError::MissingColumn(column.clone()));
+            }
+        }
+        let diff = context.timings.time("diff", || {
+            if !self.key.is_empty() {
+                diff::diff_by_key(&left, &right, &self.key, &comparison)
+            } else if self.ignore_order {
+                Ok(diff::diff_unordered(&left, &right, &comparison))
+            } else {
+                Ok(diff::diff_tables(&left, &right, &comparison))
+            }
+        });
+        let diff = diff.unwrap_or_else(|error| context.fail(None, &error));
+        let report = context.timings.time("render", || {
+            diff::format_report(
+                &diff,
+                &left_path.display().to_string(),
+                &right_path.display().to_string(),
+                &context.locale,
+                self.by_column,
+            )
+        });
+        context.write_output(report);
+
+        if diff.is_empty() {
+            Outcome::Success
+        } else {
+            Outcome::Differences
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct ConvertArgs {
+    /// Tables to convert
+    #[arg(
+        required = true,
+        help = "Tables to convert, '-' for stdin; patterns like 'reports/*.csv' are expanded"
+    )]
+    pub inputs: Vec<PathBuf>,
+
+    /// Directory receiving one output per input
+    #[arg(
+        long,
+        help = "Write each table to this directory as <name>.<format extension>, required for several inputs"
+    )]
+    pub out_dir: Option<PathBuf>,
+}
+
+impl ConvertArgs {
+    fn run(&self, context: &mut Context) {
+        let inputs =
+            input::expand_globs(&self.inputs).unwrap_or_else(|error| context.fail(None, &error));
+        let Some(out_dir) = &self.out_dir else {
+            if inputs.len() > 1 {
+                let error = TableError::InvalidArgument(format!(
+                    "{} inputs to convert, pass --out-dir to write one file each",
+                    inputs.len()
+                ));
+                context.fail(None, &error);
+            }
+            let table = context.load(&inputs[0]);
+            context.write_table(&table);
+            return;
+        };
+
+        context.create_dir(out_dir);
+        let extension = context.args.to.unwrap_or_default().extension();
+        let mut written = HashSet::new();
+        for input in &inputs {
+            let stem = match input.file_stem() {
+                Some(stem) if !input::is_stdin(input) => stem.to_string_lossy(),
+                _ => "stdin".into(),
+            };
+            let path = out_dir.join(format!("{}.{}", stem, extension));
+            let overwrites_input = fs::canonicalize(&path)
+                .is_ok_and(|path| fs::canonicalize(input).is_ok_and(|input| input == path));
+            if overwrites_input || !written.insert(path.clone()) {
+                let error = TableError::InvalidArgument(format!(
+                    "{} would be written twice or over an input",
+                    path.display()
+                ));
+                context.fail(Some(input), &error);
+            }
+
+            let table = context.load(input);
+            write_table_to(context, &table, &path);
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct CanonicalizeArgs {
+    /// Table to canonicalize
+    pub input: PathBuf,
+
+    /// Columns to sort rows by
+    #[arg(
+        long,
+        value_delimiter = ',',
+        help = "Comma-separated columns to sort rows by, in priority order"
+    )]
+    pub key: Vec<String>,
+}
+
+impl CanonicalizeArgs {
+    fn run(&self, context: &mut Context) {
+        let table = context.load(&self.input);
+        let canonical = canonicalize(&table, &self.key)
+            .unwrap_or_else(|error| context.fail(Some(&self.input), &error));
+        context.write_table(&canonical);
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct SplitArgs {
+    /// Table to split
+    pub input: PathBuf,
+
+    /// Rows per output file
+    #[arg(
+        long,
+        conflicts_with = "by",
+        required_unless_present = "by",
+        help = "Maximum number of rows in each output file"
+    )]
+    pub rows_per_file: Option<NonZeroUsize>,
+
+    /// Column whose values select the output file
+    #[arg(long, help = "Write one file per distinct value of this column")]
+    pub by: Option<String>,
+
+    /// Directory receiving the parts
+    #[arg(long, default_value = ".", help = "Directory to write the parts to")]
+    pub out_dir: PathBuf,
+}
+
+impl SplitArgs {
+    fn run(&self, context: &mut Context) {
+        let table = context.load(&self.input);
+        let parts = match (&self.by, self.rows_per_file) {
+            (Some(column), _) => split::split_by_column(&table, column).map(|parts| {
+                parts
+                    .into_iter()
+                    .map(|(value, part)| (file_label(&value), part))
+                    .collect()
+            }),
+            (None, Some(rows_per_file)) => {
+                split::split_by_rows(&table, rows_per_file).map(|parts| {
+                    parts
+                        .into_iter()
+                        .enumerate()
+                        .map(|(index, part)| ((index + 1).to_string(), part))
+                        .collect::<Vec<_>>()
+                })
+            }
+            (None, None) => unreachable!("clap requires --rows-per-file or --by"),
+        }
+        .unwrap_or_else(|error| context.fail(Some(&self.input), &error));
+
+        context.create_dir(&self.out_dir);
+        let stem = self
+            .input
+            .file_stem()
+            .map_or("part".into(), |stem| stem.to_string_lossy());
+        let extension = context.args.to.unwrap_or_default().extension();
+        // Distinct values can share a file label, e.g. `a/b` and `a b`;
+        // later ones get a numeric suffix instead of overwriting.
+        let mut written = HashSet::new();
+        for (label, part) in parts {
+            let mut path = self
+                .out_dir
+                .join(format!("{}-{}.{}", stem, label, extension));
+            let mut copy = 1;
+            while !written.insert(path.clone()) {
+                copy += 1;
+                path = self
+                    .out_dir
+                    .join(format!("{}-{}-{}.{}", stem, label, copy, extension));
+            }
+            write_table_to(context, &part, &path);
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct SplitRandomArgs {
+    /// Table to split
+    pub input: PathBuf,
+
+    /// Relative size of each part
+    #[arg(
+        long,
+        value_delimiter = ',',
+        required = true,
+        help = "Comma-separated relative part sizes, e.g. 0.8,0.2"
+    )]
+    pub fractions: Vec<f64>,
+
+    /// Seed for the row shuffle
+    #[arg(long, default_value_t = 0, help = "Seed making the split reproducible")]
+    pub seed: u64,
+
+    /// Output file per part
+    #[arg(
+        long,
+        value_delimiter = ',',
+        required = true,
+        help = "Comma-separated output files, one per fraction"
+    )]
+    pub out: Vec<PathBuf>,
+
+    /// Column whose proportions every part keeps
+    #[arg(
+        long,
+        help = "Keep the proportions of this column's values in every part"
+    )]
+    pub stratify: Option<String>,
+}
+
+impl SplitRandomArgs {
+    fn run(&self, context: &mut Context) {
+        if self.out.len() != self.fractions.len() {
+            let error = TableError::InvalidArgument(format!(
+                "{} fractions but {} output files",
+                self.fractions.len(),
+                self.out.len()
+            ));
+            context.fail(None, &error);
+        }
+
+        let table = context.load(&self.input);
+        let parts =
+            split::split_random(&table, &self.fractions, self.seed, self.stratify.as_deref())
+                .unwrap_or_else(|error| context.fail(Some(&self.input), &error));
+        for (part, path) in parts.iter().zip(&self.out) {
+            write_table_to(context, part, path);
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct SampleArgs {
+    /// Table to sample
+    pub input: PathBuf,
+
+    /// Share of the rows to keep
+    #[arg(
+        long,
+        required_unless_present = "rows",
+        conflicts_with = "rows",
+        help = "Keep this share of the rows (0-1), of each value of --stratify-by if given"
+    )]
+    pub fraction: Option<f64>,
+
+    /// Number of rows to keep
+    #[arg(
+        long,
+        help = "Keep this many rows, or this many of each value of --stratify-by"
+    )]
+    pub rows: Option<usize>,
+
+    /// Seed for the row shuffle
+    #[arg(
+        long,
+        default_value_t = 0,
+        help = "Seed making the sample reproducible"
+    )]
+    pub seed: u64,
+
+    /// Column whose values are sampled separately
+    #[arg(
+        long,
+        visible_alias = "stratify",
+        help = "Sample each value of this column separately, keeping its proportions with --fraction"
+    )]
+    pub stratify_by: Option<String>,
+}
+
+impl SampleArgs {
+    fn run(&self, context: &mut Context) {
+        let size = match (self.fraction, self.rows) {
+            (Some(fraction), _) => SampleSize::Fraction(fraction),
+            (None, rows) => SampleSize::Rows(rows.unwrap_or_default()),
+        };
+        let table = context.load(&self.input);
+        let sample = split::sample_rows(&table, size, self.seed, self.stratify_by.as_deref())
+            .unwrap_or_else(|error| context.fail(Some(&self.input), &error));
+        context.write_table(&sample);
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct DupesArgs {
+    /// Table to search
+    pub input: PathBuf,
+
+    /// Columns compared between rows
+    #[arg(
+        long,
+        value_delimiter = ',',
+        required = true,
+        help = "Comma-separated columns compared between rows"
+    )]
+    pub by: Vec<String>,
+
+    /// Similarity threshold for near-duplicates
+    #[arg(
+        long,
+        num_args = 0..=1,
+        default_missing_value = "0.8",
+        help = "Also group near-duplicates at least this similar (0-1, default 0.8)"
+    )]
+    pub fuzzy: Option<f64>,
+
+    /// Collapse each cluster into one row
+    #[arg(
+        long,
+        value_enum,
+        help = "Collapse each cluster into one row instead of reporting clusters"
+    )]
+    pub merge: Option<MergeStrategy>,
+}
+
+impl DupesArgs {
+    fn run(&self, context: &mut Context) {
+        let table = context.load(&self.input);
+        let result = dupes::find_duplicates(&table, &self.by, self.fuzzy).and_then(|clusters| {
+            match self.merge {
+                Some(strategy) => dupes::merge_duplicates(&table, &self.by, &clusters, strategy),
+                None => dupes::duplicates_report(&table, &clusters),
+            }
+        });
+        let output = result.unwrap_or_else(|error| context.fail(Some(&self.input), &error));
+        context.write_table(&output);
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct RepairArgs {
+    /// Delimited file to repair
+    pub input: PathBuf,
+
+    /// Quote character
+    #[arg(long, default_value_t = '"', help = "Quote character of the input")]
+    pub quote: char,
+
+    /// File receiving the repair report
+    #[arg(
+        long,
+        help = "Write the report of changed and unfixable lines to this file [default: stderr]"
+    )]
+    pub report: Option<PathBuf>,
+}
+
+impl RepairArgs {
+    fn run(&self, context: &mut Context) {
+        let data = context.read_delimited(&self.input);
+        let delimiter = context.args.delimiter.unwrap_or(',');
+        let (table, report) = repair_delimited(&data, delimiter, self.quote)
+            .unwrap_or_else(|error| context.fail(Some(&self.input), &error));
+
+        // The report is the record of what changed, so --quiet keeps it.
+        let mut lines: Vec<String> = report
+            .changes
+            .iter()
+            .map(|change| format!("{}: {}\n", self.input.display(), change))
+            .collect();
+        lines.extend(report.unfixed_lines.iter().map(|line| {
+            format!(
+                "{}: line {}: could not fix, dropped\n",
+                self.input.display(),
+                line
+            )
+        }));
+        match &self.report {
+            Some(path) => context.write_file(path, lines.concat()),
+            None => eprint!("{}", lines.concat()),
+        }
+        context.write_table(&table);
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct UndoArgs {
+    /// File edited in place
+    pub input: PathBuf,
+
+    /// Suffix of the backup copy
+    #[arg(
+        long,
+        default_value = ".bak",
+        help = "Suffix the original was kept with by --in-place --backup"
+    )]
+    pub suffix: String,
+}
+
+impl UndoArgs {
+    /// Copies the backup back over the file; the backup stays, so undoing
+    /// twice changes nothing
+    fn run(&self, context: &mut Context) {
+        let mut backup = self.input.clone().into_os_string();
+        backup.push(&self.suffix);
+        let backup = PathBuf::from(backup);
+        let original = fs::read(&backup).unwrap_or_else(|error| {
+            let error = io::Error::new(
+                error.kind(),
+                format!(
+                    "no backup to restore, run --in-place with --backup first ({})",
+                    error
+                ),
+            );
+            context.fail(Some(&backup), &error)
+        });
+        context.write_file(&self.input, original);
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct HistoryArgs {
+    /// Snapshots, oldest first
+    #[arg(required = true, num_args = 2.., help = "Snapshot files, oldest first")]
+    pub snapshots: Vec<PathBuf>,
+
+    /// Column identifying a record
+    #[arg(long, help = "Column identifying a record across snapshots")]
+    pub key: String,
+
+    /// Column whose changes are traced
+    #[arg(long, help = "Column whose value changes are listed")]
+    pub column: String,
+}
+
+impl HistoryArgs {
+    fn run(&self, context: &mut Context) {
+        let snapshots: Vec<(String, Table)> = self
+            .snapshots
+            .iter()
+            .map(|path| (path.display().to_string(), context.load(path)))
+            .collect();
+        let history = value_history(&snapshots, &self.key, &self.column)
+            .unwrap_or_else(|error| context.fail(None, &error));
+        context.write_table(&history);
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct TzArgs {
+    /// Table to convert
+    pub input: PathBuf,
+
+    /// Column holding timestamps
+    #[arg(long, help = "Column holding the timestamps to convert")]
+    pub column: String,
+
+    /// Zone of timestamps without an offset
+    #[arg(
+        long,
+        default_value = "UTC",
+        value_parser = parse_zone,
+        help = "Time zone of timestamps without an offset, e.g. UTC"
+    )]
+    pub from_tz: Tz,
+
+    /// Zone to convert to
+    #[arg(
+        long,
+        value_parser = parse_zone,
+        help = "Time zone to convert to, e.g. Europe/Berlin"
+    )]
+    pub to_tz: Tz,
+
+    /// Handling of times that occur twice
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = AmbiguousPolicy::Error,
+        help = "How to read local times that occur twice when clocks go back"
+    )]
+    pub ambiguous: AmbiguousPolicy,
+
+    /// Handling of times that never occur
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = NonexistentPolicy::Error,
+        help = "How to read local times skipped when clocks go forward"
+    )]
+    pub nonexistent: NonexistentPolicy,
+}
+
+impl TzArgs {
+    fn run(&self, context: &mut Context) {
+        let table = context.load(&self.input);
+        let converted = convert_timezone(
+            &table,
+            &self.column,
+            self.from_tz,
+            self.to_tz,
+            self.ambiguous,
+            self.nonexistent,
+        )
+        .unwrap_or_else(|error| context.fail(Some(&self.input), &error));
+        context.write_table(&converted);
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct FilterArgs {
+    /// Table to filter
+    pub input: PathBuf,
+
+    /// Conditions rows must satisfy
+    #[arg(
+        long = "where",
+        required = true,
+        value_parser = Condition::parse,
+        help = "Condition such as 'amount > 100' or 'ip in 10.0.0.0/8'; repeat to require several"
+    )]
+    pub conditions: Vec<Condition>,
+}
+
+impl FilterArgs {
+    fn run(&self, context: &mut Context) {
+        let table = context.load(&self.input);
+        let filtered = filter_rows(&table, &self.conditions, context.args.strict)
+            .unwrap_or_else(|error| context.fail(Some(&self.input), &error));
+        context.write_table(&filtered);
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct SortArgs {
+    /// Table to sort
+    pub input: PathBuf,
+
+    /// Columns to sort rows by
+    #[arg(
+        long,
+        value_delimiter = ',',
+        required = true,
+        help = "Comma-separated columns to sort rows by, in priority order"
+    )]
+    pub by: Vec<String>,
+
+    /// Largest values first
+    #[arg(long, help = "Sort in descending order")]
+    pub descending: bool,
+}
+
+impl SortArgs {
+    fn run(&self, context: &mut Context) {
+        let table = context.load(&self.input);
+        let sorted = sort_rows(&table, &self.by, self.descending, context.args.strict)
+            .unwrap_or_else(|error| context.fail(Some(&self.input), &error));
+        context.write_table(&sorted);
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct StackArgs {
+    /// Table to reshape
+    pub input: PathBuf,
+
+    /// Columns identifying a row
+    #[arg(
+        long,
+        value_delimiter = ',',
+        required = true,
+        help = "Comma-separated columns identifying a row, kept on every stacked row"
+    )]
+    pub index: Vec<String>,
+
+    /// Columns moved into rows
+    #[arg(
+        long,
+        value_delimiter = ',',
+        help = "Comma-separated columns to stack [default: all but the index]"
+    )]
+    pub columns: Vec<String>,
+
+    /// Column receiving the stacked column names
+    #[arg(long, default_value = "column", help = "Name of the new key column")]
+    pub into: String,
+
+    /// Column receiving the stacked cells
+    #[arg(long, default_value = "value", help = "Name of the new value column")]
+    pub values: String,
+}
+
+impl StackArgs {
+    fn run(&self, context: &mut Context) {
+        let table = context.load(&self.input);
+        let stacked = reshape::stack(&table, &self.index, &self.columns, &self.into, &self.values)
+            .unwrap_or_else(|error| context.fail(Some(&self.input), &error));
+        context.write_table(&stacked);
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct UnstackArgs {
+    /// Table to reshape
+    pub input: PathBuf,
+
+    /// Columns identifying an output row
+    #[arg(
+        long,
+        value_delimiter = ',',
+        required = true,
+        help = "Comma-separated columns identifying an output row"
+    )]
+    pub index: Vec<String>,
+
+    /// Column whose values become columns
+    #[arg(
+        long,
+        help = "Key column whose values become column names; the remaining columns fill them"
+    )]
+    pub level: String,
+}
+
+impl UnstackArgs {
+    fn run(&self, context: &mut Context) {
+        let table = context.load(&self.input);
+        let unstacked = reshape::unstack(&table, &self.index, &self.level)
+            .unwrap_or_else(|error| context.fail(Some(&self.input), &error));
+        context.write_table(&unstacked);
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct GroupArgs {
+    /// Table to aggregate
+    pub input: PathBuf,
+
+    /// Columns identifying a group
+    #[arg(
+        long,
+        value_delimiter = ',',
+        help = "Comma-separated columns identifying a group [default: the whole table is one group]"
+    )]
+    pub by: Vec<String>,
+
+    /// Aggregate columns to compute
+    #[arg(
+        long = "agg",
+        value_delimiter = ',',
+        value_parser = Aggregate::parse,
+        required = true,
+        help = "Comma-separated aggregates: count, sum, avg, min or max of a column or arithmetic expression, with an optional 'as name', e.g. 'sum(price*qty) as revenue, max(date) as last_seen'"
+    )]
+    pub aggregates: Vec<Aggregate>,
+}
+
+impl GroupArgs {
+    fn run(&self, context: &mut Context) {
+        let table = context.load(&self.input);
+        let grouped = group_rows(&table, &self.by, &self.aggregates)
+            .unwrap_or_else(|error| context.fail(Some(&self.input), &error));
+        context.write_table(&grouped);
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct GeoDistArgs {
+    /// Table with coordinates
+    pub input: PathBuf,
+
+    /// Columns of each row's point
+    #[arg(
+        long,
+        value_parser = CoordinateColumns::parse,
+        help = "Latitude and longitude columns of each row's point, as lat,lon"
+    )]
+    pub from_coords: CoordinateColumns,
+
+    /// Columns of the point to measure to
+    #[arg(
+        long,
+        value_parser = CoordinateColumns::parse,
+        required_unless_present = "near",
+        conflicts_with = "near",
+        help = "Latitude and longitude columns of the point to measure to, as lat2,lon2"
+    )]
+    pub to_coords: Option<CoordinateColumns>,
+
+    /// Circle rows must fall in
+    #[arg(
+        long,
+        value_parser = Circle::parse,
+        help = "Keep rows within radius_km of a point, given as 'lat,lon,radius_km'"
+    )]
+    pub near: Option<Circle>,
+
+    /// Name of the distance column
+    #[arg(
+        long,
+        default_value = "distance_km",
+        help = "Name of the appended distance column, in kilometres"
+    )]
+    pub into: String,
+}
+
+impl GeoDistArgs {
+    fn run(&self, context: &mut Context) {
+        let target = match (&self.to_coords, self.near) {
+            (Some(columns), _) => DistanceTarget::Columns(columns.clone()),
+            (None, Some(circle)) => DistanceTarget::Near(circle),
+            (None, None) => unreachable!("clap requires --to-coords or --near"),
+        };
+        let table = context.load(&self.input);
+        let with_distance = geo::add_distance(&table, &self.from_coords, &target, &self.into)
+            .unwrap_or_else(|error| context.fail(Some(&self.input), &error));
+        context.write_table(&with_distance);
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct DeriveArgs {
+    /// Table to extend
+    pub input: PathBuf,
+
+    /// Source column
+    #[arg(long, help = "Column the new value is computed from")]
+    pub column: String,
+
+    /// Transform to apply
+    #[arg(long, value_enum, help = "Transform computing the new column")]
+    pub transform: Transform,
+
+    /// Name of the new column
+    #[arg(
+        long,
+        help = "Name of the appended column [default: <COLUMN>_domain, <COLUMN>_host, ...]"
+    )]
+    pub into: Option<String>,
+}
+
+impl DeriveArgs {
+    fn run(&self, context: &mut Context) {
+        let table = context.load(&self.input);
+        let into = self
+            .into
+            .clone()
+            .unwrap_or_else(|| self.transform.column_name(&self.column));
+        let derived = derive_column(&table, &self.column, self.transform, &into)
+            .unwrap_or_else(|error| context.fail(Some(&self.input), &error));
+        context.write_table(&derived);
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct HashArgs {
+    /// Table to extend
+    pub input: PathBuf,
+
+    /// Columns hashed together
+    #[arg(
+        long,
+        value_delimiter = ',',
+        required = true,
+        help = "Comma-separated columns hashed together, in order"
+    )]
+    pub columns: Vec<String>,
+
+    /// Name of the digest column
+    #[arg(
+        long,
+        default_value = "hash",
+        help = "Name of the appended digest column"
+    )]
+    pub into: String,
+
+    /// Digest algorithm
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = HashAlgorithm::Sha256,
+        help = "Digest algorithm"
+    )]
+    pub algo: HashAlgorithm,
+
+    /// Secret mixed into every digest
+    #[arg(
+        long,
+        env = "TABLES_HASH_SALT",
+        hide_env_values = true,
+        default_value = "",
+        hide_default_value = true,
+        help = "Secret prepended to the hashed fields, so digests can't be matched against known values"
+    )]
+    pub salt: String,
+}
+
+impl HashArgs {
+    fn run(&self, context: &mut Context) {
+        let table = context.load(&self.input);
+        let hashed = add_checksum(&table, &self.columns, self.algo, &self.salt, &self.into)
+            .unwrap_or_else(|error| context.fail(Some(&self.input), &error));
+        context.write_table(&hashed);
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct ShareArgs {
+    /// Table to extend
+    pub input: PathBuf,
+
+    /// Numeric column to take shares of
+    #[arg(long, help = "Numeric column whose total the percentages are of")]
+    pub column: String,
+
+    /// Name of the percentage column
+    #[arg(long, help = "Name of the appended column [default: <column>_pct]")]
+    pub into: Option<String>,
+
+    /// Columns grouping rows into separate totals
+    #[arg(
+        long,
+        value_delimiter = ',',
+        help = "Comma-separated columns whose values group rows, each group totalling 100"
+    )]
+    pub partition_by: Vec<String>,
+
+    /// Precision of the percentages
+    #[arg(long, default_value_t = 2, help = "Digits after the decimal point")]
+    pub decimals: usize,
+}
+
+impl ShareArgs {
+    fn run(&self, context: &mut Context) {
+        let table = context.load(&self.input);
+        let into = self
+            .into
+            .clone()
+            .unwrap_or_else(|| format!("{}_pct", self.column));
+        let shared = add_share(
+            &table,
+            &self.column,
+            &self.partition_by,
+            &into,
+            self.decimals,
+        )
+        .unwrap_or_else(|error| context.fail(Some(&self.input), &error));
+        context.write_table(&shared);
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct BinArgs {
+    /// Table to extend
+    pub input: PathBuf,
+
+    /// Numeric column to bin
+    #[arg(long, help = "Numeric column to cut into buckets")]
+    pub column: String,
+
+    /// Bucket boundaries
+    #[arg(
+        long,
+        value_delimiter = ',',
+        required_unless_present = "quantiles",
+        conflicts_with = "quantiles",
+        help = "Comma-separated increasing bucket edges, e.g. 0,18,35,60,120; buckets include their lower edge"
+    )]
+    pub edges: Vec<f64>,
+
+    /// Number of equal-count buckets
+    #[arg(
+        long,
+        help = "Cut into this many buckets of about equal row counts, labelled Q1, Q2, ..."
+    )]
+    pub quantiles: Option<NonZeroUsize>,
+
+    /// Name of the bucket column
+    #[arg(long, help = "Name of the appended column [default: <column>_bin]")]
+    pub into: Option<String>,
+}
+
+impl BinArgs {
+    fn run(&self, context: &mut Context) {
+        let table = context.load(&self.input);
+        let bins = match self.quantiles {
+            Some(count) => Bins::Quantiles(count.get()),
+            None => Bins::Edges(self.edges.clone()),
+        };
+        let into = self
+            .into
+            .clone()
+            .unwrap_or_else(|| format!("{}_bin", self.column));
+        let binned = add_bins(&table, &self.column, &bins, &into)
+            .unwrap_or_else(|error| context.fail(Some(&self.input), &error));
+        context.write_table(&binned);
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct AddColArgs {
+    /// Table to extend
+    pub input: PathBuf,
+
+    /// Name of the new column
+    pub name: String,
+
+    /// Fill the column with UUIDs
+    #[arg(
+        long,
+        required = true,
+        help = "Fill the column with UUIDs, random (v4) by default"
+    )]
+    pub uuid: bool,
+
+    /// Time-ordered UUIDs
+    #[arg(
+        long,
+        conflicts_with = "namespace",
+        help = "Generate time-ordered UUIDs (v7) that sort in row order"
+    )]
+    pub v7: bool,
+
+    /// Namespace of name-based UUIDs
+    #[arg(
+        long,
+        value_parser = parse_namespace,
+        requires = "columns",
+        help = "Generate name-based UUIDs (v5) in this namespace: dns, url, oid, x500 or a UUID"
+    )]
+    pub namespace: Option<Uuid>,
+
+    /// Columns name-based UUIDs are derived from
+    #[arg(
+        long,
+        value_delimiter = ',',
+        requires = "namespace",
+        help = "Comma-separated columns whose values determine each name-based UUID"
+    )]
+    pub columns: Vec<String>,
+}
+
+impl AddColArgs {
+    fn run(&self, context: &mut Context) {
+        let kind = match self.namespace {
+            Some(namespace) => UuidKind::Named {
+                namespace,
+                columns: self.columns.clone(),
+            },
+            None if self.v7 => UuidKind::TimeOrdered,
+            None => UuidKind::Random,
+        };
+        let table = context.load(&self.input);
+        let extended = add_uuid_column(&table, &self.name, &kind)
+            .unwrap_or_else(|error| context.fail(Some(&self.input), &error));
+        context.write_table(&extended);
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct SchemaArgs {
+    /// Table to describe
+    pub input: PathBuf,
+
+    /// Schema language
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = SchemaFormat::JsonSchema,
+        help = "Schema language to write"
+    )]
+    pub format: SchemaFormat,
+
+    /// Largest enum listed for a text column
+    #[arg(
+        long,
+        default_value_t = 10,
+        help = "List the values of text columns with at most this many distinct values as an enum"
+    )]
+    pub max_enum: usize,
+}
+
+impl SchemaArgs {
+    fn run(&self, context: &mut Context) {
+        let table = context.load(&self.input);
+        let schema = match self.format {
+            SchemaFormat::JsonSchema => {
+                json_schema(&self.input.display().to_string(), &table, self.max_enum)
+            }
+        };
+        // Serializing a Value never fails.
+        let mut output = serde_json::to_string_pretty(&schema).unwrap();
+        output.push('\n');
+        context.write_output(output);
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct StatsArgs {
+    /// Table to summarize
+    pub input: PathBuf,
+
+    /// Earlier version of the table
+    #[arg(
+        long,
+        help = "Earlier version of the table to show statistics and their deltas against"
+    )]
+    pub baseline: Option<PathBuf>,
+
+    /// Estimate distinct counts and medians
+    #[arg(
+        long,
+        help = "Estimate distinct counts with HyperLogLog and medians from a 10,000-value sample, for huge files"
+    )]
+    pub approx: bool,
+}
+
+impl StatsArgs {
+    fn run(&self, context: &mut Context) {
+        let table = context.load(&self.input);
+        let stats = match &self.baseline {
+            Some(path) => {
+                let baseline = context.load(path);
+                stats::compare_to_baseline(&table, &baseline, self.approx)
+            }
+            None => stats::summarize(&table, self.approx),
+        }
+        .unwrap_or_else(|error| context.fail(Some(&self.input), &error));
+        context.write_table(&stats);
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct AssertArgs {
+    /// Table to check
+    pub input: PathBuf,
+
+    /// Columns the table must have
+    #[arg(
+        long,
+        value_delimiter = ',',
+        help = "Comma-separated columns the table must have, in any order"
+    )]
+    pub columns: Vec<String>,
+
+    /// Fewest rows allowed
+    #[arg(
+        long,
+        value_parser = parse_row_count,
+        help = "Least number of data rows, e.g. 100 or 1e3"
+    )]
+    pub min_rows: Option<usize>,
+
+    /// Most rows allowed
+    #[arg(
+        long,
+        value_parser = parse_row_count,
+        help = "Greatest number of data rows, e.g. 1e6"
+    )]
+    pub max_rows: Option<usize>,
+
+    /// Columns that must be filled in
+    #[arg(
+        long,
+        value_delimiter = ',',
+        help = "Comma-separated columns that must not have empty cells"
+    )]
+    pub no_nulls: Vec<String>,
+}
+
+impl AssertArgs {
+    fn run(&self, context: &mut Context) -> Outcome {
+        let table = context.load(&self.input);
+        let assertions = TableAssertions {
+            columns: self.columns.clone(),
+            min_rows: self.min_rows,
+            max_rows: self.max_rows,
+            no_nulls: self.no_nulls.clone(),
+        };
+        let violations = assertions.check(&table);
+        if violations.is_empty() {
+            return Outcome::Success;
+        }
+
+        let report: String = violations
+            .iter()
+            .map(|violation| format!("{}: {}\n", self.input.display(), violation))
+            .collect();
+        context.write_output(report);
+        Outcome::Violations
+    }
+}
+
+// Git runs an external diff driver with these 7 arguments, plus the new
+// path and a rename header for renamed files.
+#[derive(Args, Debug)]
+pub struct GitDiffArgs {
+    /// Path of the file in the repository
+    pub path: String,
+
+    /// Old version, `/dev/null` for an added file
+    pub old_file: PathBuf,
+
+    #[arg(hide = true)]
+    pub old_hex: String,
+
+    #[arg(hide = true)]
+    pub old_mode: String,
+
+    /// New version, `/dev/null` for a deleted file
+    pub new_file: PathBuf,
+
+    #[arg(hide = true)]
+    pub new_hex: String,
+
+    #[arg(hide = true)]
+    pub new_mode: String,
+
+    #[arg(hide = true)]
+    pub rename: Vec<String>,
+}
+
+impl GitDiffArgs {
+    /// What git passes in place of the missing side of an added or
+    /// deleted file
+    const NULL_FILE: &'static str = "/dev/null";
+
+    /// Prints the diff; always succeeds, since git stops at the first
+    /// driver exiting non-zero
+    fn run(&self, context: &mut Context) {
+        let new_path = self.rename.first().unwrap_or(&self.path);
+        let old = Self::load_side(context, &self.old_file);
+        let new = Self::load_side(context, &self.new_file);
+        // The missing side of an added or deleted file is the other side
+        // with no rows, so every row shows up as added or removed.
+        let empty_like = |table: &Table| {
+            Table::with_header_and_data(
+                table.header().into_iter().map(String::from).collect(),
+                Vec::new(),
+            )
+            .unwrap_or_else(|error| context.fail(Some(Path::new(&self.path)), &error))
+        };
+        let (old, new) = match (old, new) {
+            (Some(old), Some(new)) => (old, new),
+            (Some(old), None) => {
+                let new = empty_like(&old);
+                (old, new)
+            }
+            (None, Some(new)) => (empty_like(&new), new),
+            (None, None) => return,
+        };
+
+        let label = |file: &Path, prefix: &str, path: &str| {
+            if file == Path::new(Self::NULL_FILE) {
+                Self::NULL_FILE.to_string()
+            } else {
+                format!("{}/{}", prefix, path)
+            }
+        };
+        let diff = context.timings.time("diff", || {
+            diff::diff_tables(&old, &new, &CellComparison::default())
+        });
+        let report = diff::format_report(
+            &diff,
+            &label(&self.old_file, "a", &self.path),
+            &label(&self.new_file, "b", new_path),
+            &context.locale,
+            false,
+        );
+        context.write_output(report);
+    }
+
+    fn load_side(context: &mut Context, file: &Path) -> Option<Table> {
+        (file != Path::new(Self::NULL_FILE)).then(|| context.load(file))
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct CompletionsArgs {
+    /// Shell to complete for
+    #[arg(value_enum, help = "Shell the completion script is for")]
+    pub shell: Shell,
+}
+
+impl CompletionsArgs {
+    fn run(&self, context: &mut Context) {
+        let mut command = Cli::command();
+        let name = command.get_name().to_string();
+        let mut script = Vec::new();
+        clap_complete::generate(self.shell, &mut command, name, &mut script);
+        context.write_output(script);
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct ManArgs {
+    /// Directory receiving the pages
+    #[arg(
+        long,
+        help = "Write the main page and one page per subcommand to this directory"
+    )]
+    pub out_dir: Option<PathBuf>,
+}
+
+impl ManArgs {
+    fn run(&self, context: &mut Context) {
+        let mut pages = man_page::render_pages(Cli::command())
+            .unwrap_or_else(|error| context.fail(None, &error));
+        let Some(out_dir) = &self.out_dir else {
+            let (_, main_page) = pages.swap_remove(0);
+            context.write_output(main_page);
+            return;
+        };
+
+        context.create_dir(out_dir);
+        for (file_name, page) in pages {
+            context.write_file(&out_dir.join(file_name), page);
+        }
+    }
+}
+
+/// Writes a table to a file of its own, ignoring `--output`.
+fn write_table_to(context: &mut Context, table: &Table, path: &Path) {
+    let contents = context.render_table(table);
+    context.write_file(path, contents);
+}
+
+/// Turns a column value into something safe to put in a file name.
+fn file_label(value: &str) -> String {
+    if value.is_empty() {
+        return "empty".to_string();
+    }
+    value
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
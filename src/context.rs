@@ -0,0 +1,933 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process,
+};
+
+use clap::{builder::BoolishValueParser, ArgAction, Args};
+use log::{debug, info, warn, Level, LevelFilter};
+
+use crate::aggregate::{footer_row, subtotal_rows, Aggregate};
+use crate::atomic_write::AtomicWriter;
+use crate::audit_log::AuditRecord;
+use crate::bars::{add_bar, add_sparkline};
+use crate::column_metadata;
+use crate::column_order::ColumnOrder;
+use crate::dry_run;
+use crate::error_output::{format_error, ErrorFormat};
+use crate::explain;
+use crate::header_map::HeaderMap;
+use crate::highlight::{highlight_cells, Highlight};
+use crate::input;
+use crate::load::{self, LoadOptions, LoadedTable};
+use crate::locale::LocaleProfile;
+use crate::pager;
+use crate::table::{Table, TableError};
+use crate::table_parser::{parse_delimiter, InputFormat, RowError, TableType};
+use crate::table_writer::{self, OutputFormat, OutputTarget, WriteOptions};
+use crate::timings::Timings;
+
+// Options shared by every command, each also read from a TABLES_*
+// environment variable that the command line overrides
+#[derive(Args, Debug, Default)]
+pub struct GlobalArgs {
+    /// Named option set from the config file
+    #[arg(
+        long,
+        global = true,
+        env = "TABLES_PROFILE",
+        help = "Apply the options of a [profile.NAME] section of the config file; options given otherwise take precedence"
+    )]
+    pub profile: Option<String>,
+
+    /// Config file holding profiles
+    #[arg(
+        long,
+        global = true,
+        env = "TABLES_CONFIG",
+        value_name = "FILE",
+        help = "Read profiles from this TOML file [default: $XDG_CONFIG_HOME/tables/config.toml or ~/.config/tables/config.toml]"
+    )]
+    pub config: Option<PathBuf>,
+
+    /// Output files
+    #[arg(
+        short,
+        long,
+        global = true,
+        env = "TABLES_OUTPUT",
+        value_name = "PATH[:FORMAT]",
+        value_parser = OutputTarget::parse,
+        help = "Write output to file instead of stdout; repeat to write several, each in the format named after ':', else --to, else the one a .csv, .prom or .arrows extension names"
+    )]
+    pub output: Vec<OutputTarget>,
+
+    /// Explain how the inputs would be read
+    #[arg(
+        long,
+        global = true,
+        env = "TABLES_EXPLAIN",
+        value_parser = BoolishValueParser::new(),
+        help = "Print the detected format, header decision and schema of each table without running anything"
+    )]
+    pub explain: bool,
+
+    /// Password for encrypted zip archives
+    #[arg(
+        long,
+        global = true,
+        env = "TABLES_ZIP_PASSWORD",
+        hide_env_values = true,
+        help = "Password for encrypted .zip inputs"
+    )]
+    pub password: Option<String>,
+
+    /// Error output format
+    #[arg(
+        long,
+        global = true,
+        env = "TABLES_ERROR_FORMAT",
+        value_enum,
+        default_value_t = ErrorFormat::Human,
+        help = "Report errors as human-readable text or JSON"
+    )]
+    pub error_format: ErrorFormat,
+
+    /// Skip malformed rows instead of failing
+    #[arg(
+        long,
+        global = true,
+        env = "TABLES_SKIP_BAD_ROWS",
+        value_parser = BoolishValueParser::new(),
+        help = "Skip rows that don't match the table's column count and report how many were dropped"
+    )]
+    pub skip_bad_rows: bool,
+
+    /// File receiving rows skipped by --skip-bad-rows
+    #[arg(
+        long,
+        global = true,
+        env = "TABLES_ERRORS_OUT",
+        requires = "skip_bad_rows",
+        help = "Write skipped rows with the reason they were rejected to this CSV file"
+    )]
+    pub errors_out: Option<PathBuf>,
+
+    /// TOML file mapping incoming column names to canonical ones
+    #[arg(
+        long,
+        global = true,
+        env = "TABLES_HEADER_MAP",
+        help = "Rename incoming columns to canonical names using a TOML mapping file"
+    )]
+    pub header_map: Option<PathBuf>,
+
+    /// Template ordering the columns of written tables
+    #[arg(
+        long,
+        global = true,
+        env = "TABLES_COLUMN_ORDER",
+        help = "Order output columns by a template file, one column per line, '*' for the rest"
+    )]
+    pub column_order: Option<PathBuf>,
+
+    /// Locale used to render numbers and dates
+    #[arg(
+        long,
+        global = true,
+        env = "TABLES_LOCALE",
+        value_parser = LocaleProfile::from_tag,
+        requires = "locale_columns",
+        help = "Render numbers and dates of the --locale-columns for a locale, e.g. de-DE (decimal comma, day-first dates)"
+    )]
+    pub locale: Option<LocaleProfile>,
+
+    /// Columns rendered for the locale
+    #[arg(
+        long,
+        global = true,
+        env = "TABLES_LOCALE_COLUMNS",
+        value_delimiter = ',',
+        requires = "locale",
+        help = "Comma-separated columns whose numbers and dates --locale renders; other columns, e.g. IDs and postal codes, keep their digits"
+    )]
+    pub locale_columns: Vec<String>,
+
+    /// Input format, detected when not given
+    #[arg(
+        long,
+        global = true,
+        env = "TABLES_FORMAT",
+        value_enum,
+        help = "Read inputs in this format instead of detecting it"
+    )]
+    pub from: Option<InputFormat>,
+
+    /// Field delimiter of CSV input and output
+    #[arg(
+        long,
+        global = true,
+        env = "TABLES_DELIMITER",
+        value_parser = parse_delimiter,
+        help = "Delimiter for reading and writing CSV: a character, '\\t', 'tab' or 'space'"
+    )]
+    pub delimiter: Option<char>,
+
+    /// Inputs whose first line is the header
+    #[arg(
+        long,
+        global = true,
+        env = "TABLES_HEADER",
+        num_args = 0..,
+        value_delimiter = ',',
+        require_equals = true,
+        value_name = "INPUTS",
+        help = "Treat the first line as the header instead of guessing; limit to inputs by position with --header=1,2"
+    )]
+    pub header: Option<Vec<usize>>,
+
+    /// Inputs without a header line
+    #[arg(
+        long,
+        global = true,
+        env = "TABLES_NO_HEADER",
+        num_args = 0..,
+        value_delimiter = ',',
+        require_equals = true,
+        value_name = "INPUTS",
+        help = "Treat the first line as data instead of guessing; limit to inputs by position with --no-header=2"
+    )]
+    pub no_header: Option<Vec<usize>>,
+
+    /// Refuse to guess how inputs are laid out
+    #[arg(
+        long,
+        global = true,
+        env = "TABLES_STRICT",
+        value_parser = BoolishValueParser::new(),
+        conflicts_with = "skip_bad_rows",
+        help = "Fail instead of guessing: every input needs --from or --delimiter (both for CSV) and --header or --no-header, CSV quotes are '\"', malformed rows are errors, and --where and sort compare cells as text"
+    )]
+    pub strict: bool,
+
+    /// Output format for tables
+    #[arg(
+        long,
+        global = true,
+        env = "TABLES_OUTPUT_FORMAT",
+        value_enum,
+        help = "Write tables in this format [default: csv, or the input's format with --in-place]"
+    )]
+    pub to: Option<OutputFormat>,
+
+    /// Aggregates rendered in a footer row
+    #[arg(
+        long,
+        global = true,
+        env = "TABLES_FOOTER",
+        value_delimiter = ',',
+        value_parser = Aggregate::parse,
+        help = "Append a footer of comma-separated aggregates over the written rows, e.g. 'sum(amount),count(*)'; it is set apart from the data rows in every format"
+    )]
+    pub footer: Vec<Aggregate>,
+
+    /// Rules styling matching cells
+    #[arg(
+        long,
+        global = true,
+        env = "TABLES_HIGHLIGHT",
+        value_parser = Highlight::parse,
+        help = "Style the cells of ASCII output that match a condition, e.g. 'amount < 0 => red' or 'status == FAILED => bold'; repeat for more rules. Styles: bold, dim, italic, underline, red, green, yellow, blue, magenta, cyan"
+    )]
+    pub highlight: Vec<Highlight>,
+
+    /// Numeric columns drawn as bars
+    #[arg(
+        long,
+        global = true,
+        env = "TABLES_BAR",
+        value_name = "COLUMN",
+        help = "Append a bar proportional to each value of a numeric column to ASCII output, named '<column> bar'; repeat for more columns"
+    )]
+    pub bar: Vec<String>,
+
+    /// Numeric columns drawn as a sparkline per row
+    #[arg(
+        long,
+        global = true,
+        env = "TABLES_SPARK",
+        value_delimiter = ',',
+        value_name = "COLUMNS",
+        help = "Append a 'spark' column to ASCII output charting each row's values of comma-separated numeric columns, e.g. 'q1,q2,q3,q4'"
+    )]
+    pub spark: Vec<String>,
+
+    /// Column whose runs of equal values get subtotal rows
+    #[arg(
+        long,
+        global = true,
+        env = "TABLES_GROUP_BY",
+        requires = "subtotals",
+        help = "Set apart runs of rows with the same value in this column in ASCII output, each followed by a subtotal row, and end with a total row; sort by the column first to get one group per value"
+    )]
+    pub group_by: Option<String>,
+
+    /// Aggregates of the subtotal and total rows
+    #[arg(
+        long,
+        global = true,
+        env = "TABLES_SUBTOTALS",
+        value_delimiter = ',',
+        value_parser = Aggregate::parse,
+        requires = "group_by",
+        help = "Comma-separated aggregates for the subtotal and total rows of --group-by, e.g. 'sum(amount),count(*)'"
+    )]
+    pub subtotals: Vec<Aggregate>,
+
+    /// Rewrite the input file with the result
+    #[arg(
+        long,
+        global = true,
+        env = "TABLES_IN_PLACE",
+        value_parser = BoolishValueParser::new(),
+        conflicts_with = "output",
+        help = "Replace the input file with the result, keeping its format and delimiter unless --to or --delimiter is given"
+    )]
+    pub in_place: bool,
+
+    /// Report writes instead of making them
+    #[arg(
+        long,
+        global = true,
+        env = "TABLES_DRY_RUN",
+        value_parser = BoolishValueParser::new(),
+        help = "Report which files would be created, overwritten or left unchanged, with row counts, without writing any"
+    )]
+    pub dry_run: bool,
+
+    /// Also report the rows each dry-run write would change
+    #[arg(
+        long,
+        global = true,
+        env = "TABLES_DRY_RUN_DIFF",
+        value_parser = BoolishValueParser::new(),
+        requires = "dry_run",
+        help = "With --dry-run, also print a diff of the rows each overwritten table would change"
+    )]
+    pub dry_run_diff: bool,
+
+    /// Suffix of the copy --in-place keeps of the original
+    #[arg(
+        long,
+        global = true,
+        env = "TABLES_BACKUP",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = ".bak",
+        value_name = "SUFFIX",
+        requires = "in_place",
+        help = "With --in-place, keep the original file with this suffix appended [default: .bak]"
+    )]
+    pub backup: Option<String>,
+
+    /// Never page output
+    #[arg(
+        long,
+        global = true,
+        env = "TABLES_NO_PAGER",
+        value_parser = BoolishValueParser::new(),
+        help = "Print to the terminal directly instead of through $PAGER (default 'less -R') when output is longer than the screen"
+    )]
+    pub no_pager: bool,
+
+    /// Re-run whenever an input changes
+    #[arg(
+        long,
+        global = true,
+        env = "TABLES_WATCH",
+        value_parser = BoolishValueParser::new(),
+        conflicts_with = "in_place",
+        help = "Watch the input files and re-run the command on every change, clearing the terminal between runs"
+    )]
+    pub watch: bool,
+
+    /// Report time and memory per stage
+    #[arg(
+        long,
+        global = true,
+        env = "TABLES_TIMINGS",
+        value_parser = BoolishValueParser::new(),
+        help = "Print wall time and peak memory of each stage (read, detect, parse, render, write) to stderr"
+    )]
+    pub timings: bool,
+
+    /// Directory for temp files staged before atomic replacement
+    #[arg(
+        long,
+        global = true,
+        env = "TABLES_TEMP_DIR",
+        help = "Stage output files in this directory instead of next to their destination"
+    )]
+    pub temp_dir: Option<PathBuf>,
+
+    /// JSON Lines file recording every run
+    #[arg(
+        long,
+        global = true,
+        env = "TABLES_AUDIT_LOG",
+        value_name = "FILE",
+        help = "Append a JSON line per completed run to this file: time, command, inputs, row counts and SHA-256 of each output"
+    )]
+    pub audit_log: Option<PathBuf>,
+
+    /// Diagnostics verbosity
+    #[arg(
+        short,
+        long,
+        global = true,
+        action = ArgAction::Count,
+        help = "Log how inputs are read: -v for format, header and row counts, -vv for column types too"
+    )]
+    pub verbose: u8,
+
+    /// Print nothing but the result
+    #[arg(
+        short,
+        long,
+        global = true,
+        env = "TABLES_QUIET",
+        value_parser = BoolishValueParser::new(),
+        conflicts_with = "verbose",
+        help = "Suppress warnings and diagnostics, printing only the result and fatal errors"
+    )]
+    pub quiet: bool,
+}
+
+/// State shared by a command run: loading options, output and diagnostics
+pub struct Context {
+    pub args: GlobalArgs,
+    pub options: LoadOptions,
+    pub writer: AtomicWriter,
+    pub timings: Timings,
+    /// Formatting applied to values in rendered output
+    pub locale: LocaleProfile,
+    column_order: Option<ColumnOrder>,
+    rejected: Vec<(String, RowError)>,
+    stdin_read: bool,
+    /// Input rewritten by `--in-place`, with the layout it was read in
+    in_place: Option<(PathBuf, Option<TableType>)>,
+    /// Inputs loaded so far, giving the position `--header=N` refers to
+    inputs_loaded: usize,
+    /// What this run read and wrote, kept with `--audit-log`
+    audit: Option<AuditRecord>,
+    /// Rows of the table rendered last, reported by `--dry-run`
+    rendered_rows: Option<usize>,
+    /// Set once the reader of stdout has gone away; later output is dropped
+    stdout_closed: bool,
+}
+
+impl Context {
+    /// Reads the files named by global options, exiting on failure
+    pub fn new(args: GlobalArgs) -> Self {
+        init_logging(args.verbose, args.quiet);
+
+        let header_map = args
+            .header_map
+            .as_deref()
+            .map(HeaderMap::load)
+            .transpose()
+            .unwrap_or_else(|error| fail(&args, args.header_map.as_deref(), &error));
+        let mut positions = args.header.iter().chain(&args.no_header).flatten();
+        let is_bare =
+            |positions: &Option<Vec<usize>>| positions.as_ref().is_some_and(Vec::is_empty);
+        if positions.any(|position| {
+            *position == 0
+                || covers_input(&args.header, *position) && covers_input(&args.no_header, *position)
+        }) || (is_bare(&args.header) && is_bare(&args.no_header))
+        {
+            let error = TableError::InvalidArgument(
+                "--header and --no-header take distinct input positions, starting at 1".to_string(),
+            );
+            fail(&args, None, &error);
+        }
+        let column_order = args
+            .column_order
+            .as_deref()
+            .map(ColumnOrder::load)
+            .transpose()
+            .unwrap_or_else(|error| fail(&args, args.column_order.as_deref(), &error));
+
+        Context {
+            options: LoadOptions {
+                skip_bad_rows: args.skip_bad_rows,
+                header_map,
+                format: args.from,
+                delimiter: args.delimiter,
+                has_header: None,
+                strict: args.strict,
+            },
+            writer: AtomicWriter::new(args.temp_dir.clone()),
+            timings: Timings::new(args.timings),
+            locale: args
+                .locale
+                .clone()
+                .map(|locale| LocaleProfile {
+                    columns: args.locale_columns.clone(),
+                    ..locale
+                })
+                .unwrap_or_default(),
+            column_order,
+            rejected: Vec::new(),
+            stdin_read: false,
+            in_place: None,
+            inputs_loaded: 0,
+            audit: None,
+            stdout_closed: false,
+            rendered_rows: None,
+            args,
+        }
+    }
+
+    /// Starts recording the run of `command` if `--audit-log` is given
+    pub fn start_audit(&mut self, command: &str) {
+        if self.args.audit_log.is_some() {
+            self.audit = Some(AuditRecord::new(command));
+        }
+    }
+
+    /// Reports an error in the requested format and exits with status 2
+    pub fn fail(&self, file: Option<&Path>, error: &(dyn Error + 'static)) -> ! {
+        fail(&self.args, file, error)
+    }
+
+    /// Loads a table, exiting on failure
+    ///
+    /// Rows skipped by `--skip-bad-rows` are kept for `--errors-out`.
+    pub fn load(&mut self, path: &Path) -> Table {
+        let mut loaded = self
+            .load_file(path)
+            .unwrap_or_else(|error| self.fail(Some(path), &*error));
+        let name = path.display().to_string();
+        self.rejected.extend(
+            loaded
+                .row_errors
+                .drain(..)
+                .map(|row_error| (name.clone(), row_error)),
+        );
+        loaded.table
+    }
+
+    /// Prints how each input would be read instead of running the command
+    pub fn explain(&mut self, paths: &[PathBuf]) {
+        for path in paths {
+            if self.stdout_closed {
+                break;
+            }
+            let loaded = self
+                .load_file(path)
+                .unwrap_or_else(|error| self.fail(Some(path), &*error));
+            let name = path.display().to_string();
+            let explanation = self.timings.time(format!("render {}", name), || {
+                explain::explain_table(&name, &loaded, &self.options)
+            });
+            self.print(explanation.as_bytes());
+            self.rejected.extend(
+                loaded
+                    .row_errors
+                    .into_iter()
+                    .map(|row_error| (name.clone(), row_error)),
+            );
+        }
+    }
+
+    /// Makes output replace `path`, the input of the command, instead of
+    /// going to stdout
+    pub fn edit_in_place(&mut self, path: &Path) {
+        if input::is_stdin(path) {
+            let error = TableError::InvalidArgument(
+                "--in-place needs an input file, not standard input".to_string(),
+            );
+            self.fail(None, &error);
+        }
+        self.in_place = Some((path.to_path_buf(), None));
+    }
+
+    /// Writes command output to `--output`, the input with `--in-place`, or
+    /// stdout without either
+    pub fn write_output(&mut self, contents: impl AsRef<[u8]>) {
+        if let Some((path, _)) = self.in_place.clone() {
+            if let Some(suffix) = &self.args.backup {
+                let mut backup = path.clone().into_os_string();
+                backup.push(suffix);
+                let original =
+                    fs::read(&path).unwrap_or_else(|error| fail(&self.args, Some(&path), &error));
+                // The backup is a plain copy, not the rendered table.
+                let rows = self.rendered_rows.take();
+                self.write_file(&PathBuf::from(backup), original);
+                self.rendered_rows = rows;
+            }
+            return self.write_file(&path, contents);
+        }
+
+        if self.args.output.is_empty() {
+            if let Some(audit) = &mut self.audit {
+                audit.add_output(None, contents.as_ref());
+            }
+            if !self.args.no_pager && pager::page(contents.as_ref()) {
+                return;
+            }
+            return self.print(contents.as_ref());
+        }
+        let rows = self.rendered_rows;
+        for target in self.args.output.clone() {
+            self.rendered_rows = rows;
+            self.write_file(&target.path, contents.as_ref());
+        }
+    }
+
+    /// Writes to stdout, exiting on failure
+    ///
+    /// A reader that has gone away, e.g. `head` after its lines, is not a
+    /// failure: the output is dropped, and the run still finishes and
+    /// exits with its command's status.
+    fn print(&mut self, contents: &[u8]) {
+        if self.stdout_closed {
+            return;
+        }
+        let mut stdout = io::stdout().lock();
+        match stdout.write_all(contents).and_then(|()| stdout.flush()) {
+            Err(error) if error.kind() == io::ErrorKind::BrokenPipe => self.stdout_closed = true,
+            result => result.unwrap_or_else(|error| fail(&self.args, None, &error)),
+        }
+    }
+
+    /// Writes a file of its own, ignoring `--output`, exiting on failure
+    ///
+    /// With `--dry-run` only reports what the write would do.
+    pub fn write_file(&mut self, path: &Path, contents: impl AsRef<[u8]>) {
+        let rows = self.rendered_rows.take();
+        if self.args.dry_run {
+            eprintln!("{}", dry_run::describe_write(path, contents.as_ref(), rows));
+            if self.args.dry_run_diff {
+                if let Some(changes) =
+                    dry_run::describe_changes(path, contents.as_ref(), &self.locale)
+                {
+                    eprint!("{}", changes);
+                }
+            }
+            return;
+        }
+        if let Some(audit) = &mut self.audit {
+            audit.add_output(Some(path), contents.as_ref());
+        }
+        self.timings
+            .time("write", || self.writer.write(path, contents))
+            .unwrap_or_else(|error| fail(&self.args, Some(path), &error));
+    }
+
+    /// Creates an output directory, which `--dry-run` leaves to be created
+    pub fn create_dir(&self, path: &Path) {
+        if !self.args.dry_run {
+            fs::create_dir_all(path).unwrap_or_else(|error| self.fail(Some(path), &error));
+        }
+    }
+
+    /// Renders a table in the `--to` format, applying `--column-order` and
+    /// `--locale` if given
+    pub fn render_table(&mut self, table: &Table) -> Vec<u8> {
+        if let Some(audit) = &mut self.audit {
+            audit.rows_out += table.row_count();
+        }
+        self.render_as(table, None)
+    }
+
+    /// Renders a table like `render_table`, in `format` if given
+    fn render_as(&mut self, table: &Table, format: Option<OutputFormat>) -> Vec<u8> {
+        let ordered = self
+            .column_order
+            .as_ref()
+            .map(|column_order| column_order.apply(table))
+            .transpose()
+            .unwrap_or_else(|error| self.fail(None, &error));
+        let table = ordered.as_ref().unwrap_or(table);
+        let (format, delimiter) = match format {
+            Some(format) => (format, self.args.delimiter.unwrap_or(',')),
+            None => self
+                .output_format()
+                .unwrap_or_else(|error| self.fail(None, &error)),
+        };
+        // Only ASCII output shows bars, sparklines, highlights and subtotals
+        let mut charted = None;
+        if format == OutputFormat::Ascii {
+            for column in &self.args.bar {
+                let bars = add_bar(charted.as_ref().unwrap_or(table), column);
+                charted = Some(bars.unwrap_or_else(|error| self.fail(None, &error)));
+            }
+            if !self.args.spark.is_empty() {
+                let sparklines = add_sparkline(charted.as_ref().unwrap_or(table), &self.args.spark);
+                charted = Some(sparklines.unwrap_or_else(|error| self.fail(None, &error)));
+            }
+        }
+        let table = charted.as_ref().unwrap_or(table);
+        let footer = (!self.args.footer.is_empty())
+            .then(|| footer_row(table, &self.args.footer))
+            .transpose()
+            .unwrap_or_else(|error| self.fail(None, &error));
+        let (highlighted, subtotals) = match format {
+            OutputFormat::Ascii => {
+                let highlighted = highlight_cells(table, &self.args.highlight, self.args.strict);
+                let subtotals = self.args.group_by.as_ref().map_or(Ok(Vec::new()), |by| {
+                    subtotal_rows(table, by, &self.args.subtotals)
+                });
+                highlighted
+                    .and_then(|highlighted| Ok((highlighted, subtotals?)))
+                    .unwrap_or_else(|error| self.fail(None, &error))
+            }
+            _ => (HashMap::new(), Vec::new()),
+        };
+        let localized = self
+            .args
+            .locale
+            .is_some()
+            .then(|| self.locale.localize(table))
+            .transpose()
+            .unwrap_or_else(|error| self.fail(None, &error));
+        let table = localized.as_ref().unwrap_or(table);
+        self.rendered_rows = Some(table.row_count());
+        let writer = format.writer();
+        let options = WriteOptions {
+            delimiter,
+            footer,
+            highlighted,
+            subtotals,
+        };
+        self.timings
+            .time("render", || writer(table, &options))
+            .unwrap_or_else(|error| self.fail(None, &error))
+    }
+
+    /// Format and delimiter of written tables: `--to` and `--delimiter`,
+    /// else the input's layout with `--in-place`, else CSV
+    fn output_format(&self) -> Result<(OutputFormat, char), TableError> {
+        let (format, delimiter) = match (&self.args.to, &self.in_place) {
+            (None, Some((path, Some(table_type)))) => OutputFormat::matching(table_type)
+                .ok_or_else(|| {
+                    TableError::InvalidArgument(format!(
+                        "{} was read as {}, which can't be written back; pass --to",
+                        path.display(),
+                        table_type
+                    ))
+                })?,
+            _ => (self.args.to.unwrap_or_default(), None),
+        };
+        Ok((format, self.args.delimiter.or(delimiter).unwrap_or(',')))
+    }
+
+    /// Writes a table to each `--output` in its format, or stdout without
+    /// one; `--to` takes precedence over an output's extension
+    pub fn write_table(&mut self, table: &Table) {
+        let formats: Vec<Option<OutputFormat>> = self
+            .args
+            .output
+            .iter()
+            .map(|target| match (target.format, self.args.to) {
+                (None, None) => OutputFormat::from_extension(&target.path),
+                (format, _) => format,
+            })
+            .collect();
+        if formats.iter().all(Option::is_none) {
+            let contents = self.render_table(table);
+            return self.write_output(contents);
+        }
+        if let Some(audit) = &mut self.audit {
+            audit.rows_out += table.row_count();
+        }
+        for (target, format) in self.args.output.clone().iter().zip(formats) {
+            let contents = self.render_as(table, format);
+            self.write_file(&target.path, contents);
+        }
+    }
+
+    /// Writes skipped rows to `--errors-out`, appends the `--audit-log`
+    /// record and prints the timing report
+    pub fn finish(mut self) {
+        if let Some(errors_out) = &self.args.errors_out {
+            let output = table_writer::write_rejected_rows(
+                self.rejected
+                    .iter()
+                    .map(|(file, row_error)| (file.as_str(), row_error)),
+            );
+            let errors_out = errors_out.clone();
+            self.write_file(&errors_out, output);
+        }
+
+        if let (Some(audit), Some(audit_log)) = (&self.audit, &self.args.audit_log) {
+            if self.args.dry_run {
+                eprintln!("would append a record to {}", audit_log.display());
+            } else {
+                audit
+                    .append_to(audit_log)
+                    .unwrap_or_else(|error| fail(&self.args, Some(audit_log), &error));
+            }
+        }
+
+        if let Some(report) = self.timings.report() {
+            eprint!("{}", report);
+        }
+    }
+
+    /// Reads raw delimited input for a command that parses it itself,
+    /// exiting on failure
+    ///
+    /// The input counts towards `--header=N` positions and `--strict` is
+    /// checked as if it were read as CSV.
+    pub fn read_delimited(&mut self, path: &Path) -> String {
+        let data = self.start_input(path).and_then(|()| {
+            let options = LoadOptions {
+                format: Some(InputFormat::Csv),
+                delimiter: self.options.delimiter,
+                has_header: self.options.has_header,
+                strict: self.options.strict,
+                ..LoadOptions::default()
+            };
+            load::check_strict(&options)?;
+            Ok(input::read_input(path, self.args.password.as_deref())?)
+        });
+        data.unwrap_or_else(|error: Box<dyn Error>| self.fail(Some(path), &*error))
+    }
+
+    /// Tracks the position of the next input and its header decision
+    fn start_input(&mut self, path: &Path) -> Result<(), Box<dyn Error>> {
+        if input::is_stdin(path) {
+            if self.stdin_read {
+                return Err(TableError::InvalidArgument(
+                    "standard input can only be read once".to_string(),
+                )
+                .into());
+            }
+            self.stdin_read = true;
+        }
+
+        self.inputs_loaded += 1;
+        self.options.has_header = if covers_input(&self.args.header, self.inputs_loaded) {
+            Some(true)
+        } else if covers_input(&self.args.no_header, self.inputs_loaded) {
+            Some(false)
+        } else {
+            None
+        };
+        Ok(())
+    }
+
+    fn load_file(&mut self, path: &Path) -> Result<LoadedTable, Box<dyn Error>> {
+        self.start_input(path)?;
+        load::check_strict(&self.options)?;
+
+        let name = path.display();
+        let password = self.args.password.as_deref();
+        let data = self.timings.time(format!("read {}", name), || {
+            input::read_input(path, password)
+        })?;
+        let (table_type, has_header) = self.timings.time(format!("detect {}", name), || {
+            load::detect_layout(&data, &self.options)
+        });
+        if let Some((in_place, layout)) = &mut self.in_place {
+            if in_place == path {
+                *layout = Some(table_type);
+            }
+        }
+        let options = &self.options;
+        let mut loaded = self.timings.time(format!("parse {}", name), || {
+            load::load_with_layout(&data, table_type, has_header, options)
+        })?;
+
+        let sidecar = column_metadata::sidecar_path(path);
+        if !input::is_stdin(path) && sidecar.is_file() {
+            let metadata = column_metadata::load_metadata(&sidecar).map_err(|error| {
+                io::Error::new(error.kind(), format!("{}: {}", sidecar.display(), error))
+            })?;
+            loaded.table.set_column_metadata(metadata);
+        }
+
+        if let Some(audit) = &mut self.audit {
+            audit.add_input(path, loaded.table.row_count());
+        }
+
+        let skipped = loaded.row_errors.len();
+        if skipped > 0 {
+            let hint = self
+                .args
+                .errors_out
+                .as_ref()
+                .map_or(String::new(), |errors_out| {
+                    format!(", see {}", errors_out.display())
+                });
+            warn!(
+                "{}: {} of {} rows skipped{}",
+                path.display(),
+                skipped,
+                loaded.table.row_count() + skipped,
+                hint
+            );
+        }
+
+        info!(
+            "{}: read as {}, {}, {} rows, {} columns",
+            path.display(),
+            loaded.table_type,
+            if loaded.has_header {
+                "header on first line"
+            } else {
+                "no header"
+            },
+            loaded.table.row_count(),
+            loaded.table.column_count()
+        );
+        if log::log_enabled!(Level::Debug) {
+            let name = path.display().to_string();
+            for line in explain::explain_table(&name, &loaded, &self.options).lines() {
+                debug!("{}", line);
+            }
+        }
+
+        Ok(loaded)
+    }
+}
+
+/// Sends warnings to stderr, more with `-v`/`-vv`, nothing with `-q`.
+fn init_logging(verbose: u8, quiet: bool) {
+    let level = match (quiet, verbose) {
+        (true, _) => LevelFilter::Off,
+        (false, 0) => LevelFilter::Warn,
+        (false, 1) => LevelFilter::Info,
+        (false, _) => LevelFilter::Debug,
+    };
+    env_logger::Builder::new()
+        .filter_level(level)
+        .format(|buf, record| {
+            let level = match record.level() {
+                Level::Error => "error",
+                Level::Warn => "warning",
+                Level::Info => "info",
+                Level::Debug | Level::Trace => "debug",
+            };
+            writeln!(buf, "{}: {}", level, record.args())
+        })
+        .init();
+}
+
+/// Checks whether `--header`/`--no-header` positions include an input;
+/// a bare flag includes every input.
+fn covers_input(positions: &Option<Vec<usize>>, position: usize) -> bool {
+    positions
+        .as_ref()
+        .is_some_and(|positions| positions.is_empty() || positions.contains(&position))
+}
+
+fn fail(args: &GlobalArgs, file: Option<&Path>, error: &(dyn Error + 'static)) -> ! {
+    eprintln!("{}", format_error(args.error_format, file, error));
+    process::exit(2);
+}
@@ -0,0 +1,1416 @@
+use std::{
+    borrow::Cow,
+    cmp::Reverse,
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt::Write,
+};
+
+use crate::cell_value::CellValue;
+use crate::locale::LocaleProfile;
+use crate::table::{Table, TableError};
+
+/// Largest number of cells in the alignment matrix before falling back to
+/// pairing rows by position
+const MAX_ALIGNMENT_CELLS: usize = 4_000_000;
+
+/// A cell whose value differs between the two tables
+#[derive(Debug, PartialEq)]
+pub struct CellChange {
+    pub column: String,
+    pub old: String,
+    pub new: String,
+}
+
+/// One difference between two tables; row indices are 0-based data rows
+/// and `key` holds the row's key values when rows were matched by key
+#[derive(Debug, PartialEq)]
+pub enum RowDiff {
+    Added {
+        right_index: usize,
+        key: Vec<String>,
+        row: Vec<String>,
+    },
+    Removed {
+        left_index: usize,
+        key: Vec<String>,
+        row: Vec<String>,
+    },
+    Changed {
+        left_index: usize,
+        right_index: usize,
+        key: Vec<String>,
+        cells: Vec<CellChange>,
+    },
+}
+
+/// Differences between two tables
+#[derive(Debug, Default, PartialEq)]
+pub struct TableDiff {
+    /// Columns only the right table has
+    pub added_columns: Vec<String>,
+    /// Columns only the left table has
+    pub removed_columns: Vec<String>,
+    /// Row differences in table order
+    pub rows: Vec<RowDiff>,
+    /// Columns rows were matched on, empty when matched by position
+    pub key: Vec<String>,
+    /// Header of the left table, naming the cells of removed rows
+    pub left_header: Vec<String>,
+    /// Header of the right table, naming the cells of added rows
+    pub right_header: Vec<String>,
+}
+
+impl TableDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added_columns.is_empty() && self.removed_columns.is_empty() && self.rows.is_empty()
+    }
+
+    /// Counts of (added, removed, changed) rows
+    pub fn counts(&self) -> (usize, usize, usize) {
+        self.rows
+            .iter()
+            .fold((0, 0, 0), |(added, removed, changed), row| match row {
+                RowDiff::Added { .. } => (added + 1, removed, changed),
+                RowDiff::Removed { .. } => (added, removed + 1, changed),
+                RowDiff::Changed { .. } => (added, removed, changed + 1),
+            })
+    }
+}
+
+/// How two cells are decided equal; the default compares text exactly
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CellComparison {
+    /// Largest difference between two numbers still counted as equal
+    pub tolerance: f64,
+    /// Largest difference between two numbers, as a fraction of the larger
+    /// magnitude, still counted as equal
+    pub rel_tolerance: f64,
+    /// Absolute tolerances of single columns, replacing both tolerances above
+    pub column_tolerances: HashMap<String, f64>,
+    /// Columns left out of the comparison; rows still show their cells
+    pub ignored_columns: HashSet<String>,
+    /// Compare cells regardless of letter case
+    pub ignore_case: bool,
+    /// Compare cells without leading and trailing whitespace and with
+    /// inner runs of whitespace as one space
+    pub ignore_whitespace: bool,
+}
+
+impl CellComparison {
+    /// Builds the comparison for a list of `--tolerance` values, later
+    /// values overriding earlier ones
+    pub fn new(tolerances: &[Tolerance], rel_tolerance: f64) -> Self {
+        let mut comparison = CellComparison {
+            rel_tolerance,
+            ..CellComparison::default()
+        };
+        for tolerance in tolerances {
+            match tolerance {
+                Tolerance::All(tolerance) => comparison.tolerance = *tolerance,
+                Tolerance::Column(column, tolerance) => {
+                    comparison
+                        .column_tolerances
+                        .insert(column.clone(), *tolerance);
+                }
+            }
+        }
+        comparison
+    }
+
+    /// Whether some numbers count as equal without being the same text
+    pub fn has_tolerance(&self) -> bool {
+        self.tolerance > 0.0
+            || self.rel_tolerance > 0.0
+            || self
+                .column_tolerances
+                .values()
+                .any(|tolerance| *tolerance > 0.0)
+    }
+
+    /// Fails when tolerances would read cells as numbers, which `--strict`
+    /// rules out
+    pub fn check_strict(&self) -> Result<(), TableError> {
+        if self.has_tolerance() {
+            return Err(TableError::InvalidArgument(
+                "--strict compares cells as text, so tolerances can't be used".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// The form of a cell that is compared, after `ignore_case` and
+    /// `ignore_whitespace`
+    pub fn normalize<'a>(&self, cell: &'a str) -> Cow<'a, str> {
+        let mut cell = Cow::Borrowed(cell);
+        if self.ignore_whitespace {
+            cell = Cow::Owned(cell.split_whitespace().collect::<Vec<_>>().join(" "));
+        }
+        if self.ignore_case {
+            cell = Cow::Owned(cell.to_lowercase());
+        }
+        cell
+    }
+
+    /// Whether two cells of a column count as equal; cells are compared as
+    /// numbers when both parse as one and the column has a tolerance
+    pub fn equal(&self, column: &str, left: &str, right: &str) -> bool {
+        if left == right {
+            return true;
+        }
+        let (left, right) = (self.normalize(left), self.normalize(right));
+        if left == right {
+            return true;
+        }
+        if !self.column_has_tolerance(column) {
+            return false;
+        }
+        let (tolerance, rel_tolerance) = self.tolerances(column);
+        match (CellValue::parse(&left), CellValue::parse(&right)) {
+            (CellValue::Number(left), CellValue::Number(right)) => {
+                let difference = (left - right).abs();
+                difference <= tolerance || difference <= rel_tolerance * left.abs().max(right.abs())
+            }
+            _ => false,
+        }
+    }
+
+    /// Absolute and relative tolerance of a column
+    fn tolerances(&self, column: &str) -> (f64, f64) {
+        match self.column_tolerances.get(column) {
+            Some(tolerance) => (*tolerance, 0.0),
+            None => (self.tolerance, self.rel_tolerance),
+        }
+    }
+
+    /// Whether cells of a column can be equal without being the same
+    /// after normalizing
+    fn column_has_tolerance(&self, column: &str) -> bool {
+        let (tolerance, rel_tolerance) = self.tolerances(column);
+        tolerance > 0.0 || rel_tolerance > 0.0
+    }
+
+    /// Normalizes every cell of projected rows
+    fn normalize_rows<'a>(&self, rows: &[Vec<&'a str>]) -> Vec<Vec<Cow<'a, str>>> {
+        rows.iter()
+            .map(|row| row.iter().map(|cell| self.normalize(cell)).collect())
+            .collect()
+    }
+
+    /// Whether two rows of cells of the given columns count as equal
+    fn rows_equal(&self, columns: &[&str], left: &[&str], right: &[&str]) -> bool {
+        left.len() == right.len()
+            && columns
+                .iter()
+                .zip(left.iter().zip(right))
+                .all(|(column, (left, right))| self.equal(column, left, right))
+    }
+}
+
+/// A `--tolerance` value: `0.01` for every column or `price=0.01` for one
+#[derive(Debug, Clone, PartialEq)]
+pub enum Tolerance {
+    All(f64),
+    Column(String, f64),
+}
+
+impl Tolerance {
+    /// Parses `0.01` or `column=0.01`
+    pub fn parse(value: &str) -> Result<Self, TableError> {
+        let invalid = || {
+            TableError::InvalidArgument(format!(
+                "expected a finite, non-negative tolerance or 'column=tolerance', got '{}'",
+                value
+            ))
+        };
+        match value.rsplit_once('=') {
+            Some((column, tolerance)) if !column.is_empty() => Ok(Tolerance::Column(
+                column.to_string(),
+                parse_tolerance(tolerance).map_err(|_| invalid())?,
+            )),
+            _ => Ok(Tolerance::All(
+                parse_tolerance(value).map_err(|_| invalid())?,
+            )),
+        }
+    }
+}
+
+/// Parses a tolerance: a finite, non-negative number
+pub fn parse_tolerance(value: &str) -> Result<f64, TableError> {
+    value
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|tolerance| tolerance.is_finite() && *tolerance >= 0.0)
+        .ok_or_else(|| {
+            TableError::InvalidArgument(format!(
+                "expected a finite, non-negative tolerance, got '{}'",
+                value
+            ))
+        })
+}
+
+/// Compares two tables row by row
+///
+/// Columns are matched by name when both tables have a header and by
+/// position otherwise; only shared columns take part in row comparison.
+/// Rows are aligned on their longest common subsequence, so an inserted
+/// row shows up as added instead of shifting every later row. Within a
+/// run of unmatched rows, rows are paired in order as changed and the
+/// rest are reported as removed or added.
+///
+/// # Arguments
+/// * `left` - The original table
+/// * `right` - The table compared against it
+/// * `comparison` - When two cells count as equal
+///
+/// # Returns
+/// * `TableDiff` - Column and row differences
+pub fn diff_tables(left: &Table, right: &Table, comparison: &CellComparison) -> TableDiff {
+    let (shared, added_columns, removed_columns) =
+        match_columns(left, right, &comparison.ignored_columns);
+
+    let left_columns: Vec<usize> = shared.iter().map(|column| column.left).collect();
+    let right_columns: Vec<usize> = shared.iter().map(|column| column.right).collect();
+    let left_keys = project_rows(left, &left_columns);
+    let right_keys = project_rows(right, &right_columns);
+
+    let mut rows = Vec::new();
+    let mut pending_removed = Vec::new();
+    let mut pending_added = Vec::new();
+    let flush = |removed: &mut Vec<usize>, added: &mut Vec<usize>, rows: &mut Vec<RowDiff>| {
+        let paired = removed.len().min(added.len());
+        for (left_index, right_index) in removed.iter().zip(added.iter()) {
+            let cells = shared
+                .iter()
+                .zip(left_keys[*left_index].iter().zip(&right_keys[*right_index]))
+                .filter(|(column, (old, new))| !comparison.equal(&column.name, old, new))
+                .map(|(column, (old, new))| CellChange {
+                    column: column.name.clone(),
+                    old: old.to_string(),
+                    new: new.to_string(),
+                })
+                .collect();
+            rows.push(RowDiff::Changed {
+                left_index: *left_index,
+                right_index: *right_index,
+                key: Vec::new(),
+                cells,
+            });
+        }
+        rows.extend(removed[paired..].iter().map(|left_index| RowDiff::Removed {
+            left_index: *left_index,
+            key: Vec::new(),
+            row: left.rows()[*left_index].clone(),
+        }));
+        rows.extend(added[paired..].iter().map(|right_index| RowDiff::Added {
+            right_index: *right_index,
+            key: Vec::new(),
+            row: right.rows()[*right_index].clone(),
+        }));
+        removed.clear();
+        added.clear();
+    };
+
+    let names: Vec<&str> = shared.iter().map(|column| column.name.as_str()).collect();
+    for step in align(&left_keys, &right_keys, |left, right| {
+        comparison.rows_equal(&names, left, right)
+    }) {
+        match step {
+            Alignment::Equal => flush(&mut pending_removed, &mut pending_added, &mut rows),
+            Alignment::Removed(left_index) => pending_removed.push(left_index),
+            Alignment::Added(right_index) => pending_added.push(right_index),
+        }
+    }
+    flush(&mut pending_removed, &mut pending_added, &mut rows);
+
+    TableDiff {
+        added_columns,
+        removed_columns,
+        rows,
+        key: Vec::new(),
+        left_header: header_of(left),
+        right_header: header_of(right),
+    }
+}
+
+/// Compares two tables as multisets of rows, ignoring their order
+///
+/// Rows are compared over the shared columns; each right row cancels one
+/// equal left row, and what is left over is reported as removed or added,
+/// each in table order. Rows are never paired as changed.
+///
+/// Rows equal only within a tolerance are matched pairwise among rows
+/// agreeing on the columns without one. When such a group is too large
+/// to match pairwise, its rows are left unmatched, like the alignment of
+/// `diff_tables` falls back to pairing by position.
+///
+/// # Arguments
+/// * `left` - The original table
+/// * `right` - The table compared against it
+/// * `comparison` - When two cells count as equal
+///
+/// # Returns
+/// * `TableDiff` - Column and row differences
+pub fn diff_unordered(left: &Table, right: &Table, comparison: &CellComparison) -> TableDiff {
+    let (shared, added_columns, removed_columns) =
+        match_columns(left, right, &comparison.ignored_columns);
+    let left_columns: Vec<usize> = shared.iter().map(|column| column.left).collect();
+    let right_columns: Vec<usize> = shared.iter().map(|column| column.right).collect();
+    let left_keys = project_rows(left, &left_columns);
+    let right_keys = project_rows(right, &right_columns);
+
+    let (left_normalized, right_normalized) = (
+        comparison.normalize_rows(&left_keys),
+        comparison.normalize_rows(&right_keys),
+    );
+
+    let mut counts: HashMap<&Vec<Cow<str>>, usize> = HashMap::new();
+    for key in &right_normalized {
+        *counts.entry(key).or_default() += 1;
+    }
+    let mut removed = Vec::new();
+    for (left_index, key) in left_normalized.iter().enumerate() {
+        match counts.get_mut(key) {
+            Some(count) if *count > 0 => *count -= 1,
+            _ => removed.push(left_index),
+        }
+    }
+    // What remains counts the right rows no left row cancelled.
+    let mut added = Vec::new();
+    for (right_index, key) in right_normalized.iter().enumerate() {
+        let count = counts.get_mut(key).unwrap();
+        if *count > 0 {
+            *count -= 1;
+            added.push(right_index);
+        }
+    }
+    // Rows equal only within a tolerance don't hash alike, but they agree
+    // on the columns without a tolerance, so the leftovers are bucketed on
+    // those and matched pairwise within a bucket.
+    if comparison.has_tolerance() {
+        let names: Vec<&str> = shared.iter().map(|column| column.name.as_str()).collect();
+        let exact: Vec<usize> = (0..names.len())
+            .filter(|index| !comparison.column_has_tolerance(names[*index]))
+            .collect();
+        let bucket = |row: &'_ [Cow<'_, str>]| -> Vec<String> {
+            exact.iter().map(|index| row[*index].to_string()).collect()
+        };
+        let mut candidates: HashMap<Vec<String>, (usize, Vec<usize>)> = HashMap::new();
+        for left_index in &removed {
+            candidates
+                .entry(bucket(&left_normalized[*left_index]))
+                .or_default()
+                .0 += 1;
+        }
+        for right_index in &added {
+            if let Some((_, right_indices)) =
+                candidates.get_mut(&bucket(&right_normalized[*right_index]))
+            {
+                right_indices.push(*right_index);
+            }
+        }
+
+        let mut matched = HashSet::new();
+        removed.retain(|left_index| {
+            let Some((left_count, right_indices)) =
+                candidates.get_mut(&bucket(&left_normalized[*left_index]))
+            else {
+                return true;
+            };
+            if left_count.saturating_mul(right_indices.len()) > MAX_ALIGNMENT_CELLS {
+                return true;
+            }
+            let position = right_indices.iter().position(|right_index| {
+                comparison.rows_equal(&names, &left_keys[*left_index], &right_keys[*right_index])
+            });
+            match position {
+                Some(position) => {
+                    matched.insert(right_indices.remove(position));
+                    false
+                }
+                None => true,
+            }
+        });
+        added.retain(|right_index| !matched.contains(right_index));
+    }
+
+    let mut rows: Vec<RowDiff> = removed
+        .into_iter()
+        .map(|left_index| RowDiff::Removed {
+            left_index,
+            key: Vec::new(),
+            row: left.rows()[left_index].clone(),
+        })
+        .collect();
+    rows.extend(added.into_iter().map(|right_index| RowDiff::Added {
+        right_index,
+        key: Vec::new(),
+        row: right.rows()[right_index].clone(),
+    }));
+
+    TableDiff {
+        added_columns,
+        removed_columns,
+        rows,
+        key: Vec::new(),
+        left_header: header_of(left),
+        right_header: header_of(right),
+    }
+}
+
+/// Compares two tables matching rows by the values of key columns
+///
+/// Matched rows are compared cell by cell over the shared columns, in left
+/// order; rows whose key only the left table has are removed, and rows
+/// whose key only the right table has are added after them, in right
+/// order. Each key must be unique within its table.
+///
+/// # Arguments
+/// * `left` - The original table
+/// * `right` - The table compared against it
+/// * `key` - Columns whose values together identify a row in both tables
+/// * `comparison` - When two cells count as equal
+///
+/// # Returns
+/// * `TableDiff` - Column and row differences
+pub fn diff_by_key(
+    left: &Table,
+    right: &Table,
+    key: &[String],
+    comparison: &CellComparison,
+) -> Result<TableDiff, TableError> {
+    let (shared, added_columns, removed_columns) =
+        match_columns(left, right, &comparison.ignored_columns);
+    let key_indices = |table: &Table| {
+        key.iter()
+            .map(|column| {
+                table
+                    .column_index(column)
+                    .ok_or_else(|| TableError::MissingColumn(column.clone()))
+            })
+            .collect::<Result<Vec<usize>, TableError>>()
+    };
+    let left_keys = project_rows(left, &key_indices(left)?);
+    let right_keys = project_rows(right, &key_indices(right)?);
+    let (left_normalized, right_normalized) = (
+        comparison.normalize_rows(&left_keys),
+        comparison.normalize_rows(&right_keys),
+    );
+    let left_index = index_rows(key, &left_normalized, "left")?;
+    let right_index = index_rows(key, &right_normalized, "right")?;
+    let owned = |values: &[&str]| values.iter().map(|value| value.to_string()).collect();
+
+    let mut rows = Vec::new();
+    for (left_index, row) in left.rows().iter().enumerate() {
+        let Some(&right_index) = right_index.get(&left_normalized[left_index]) else {
+            rows.push(RowDiff::Removed {
+                left_index,
+                key: owned(&left_keys[left_index]),
+                row: row.clone(),
+            });
+            continue;
+        };
+        let right_row = &right.rows()[right_index];
+        let cells: Vec<CellChange> = shared
+            .iter()
+            .filter(|column| {
+                !comparison.equal(&column.name, &row[column.left], &right_row[column.right])
+            })
+            .map(|column| CellChange {
+                column: column.name.clone(),
+                old: row[column.left].clone(),
+                new: right_row[column.right].clone(),
+            })
+            .collect();
+        if !cells.is_empty() {
+            rows.push(RowDiff::Changed {
+                left_index,
+                right_index,
+                key: owned(&left_keys[left_index]),
+                cells,
+            });
+        }
+    }
+    for (right_index, row) in right.rows().iter().enumerate() {
+        if !left_index.contains_key(&right_normalized[right_index]) {
+            rows.push(RowDiff::Added {
+                right_index,
+                key: owned(&right_keys[right_index]),
+                row: row.clone(),
+            });
+        }
+    }
+
+    Ok(TableDiff {
+        added_columns,
+        removed_columns,
+        rows,
+        key: key.to_vec(),
+        left_header: header_of(left),
+        right_header: header_of(right),
+    })
+}
+
+/// Renders a diff as a human-readable report
+///
+/// # Arguments
+/// * `diff` - Differences to render
+/// * `left_name` - Label of the original table
+/// * `right_name` - Label of the compared table
+/// * `locale` - How values are rendered
+/// * `by_column` - Also count the changed cells of each column
+///
+/// # Returns
+/// * `String` - One line per difference followed by a summary
+pub fn format_report(
+    diff: &TableDiff,
+    left_name: &str,
+    right_name: &str,
+    locale: &LocaleProfile,
+    by_column: bool,
+) -> String {
+    let format_row = |header: &[String], row: &[String]| -> String {
+        row.iter()
+            .enumerate()
+            .map(|(index, cell)| match header.get(index) {
+                Some(column) => locale.format_cell(column, cell),
+                None => cell.clone(),
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    let mut report = String::new();
+    // Writing to a String never fails.
+    writeln!(report, "--- {}", left_name).unwrap();
+    writeln!(report, "+++ {}", right_name).unwrap();
+
+    if diff.is_empty() {
+        writeln!(report, "No differences").unwrap();
+        return report;
+    }
+
+    for column in &diff.removed_columns {
+        writeln!(report, "- column {}", column).unwrap();
+    }
+    for column in &diff.added_columns {
+        writeln!(report, "+ column {}", column).unwrap();
+    }
+
+    // Rows matched by key are named by their key values.
+    let label = |key: &[String], position: String| -> String {
+        if key.is_empty() {
+            format!("row {}", position)
+        } else {
+            diff.key
+                .iter()
+                .zip(key)
+                .map(|(column, value)| format!("{}={}", column, locale.format_cell(column, value)))
+                .collect::<Vec<_>>()
+                .join(", ")
+        }
+    };
+
+    for row in &diff.rows {
+        match row {
+            RowDiff::Removed {
+                left_index,
+                key,
+                row,
+            } => writeln!(
+                report,
+                "- {}: {}",
+                label(key, (left_index + 1).to_string()),
+                format_row(&diff.left_header, row)
+            )
+            .unwrap(),
+            RowDiff::Added {
+                right_index,
+                key,
+                row,
+            } => writeln!(
+                report,
+                "+ {}: {}",
+                label(key, (right_index + 1).to_string()),
+                format_row(&diff.right_header, row)
+            )
+            .unwrap(),
+            RowDiff::Changed {
+                left_index,
+                right_index,
+                key,
+                cells,
+            } => {
+                let position = if left_index == right_index {
+                    format!("{}", left_index + 1)
+                } else {
+                    format!("{} -> {}", left_index + 1, right_index + 1)
+                };
+                let cells: Vec<String> = cells
+                    .iter()
+                    .map(|cell| {
+                        format!(
+                            "{}: {:?} -> {:?}",
+                            cell.column,
+                            locale.format_cell(&cell.column, &cell.old),
+                            locale.format_cell(&cell.column, &cell.new)
+                        )
+                    })
+                    .collect();
+                writeln!(report, "~ {}: {}", label(key, position), cells.join(", ")).unwrap();
+            }
+        }
+    }
+
+    let (added, removed, changed) = diff.counts();
+    writeln!(
+        report,
+        "\n{} added, {} removed, {} changed",
+        added, removed, changed
+    )
+    .unwrap();
+    if by_column {
+        report.push_str(&format_column_summary(diff));
+    }
+    report
+}
+
+/// The changed cells of one column
+struct ColumnChanges<'a> {
+    column: &'a str,
+    count: usize,
+    /// Numeric changes per power of ten of their size, `None` for no
+    /// change in value; dropped once a change isn't numeric
+    bands: Option<BTreeMap<Option<i32>, usize>>,
+}
+
+/// Counts the changed cells of each column, most changed first
+///
+/// Columns whose changes are all numeric also get a histogram of the
+/// change sizes by power of ten, e.g. `0.1-1: 2, 10-100: 1`.
+fn format_column_summary(diff: &TableDiff) -> String {
+    let mut columns: Vec<ColumnChanges> = Vec::new();
+    for row in &diff.rows {
+        let RowDiff::Changed { cells, .. } = row else {
+            continue;
+        };
+        for cell in cells {
+            let position = match columns
+                .iter()
+                .position(|changes| changes.column == cell.column)
+            {
+                Some(position) => position,
+                None => {
+                    columns.push(ColumnChanges {
+                        column: &cell.column,
+                        count: 0,
+                        bands: Some(BTreeMap::new()),
+                    });
+                    columns.len() - 1
+                }
+            };
+            let changes = &mut columns[position];
+            changes.count += 1;
+            changes.bands = changes.bands.take().and_then(|mut bands| {
+                match (CellValue::parse(&cell.old), CellValue::parse(&cell.new)) {
+                    (CellValue::Number(old), CellValue::Number(new)) => {
+                        let change = (new - old).abs();
+                        let band = (change > 0.0).then(|| change.log10().floor() as i32);
+                        *bands.entry(band).or_default() += 1;
+                        Some(bands)
+                    }
+                    _ => None,
+                }
+            });
+        }
+    }
+    columns.sort_by_key(|changes| Reverse(changes.count));
+
+    let mut summary = String::from("\nChanged cells by column:\n");
+    for changes in columns {
+        write!(summary, "  {}: {}", changes.column, changes.count).unwrap();
+        if let Some(bands) = changes.bands {
+            let bands: Vec<String> = bands
+                .iter()
+                .map(|(band, count)| match band {
+                    None => format!("0: {}", count),
+                    Some(band) => format!(
+                        "{}-{}: {}",
+                        power_of_ten(*band),
+                        power_of_ten(band + 1),
+                        count
+                    ),
+                })
+                .collect();
+            write!(summary, " ({})", bands.join(", ")).unwrap();
+        }
+        summary.push('\n');
+    }
+    summary
+}
+
+/// Writes 10^exponent out in full, e.g. `100` or `0.01`
+fn power_of_ten(exponent: i32) -> String {
+    match usize::try_from(exponent) {
+        Ok(zeros) => format!("1{}", "0".repeat(zeros)),
+        Err(_) => format!("0.{}1", "0".repeat(exponent.unsigned_abs() as usize - 1)),
+    }
+}
+
+/// A column present in both tables
+struct SharedColumn {
+    name: String,
+    left: usize,
+    right: usize,
+}
+
+/// Whether a column can be named in `--tolerance` and `--ignore-columns`
+///
+/// Columns go by their header names, or by `#1`, `#2`, and so on when
+/// either table has no header, as reports name them.
+pub fn has_column(left: &Table, right: &Table, column: &str) -> bool {
+    let (shared, added, removed) = match_columns(left, right, &HashSet::new());
+    shared.iter().any(|shared| shared.name == column)
+        || added.iter().chain(&removed).any(|name| name == column)
+}
+
+/// The header of a table as owned names, empty without a header
+fn header_of(table: &Table) -> Vec<String> {
+    table.header().into_iter().map(String::from).collect()
+}
+
+/// Returns the shared columns in left order, then the names of columns
+/// only on the right and only on the left, leaving out ignored columns.
+fn match_columns(
+    left: &Table,
+    right: &Table,
+    ignored: &HashSet<String>,
+) -> (Vec<SharedColumn>, Vec<String>, Vec<String>) {
+    let left_header = left.header();
+    let right_header = right.header();
+
+    let (mut shared, mut added, mut removed): (Vec<SharedColumn>, Vec<String>, Vec<String>) =
+        if left_header.is_empty() || right_header.is_empty() {
+            let shared = left.column_count().min(right.column_count());
+            let name = |index: usize| format!("#{}", index + 1);
+            (
+                (0..shared)
+                    .map(|index| SharedColumn {
+                        name: name(index),
+                        left: index,
+                        right: index,
+                    })
+                    .collect(),
+                (shared..right.column_count()).map(name).collect(),
+                (shared..left.column_count()).map(name).collect(),
+            )
+        } else {
+            (
+                left_header
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(left_index, column)| {
+                        let right_index = right.column_index(column)?;
+                        Some(SharedColumn {
+                            name: column.to_string(),
+                            left: left_index,
+                            right: right_index,
+                        })
+                    })
+                    .collect(),
+                right_header
+                    .iter()
+                    .filter(|column| left.column_index(column).is_none())
+                    .map(|column| column.to_string())
+                    .collect(),
+                left_header
+                    .iter()
+                    .filter(|column| right.column_index(column).is_none())
+                    .map(|column| column.to_string())
+                    .collect(),
+            )
+        };
+    shared.retain(|column| !ignored.contains(&column.name));
+    added.retain(|column| !ignored.contains(column));
+    removed.retain(|column| !ignored.contains(column));
+    (shared, added, removed)
+}
+
+/// Maps each row's key values to the row, rejecting keys that appear twice.
+fn index_rows<'a, 'b>(
+    columns: &[String],
+    keys: &'b [Vec<Cow<'a, str>>],
+    side: &str,
+) -> Result<HashMap<&'b Vec<Cow<'a, str>>, usize>, TableError> {
+    let mut index = HashMap::with_capacity(keys.len());
+    for (row_index, key) in keys.iter().enumerate() {
+        if let Some(first) = index.insert(key, row_index) {
+            let key: Vec<String> = columns
+                .iter()
+                .zip(key)
+                .map(|(column, value)| format!("{}={}", column, value))
+                .collect();
+            return Err(TableError::InvalidArgument(format!(
+                "key {} appears in rows {} and {} of the {} table",
+                key.join(", "),
+                first + 1,
+                row_index + 1,
+                side
+            )));
+        }
+    }
+    Ok(index)
+}
+
+/// Picks the given columns out of every row, empty where a row is short.
+fn project_rows<'a>(table: &'a Table, columns: &[usize]) -> Vec<Vec<&'a str>> {
+    table
+        .rows()
+        .iter()
+        .map(|row| {
+            columns
+                .iter()
+                .map(|index| row.get(*index).map_or("", String::as_str))
+                .collect()
+        })
+        .collect()
+}
+
+enum Alignment {
+    Equal,
+    Removed(usize),
+    Added(usize),
+}
+
+/// Aligns two row sequences on their longest common subsequence of rows
+/// `equal` deems equal.
+///
+/// Common leading and trailing rows are matched directly; if the
+/// remaining middle is too large for the quadratic table, its rows are
+/// paired by position instead.
+fn align<T>(left: &[T], right: &[T], equal: impl Fn(&T, &T) -> bool) -> Vec<Alignment> {
+    let prefix = left
+        .iter()
+        .zip(right)
+        .take_while(|(left, right)| equal(left, right))
+        .count();
+    let suffix = left[prefix..]
+        .iter()
+        .rev()
+        .zip(right[prefix..].iter().rev())
+        .take_while(|(left, right)| equal(left, right))
+        .count();
+    let left_middle = &left[prefix..left.len() - suffix];
+    let right_middle = &right[prefix..right.len() - suffix];
+    let (n, m) = (left_middle.len(), right_middle.len());
+
+    let mut steps: Vec<Alignment> = (0..prefix).map(|_| Alignment::Equal).collect();
+
+    if n.saturating_mul(m) > MAX_ALIGNMENT_CELLS {
+        steps.extend((prefix..prefix + n).map(Alignment::Removed));
+        steps.extend((prefix..prefix + m).map(Alignment::Added));
+    } else {
+        // lengths[i][j] is the LCS length of left_middle[i..] and right_middle[j..]
+        let mut lengths = vec![vec![0u32; m + 1]; n + 1];
+        for i in (0..n).rev() {
+            for j in (0..m).rev() {
+                lengths[i][j] = if equal(&left_middle[i], &right_middle[j]) {
+                    lengths[i + 1][j + 1] + 1
+                } else {
+                    lengths[i + 1][j].max(lengths[i][j + 1])
+                };
+            }
+        }
+
+        let (mut i, mut j) = (0, 0);
+        while i < n || j < m {
+            if i < n && j < m && equal(&left_middle[i], &right_middle[j]) {
+                steps.push(Alignment::Equal);
+                i += 1;
+                j += 1;
+            } else if j == m || (i < n && lengths[i + 1][j] >= lengths[i][j + 1]) {
+                steps.push(Alignment::Removed(prefix + i));
+                i += 1;
+            } else {
+                steps.push(Alignment::Added(prefix + j));
+                j += 1;
+            }
+        }
+    }
+
+    steps.extend((0..suffix).map(|_| Alignment::Equal));
+    steps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(header: &[&str], rows: &[&[&str]]) -> Table {
+        Table::with_header_and_data(
+            header.iter().map(|cell| cell.to_string()).collect(),
+            rows.iter()
+                .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+                .collect(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_diff_aligns_rows_and_columns() {
+        let left = table(
+            &["id", "name", "amount"],
+            &[&["1", "a", "10"], &["2", "b", "20"], &["3", "c", "30"]],
+        );
+        let right = table(
+            &["amount", "id", "name", "note"],
+            &[
+                &["10", "1", "a", ""],
+                &["15", "9", "x", ""],
+                &["20", "2", "b", ""],
+                &["31", "3", "c", ""],
+            ],
+        );
+
+        let diff = diff_tables(&left, &right, &CellComparison::default());
+
+        assert_eq!(diff.added_columns, vec!["note"]);
+        assert!(diff.removed_columns.is_empty());
+        assert_eq!(
+            diff.rows,
+            vec![
+                RowDiff::Added {
+                    right_index: 1,
+                    key: Vec::new(),
+                    row: vec!["15".into(), "9".into(), "x".into(), "".into()]
+                },
+                RowDiff::Changed {
+                    left_index: 2,
+                    right_index: 3,
+                    key: Vec::new(),
+                    cells: vec![CellChange {
+                        column: "amount".into(),
+                        old: "30".into(),
+                        new: "31".into()
+                    }]
+                },
+            ]
+        );
+        assert_eq!(diff.counts(), (1, 0, 1));
+        assert!(
+            format_report(&diff, "a", "b", &LocaleProfile::default(), false)
+                .contains("~ row 3 -> 4: amount: \"30\" -> \"31\"")
+        );
+    }
+
+    #[test]
+    fn test_identical_tables_have_no_differences() {
+        let left = table(&["id"], &[&["1"], &["2"]]);
+        let diff = diff_tables(
+            &left,
+            &table(&["id"], &[&["1"], &["2"]]),
+            &CellComparison::default(),
+        );
+
+        assert!(diff.is_empty());
+        assert!(
+            format_report(&diff, "a", "b", &LocaleProfile::default(), false)
+                .ends_with("No differences\n")
+        );
+    }
+
+    fn keyed_tables() -> (Table, Table) {
+        (
+            table(
+                &["id", "amount"],
+                &[&["1", "10"], &["2", "20"], &["3", "30"]],
+            ),
+            table(
+                &["amount", "id"],
+                &[&["31", "3"], &["10", "1"], &["40", "4"]],
+            ),
+        )
+    }
+
+    #[test]
+    fn test_diff_by_key() {
+        let (left, right) = keyed_tables();
+        let diff = diff_by_key(
+            &left,
+            &right,
+            &["id".to_string()],
+            &CellComparison::default(),
+        )
+        .unwrap();
+
+        assert_eq!(diff.counts(), (1, 1, 1));
+        let report = format_report(&diff, "a", "b", &LocaleProfile::default(), false);
+        assert!(report.contains("- id=2: 2, 20"));
+        assert!(report.contains("~ id=3: amount: \"30\" -> \"31\""));
+        assert!(report.contains("+ id=4: 40, 4"));
+    }
+
+    #[test]
+    fn test_diff_by_duplicated_key() {
+        let (_, right) = keyed_tables();
+        let duplicated = table(&["id", "amount"], &[&["1", "10"], &["1", "11"]]);
+        assert!(matches!(
+            diff_by_key(
+                &duplicated,
+                &right,
+                &["id".to_string()],
+                &CellComparison::default()
+            ),
+            Err(TableError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn test_diff_by_unknown_key() {
+        let (left, right) = keyed_tables();
+        assert!(matches!(
+            diff_by_key(
+                &left,
+                &right,
+                &["name".to_string()],
+                &CellComparison::default()
+            ),
+            Err(TableError::MissingColumn(column)) if column == "name"
+        ));
+    }
+
+    #[test]
+    fn test_diff_by_composite_key() {
+        let left = table(
+            &["id", "region", "amount"],
+            &[&["1", "eu", "10"], &["1", "us", "20"]],
+        );
+        let right = table(
+            &["id", "region", "amount"],
+            &[&["1", "us", "21"], &["1", "eu", "10"], &["2", "eu", "5"]],
+        );
+        let key = ["id".to_string(), "region".to_string()];
+
+        let diff = diff_by_key(&left, &right, &key, &CellComparison::default()).unwrap();
+
+        assert_eq!(diff.counts(), (1, 0, 1));
+        let report = format_report(&diff, "a", "b", &LocaleProfile::default(), false);
+        assert!(report.contains("~ id=1, region=us: amount: \"20\" -> \"21\""));
+        assert!(report.contains("+ id=2, region=eu: 2, eu, 5"));
+    }
+
+    #[test]
+    fn test_diff_by_partial_composite_key() {
+        let left = table(
+            &["id", "region", "amount"],
+            &[&["1", "eu", "10"], &["1", "us", "20"]],
+        );
+        assert!(matches!(
+            diff_by_key(
+                &left,
+                &left,
+                &["id".to_string()],
+                &CellComparison::default()
+            ),
+            Err(TableError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn test_diff_unordered_counts_duplicate_rows() {
+        let left = table(&["id", "name"], &[&["1", "a"], &["2", "b"], &["2", "b"]]);
+        let right = table(
+            &["name", "id"],
+            &[&["b", "2"], &["c", "3"], &["a", "1"], &["c", "3"]],
+        );
+
+        let diff = diff_unordered(&left, &right, &CellComparison::default());
+
+        assert_eq!(
+            diff.rows,
+            vec![
+                RowDiff::Removed {
+                    left_index: 2,
+                    key: Vec::new(),
+                    row: vec!["2".into(), "b".into()]
+                },
+                RowDiff::Added {
+                    right_index: 1,
+                    key: Vec::new(),
+                    row: vec!["c".into(), "3".into()]
+                },
+                RowDiff::Added {
+                    right_index: 3,
+                    key: Vec::new(),
+                    row: vec!["c".into(), "3".into()]
+                },
+            ]
+        );
+        let reordered = table(
+            &["id", "name"],
+            &[&["3", "c"], &["1", "a"], &["3", "c"], &["2", "b"]],
+        );
+        assert!(diff_unordered(&right, &reordered, &CellComparison::default()).is_empty());
+    }
+
+    #[test]
+    fn test_report_by_column() {
+        let left = table(
+            &["id", "name", "amount"],
+            &[&["1", "a", "10"], &["2", "b", "20"], &["3", "c", "30"]],
+        );
+        let right = table(
+            &["id", "name", "amount"],
+            &[&["1", "A", "10.5"], &["2", "b", "20.2"], &["3", "c", "130"]],
+        );
+
+        let diff = diff_tables(&left, &right, &CellComparison::default());
+        let report = format_report(&diff, "a", "b", &LocaleProfile::default(), true);
+
+        assert!(report.ends_with(
+            "Changed cells by column:\n  amount: 3 (0.1-1: 2, 100-1000: 1)\n  name: 1\n"
+        ));
+        assert!(
+            !format_report(&diff, "a", "b", &LocaleProfile::default(), false).contains("by column")
+        );
+    }
+
+    fn priced_tables() -> (Table, Table) {
+        (
+            table(
+                &["id", "price"],
+                &[&["1", "10.0001"], &["2", "200"], &["3", "x"]],
+            ),
+            table(
+                &["id", "price"],
+                &[&["1", "10"], &["2", "201"], &["3", "x"]],
+            ),
+        )
+    }
+
+    fn changed_rows(comparison: &CellComparison) -> (usize, usize, usize) {
+        let (left, right) = priced_tables();
+        diff_tables(&left, &right, comparison).counts()
+    }
+
+    #[test]
+    fn test_numbers_compare_exactly_by_default() {
+        assert_eq!(changed_rows(&CellComparison::default()), (0, 0, 2));
+    }
+
+    #[test]
+    fn test_absolute_tolerance() {
+        let absolute = CellComparison {
+            tolerance: 0.001,
+            ..CellComparison::default()
+        };
+        assert_eq!(changed_rows(&absolute), (0, 0, 1));
+    }
+
+    #[test]
+    fn test_relative_tolerance() {
+        let relative = CellComparison {
+            rel_tolerance: 0.01,
+            ..CellComparison::default()
+        };
+        assert_eq!(changed_rows(&relative), (0, 0, 0));
+        assert!(!relative.equal("price", "1", "1.5"));
+    }
+
+    #[test]
+    fn test_unordered_relative_tolerance() {
+        let (left, right) = priced_tables();
+        let relative = CellComparison {
+            rel_tolerance: 0.01,
+            ..CellComparison::default()
+        };
+        assert!(diff_unordered(&right, &left, &relative).is_empty());
+    }
+
+    #[test]
+    fn test_per_column_tolerance() {
+        let tolerances = ["1".to_string(), "price=0.01".to_string()]
+            .iter()
+            .map(|value| Tolerance::parse(value).unwrap())
+            .collect::<Vec<_>>();
+        let per_column = CellComparison::new(&tolerances, 0.0);
+        assert_eq!(changed_rows(&per_column), (0, 0, 1));
+        assert!(per_column.equal("id", "1", "2"));
+    }
+
+    #[test]
+    fn test_invalid_tolerance() {
+        for value in ["price=-1", "inf"] {
+            assert!(matches!(
+                Tolerance::parse(value),
+                Err(TableError::InvalidArgument(_))
+            ));
+        }
+        assert!(matches!(
+            parse_tolerance("NaN"),
+            Err(TableError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn test_unordered_tolerance_matches_within_buckets() {
+        let left = table(&["id", "price"], &[&["a", "1.0"], &["b", "2.0"]]);
+        let right = table(
+            &["id", "price"],
+            &[&["b", "2.05"], &["a", "9"], &["a", "1.04"]],
+        );
+        let comparison = CellComparison {
+            column_tolerances: HashMap::from([("price".to_string(), 0.1)]),
+            ..CellComparison::default()
+        };
+
+        assert_eq!(
+            diff_unordered(&left, &right, &comparison).rows,
+            vec![RowDiff::Added {
+                right_index: 1,
+                key: Vec::new(),
+                row: vec!["a".into(), "9".into()]
+            }]
+        );
+    }
+
+    #[test]
+    fn test_unordered_tolerance_skips_oversized_buckets() {
+        let rows = 2_001;
+        let cells = |offset: f64| -> Vec<Vec<String>> {
+            (0..rows)
+                .map(|row| vec!["x".to_string(), (row as f64 + offset).to_string()])
+                .collect()
+        };
+        let header = vec!["id".to_string(), "price".to_string()];
+        let left = Table::with_header_and_data(header.clone(), cells(0.0)).unwrap();
+        let right = Table::with_header_and_data(header, cells(0.5)).unwrap();
+        let comparison = CellComparison {
+            column_tolerances: HashMap::from([("price".to_string(), 1.0)]),
+            ..CellComparison::default()
+        };
+
+        assert_eq!(
+            diff_unordered(&left, &right, &comparison).counts(),
+            (rows, rows, 0)
+        );
+    }
+
+    fn headerless_tables() -> (Table, Table) {
+        (
+            Table::with_data(vec![vec!["1".to_string(), "10.0".to_string()]]).unwrap(),
+            Table::with_data(vec![vec!["1".to_string(), "10.05".to_string()]]).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_headerless_columns_by_position() {
+        let (left, right) = headerless_tables();
+        assert!(has_column(&left, &right, "#2"));
+        assert!(!has_column(&left, &right, "#3"));
+        assert!(!has_column(&left, &right, "price"));
+    }
+
+    #[test]
+    fn test_names_need_headers_on_both_sides() {
+        let (_, right) = headerless_tables();
+        let named = table(&["id", "price"], &[&["1", "10"]]);
+        assert!(has_column(&named, &right, "#1"));
+        assert!(has_column(&named, &named, "price"));
+        assert!(!has_column(&named, &named, "#1"));
+    }
+
+    #[test]
+    fn test_tolerance_by_position() {
+        let (left, right) = headerless_tables();
+        let tolerance = [Tolerance::parse("#2=0.1").unwrap()];
+        let comparison = CellComparison::new(&tolerance, 0.0);
+        assert!(diff_tables(&left, &right, &comparison).is_empty());
+    }
+
+    #[test]
+    fn test_ignore_column_by_position() {
+        let (left, right) = headerless_tables();
+        let ignored = CellComparison {
+            ignored_columns: HashSet::from(["#2".to_string()]),
+            ..CellComparison::default()
+        };
+        assert!(diff_tables(&left, &right, &ignored).is_empty());
+    }
+
+    #[test]
+    fn test_strict_rejects_tolerances() {
+        assert!(CellComparison::default().check_strict().is_ok());
+        let tolerant = CellComparison::new(&[Tolerance::All(0.5)], 0.0);
+        assert!(tolerant.check_strict().is_err());
+    }
+
+    #[test]
+    fn test_ignored_columns_stay_in_rows() {
+        let left = table(&["id", "updated_at"], &[&["1", "09:00"], &["2", "09:00"]]);
+        let right = table(
+            &["id", "updated_at", "run_id"],
+            &[
+                &["1", "10:00", "a"],
+                &["3", "10:00", "a"],
+                &["2", "10:00", "a"],
+            ],
+        );
+        let comparison = CellComparison {
+            ignored_columns: ["updated_at", "run_id"].map(String::from).into(),
+            ..CellComparison::default()
+        };
+
+        let diff = diff_tables(&left, &right, &comparison);
+
+        assert!(diff.added_columns.is_empty());
+        assert_eq!(
+            diff.rows,
+            vec![RowDiff::Added {
+                right_index: 1,
+                key: Vec::new(),
+                row: vec!["3".into(), "10:00".into(), "a".into()]
+            }]
+        );
+        assert_eq!(
+            diff_unordered(&left, &right, &comparison).counts(),
+            (1, 0, 0)
+        );
+    }
+
+    #[test]
+    fn test_ignore_case_and_whitespace() {
+        let left = table(&["code", "name"], &[&["ab", "Jane  Doe"], &["cd", "x"]]);
+        let right = table(&["code", "name"], &[&["CD", "x "], &["AB", " jane doe"]]);
+        let comparison = CellComparison {
+            ignore_case: true,
+            ignore_whitespace: true,
+            ..CellComparison::default()
+        };
+
+        assert!(diff_unordered(&left, &right, &comparison).is_empty());
+        assert!(
+            diff_by_key(&left, &right, &["code".to_string()], &comparison)
+                .unwrap()
+                .is_empty()
+        );
+        let case_only = CellComparison {
+            ignore_case: true,
+            ..CellComparison::default()
+        };
+        let diff = diff_by_key(&left, &right, &["code".to_string()], &case_only).unwrap();
+        assert_eq!(
+            diff.rows[0],
+            RowDiff::Changed {
+                left_index: 0,
+                right_index: 1,
+                key: vec!["ab".into()],
+                cells: vec![CellChange {
+                    column: "name".into(),
+                    old: "Jane  Doe".into(),
+                    new: " jane doe".into()
+                }]
+            }
+        );
+    }
+}
@@ -0,0 +1,96 @@
+use std::{fs, path::Path};
+
+use crate::diff::{diff_tables, format_report, CellComparison};
+use crate::load::{load_table, LoadOptions};
+use crate::locale::LocaleProfile;
+
+/// Describes what writing `contents` to `path` would do, for `--dry-run`
+///
+/// # Arguments
+/// * `path` - File that would be written
+/// * `contents` - What would be written
+/// * `rows` - Rows of the rendered table, `None` for other output
+///
+/// # Returns
+/// * `String` - E.g. `would overwrite out.csv (3 rows, 24 bytes)`
+pub fn describe_write(path: &Path, contents: &[u8], rows: Option<usize>) -> String {
+    let action = match fs::read(path) {
+        Ok(existing) if existing == contents => {
+            return format!("would leave {} unchanged", path.display());
+        }
+        Ok(_) => "overwrite",
+        Err(_) => "create",
+    };
+    let size = match rows {
+        Some(rows) => format!("{} rows, {} bytes", rows, contents.len()),
+        None => format!("{} bytes", contents.len()),
+    };
+    format!("would {} {} ({})", action, path.display(), size)
+}
+
+/// Reports the rows writing `contents` over `path` would change, for
+/// `--dry-run-diff`
+///
+/// Both sides are read with detected layouts. There is nothing to report
+/// when the file doesn't exist yet or either side isn't a table.
+///
+/// # Arguments
+/// * `path` - File that would be overwritten
+/// * `contents` - What would be written
+/// * `locale` - Locale the report renders values in
+///
+/// # Returns
+/// * `Option<String>` - The diff report, as `diff` prints it
+pub fn describe_changes(path: &Path, contents: &[u8], locale: &LocaleProfile) -> Option<String> {
+    let existing = fs::read_to_string(path).ok()?;
+    let contents = std::str::from_utf8(contents).ok()?;
+    let options = LoadOptions::default();
+    let old = load_table(&existing, &options).ok()?.table;
+    let new = load_table(contents, &options).ok()?.table;
+    let diff = diff_tables(&old, &new, &CellComparison::default());
+    let name = path.display().to_string();
+    Some(format_report(
+        &diff,
+        &name,
+        &format!("{} (dry run)", name),
+        locale,
+        false,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_describe_write() {
+        let path = std::env::temp_dir().join(format!("tables-dry-run-{}.csv", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let created = describe_write(&path, b"a\n1\n", Some(1));
+        fs::write(&path, "a\n1\n").unwrap();
+        let unchanged = describe_write(&path, b"a\n1\n", Some(1));
+        let overwritten = describe_write(&path, b"a\n2\n3\n", None);
+        fs::remove_file(&path).unwrap();
+
+        let name = path.display();
+        assert_eq!(created, format!("would create {} (1 rows, 4 bytes)", name));
+        assert_eq!(unchanged, format!("would leave {} unchanged", name));
+        assert_eq!(overwritten, format!("would overwrite {} (6 bytes)", name));
+    }
+
+    #[test]
+    fn test_describe_changes() {
+        let path = std::env::temp_dir().join(format!("tables-dry-diff-{}.csv", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let locale = LocaleProfile::default();
+        let missing = describe_changes(&path, b"id,name\n1,tea\n", &locale);
+        fs::write(&path, "id,name\n1,tea\n2,cake\n").unwrap();
+        let report = describe_changes(&path, b"id,name\n1,tea\n2,pie\n", &locale);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(missing, None);
+        let report = report.unwrap();
+        assert!(report.contains("cake"), "{}", report);
+        assert!(report.contains("pie"), "{}", report);
+    }
+}
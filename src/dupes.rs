@@ -0,0 +1,318 @@
+use std::collections::HashSet;
+
+use clap::ValueEnum;
+
+use crate::table::{Table, TableError};
+
+/// Similarity at or above which two rows are treated as near-duplicates
+pub const DEFAULT_SIMILARITY_THRESHOLD: f64 = 0.8;
+
+/// How a cluster of duplicates is collapsed into one row
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum MergeStrategy {
+    /// Keep the first row of the cluster
+    First,
+    /// Keep the row with the most text in the compared columns
+    Longest,
+}
+
+/// A group of rows considered duplicates of each other
+#[derive(Debug)]
+pub struct DuplicateCluster {
+    /// Row indices with their similarity to the first row of the cluster
+    pub rows: Vec<(usize, f64)>,
+}
+
+/// Groups rows whose values in `columns` are duplicates
+///
+/// Values are compared case-insensitively with whitespace collapsed.
+/// Without `fuzzy_threshold` only equal values are grouped; with it, a row
+/// joins the first cluster whose first row is at least that similar, by
+/// the better of normalized edit distance and token overlap.
+///
+/// # Returns
+/// * `Vec<DuplicateCluster>` - Clusters with more than one row, in order of first appearance
+pub fn find_duplicates(
+    table: &Table,
+    columns: &[String],
+    fuzzy_threshold: Option<f64>,
+) -> Result<Vec<DuplicateCluster>, TableError> {
+    let keys = normalized_keys(table, columns)?;
+
+    let mut clusters: Vec<DuplicateCluster> = Vec::new();
+    for (row_index, key) in keys.iter().enumerate() {
+        let best_match = clusters
+            .iter_mut()
+            .map(|cluster| {
+                let representative = &keys[cluster.rows[0].0];
+                let score = match fuzzy_threshold {
+                    Some(_) => similarity(representative, key),
+                    None if representative == key => 1.0,
+                    None => 0.0,
+                };
+                (cluster, score)
+            })
+            .find(|(_, score)| *score >= fuzzy_threshold.unwrap_or(1.0));
+
+        match best_match {
+            Some((cluster, score)) => cluster.rows.push((row_index, score)),
+            None => clusters.push(DuplicateCluster {
+                rows: vec![(row_index, 1.0)],
+            }),
+        }
+    }
+
+    clusters.retain(|cluster| cluster.rows.len() > 1);
+    Ok(clusters)
+}
+
+/// Lists the rows of every cluster with `cluster_id` and `similarity` columns appended
+pub fn duplicates_report(
+    table: &Table,
+    clusters: &[DuplicateCluster],
+) -> Result<Table, TableError> {
+    let listed = clusters.iter().flat_map(|cluster| &cluster.rows);
+    let rows = listed
+        .clone()
+        .map(|(row_index, _)| table.rows()[*row_index].clone())
+        .collect();
+    let cluster_ids = clusters
+        .iter()
+        .enumerate()
+        .flat_map(|(cluster_id, cluster)| vec![(cluster_id + 1).to_string(); cluster.rows.len()])
+        .collect();
+    let similarities = listed.map(|(_, score)| format!("{:.3}", score)).collect();
+
+    table
+        .with_rows(rows)?
+        .append_column("cluster_id", cluster_ids)?
+        .append_column("similarity", similarities)
+}
+
+/// Collapses every cluster into a single row, keeping rows outside clusters as they are
+pub fn merge_duplicates(
+    table: &Table,
+    columns: &[String],
+    clusters: &[DuplicateCluster],
+    strategy: MergeStrategy,
+) -> Result<Table, TableError> {
+    let column_indices = column_indices(table, columns)?;
+    let text_length = |row_index: usize| -> usize {
+        column_indices
+            .iter()
+            .map(|index| table.rows()[row_index][*index].chars().count())
+            .sum()
+    };
+
+    let mut dropped = HashSet::new();
+    for cluster in clusters {
+        let keep = match strategy {
+            MergeStrategy::First => cluster.rows[0].0,
+            MergeStrategy::Longest => cluster
+                .rows
+                .iter()
+                .map(|(row_index, _)| *row_index)
+                .rev()
+                .max_by_key(|row_index| text_length(*row_index))
+                .unwrap(),
+        };
+        dropped.extend(
+            cluster
+                .rows
+                .iter()
+                .map(|(row_index, _)| *row_index)
+                .filter(|row_index| *row_index != keep),
+        );
+    }
+
+    let rows = table
+        .rows()
+        .iter()
+        .enumerate()
+        .filter(|(row_index, _)| !dropped.contains(row_index))
+        .map(|(_, row)| row.clone())
+        .collect();
+
+    table.with_rows(rows)
+}
+
+fn column_indices(table: &Table, columns: &[String]) -> Result<Vec<usize>, TableError> {
+    columns
+        .iter()
+        .map(|column| {
+            table
+                .column_index(column)
+                .ok_or_else(|| TableError::MissingColumn(column.clone()))
+        })
+        .collect()
+}
+
+fn normalized_keys(table: &Table, columns: &[String]) -> Result<Vec<String>, TableError> {
+    let column_indices = column_indices(table, columns)?;
+
+    Ok(table
+        .rows()
+        .iter()
+        .map(|row| {
+            column_indices
+                .iter()
+                .flat_map(|index| row[*index].split_whitespace())
+                .map(str::to_lowercase)
+                .collect::<Vec<String>>()
+                .join(" ")
+        })
+        .collect())
+}
+
+/// Scores two normalized keys between 0 and 1.
+fn similarity(left: &str, right: &str) -> f64 {
+    edit_similarity(left, right).max(token_overlap(left, right))
+}
+
+fn edit_similarity(left: &str, right: &str) -> f64 {
+    let left: Vec<char> = left.chars().collect();
+    let right: Vec<char> = right.chars().collect();
+
+    let longest = left.len().max(right.len());
+    if longest == 0 {
+        return 1.0;
+    }
+
+    let mut previous: Vec<usize> = (0..=right.len()).collect();
+    for (i, left_char) in left.iter().enumerate() {
+        let mut current = vec![i + 1; right.len() + 1];
+        for (j, right_char) in right.iter().enumerate() {
+            let substitution = previous[j] + usize::from(left_char != right_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+
+    1.0 - previous[right.len()] as f64 / longest as f64
+}
+
+fn token_overlap(left: &str, right: &str) -> f64 {
+    let left: HashSet<&str> = left.split(' ').filter(|t| !t.is_empty()).collect();
+    let right: HashSet<&str> = right.split(' ').filter(|t| !t.is_empty()).collect();
+
+    let union = left.union(&right).count();
+    if union == 0 {
+        return 1.0;
+    }
+
+    left.intersection(&right).count() as f64 / union as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_table() -> Table {
+        let to_strings = |cells: &[&str]| cells.iter().map(|c| c.to_string()).collect();
+        Table::with_header_and_data(
+            to_strings(&["name", "address"]),
+            vec![
+                to_strings(&["John Smith", "1 Main St"]),
+                to_strings(&["Jane Doe", "5 Oak Ave"]),
+                to_strings(&["john  smith", "1 Main St"]),
+                to_strings(&["Jon Smith", "1 Main St"]),
+            ],
+        )
+        .unwrap()
+    }
+
+    fn columns() -> Vec<String> {
+        vec!["name".to_string(), "address".to_string()]
+    }
+
+    #[test]
+    fn test_exact_clusters() {
+        let exact = find_duplicates(&sample_table(), &columns(), None).unwrap();
+        assert_eq!(exact.len(), 1);
+        assert_eq!(exact[0].rows, vec![(0, 1.0), (2, 1.0)]);
+    }
+
+    #[test]
+    fn test_fuzzy_clusters() {
+        let fuzzy = find_duplicates(
+            &sample_table(),
+            &columns(),
+            Some(DEFAULT_SIMILARITY_THRESHOLD),
+        )
+        .unwrap();
+        assert_eq!(fuzzy.len(), 1);
+        let rows: Vec<usize> = fuzzy[0]
+            .rows
+            .iter()
+            .map(|(row_index, _)| *row_index)
+            .collect();
+        assert_eq!(rows, vec![0, 2, 3]);
+        assert!(fuzzy[0].rows[2].1 < 1.0);
+    }
+
+    #[test]
+    fn test_merge_longest() {
+        let table = sample_table();
+        let fuzzy =
+            find_duplicates(&table, &columns(), Some(DEFAULT_SIMILARITY_THRESHOLD)).unwrap();
+        let merged = merge_duplicates(&table, &columns(), &fuzzy, MergeStrategy::Longest).unwrap();
+        assert_eq!(merged.row_count(), 2);
+        assert_eq!(merged.get_value(0, "name").unwrap(), "Jane Doe");
+        assert_eq!(merged.get_value(1, "name").unwrap(), "john  smith");
+    }
+
+    #[test]
+    fn test_merge_first() {
+        let table = sample_table();
+        let exact = find_duplicates(&table, &columns(), None).unwrap();
+        let merged = merge_duplicates(&table, &columns(), &exact, MergeStrategy::First).unwrap();
+        assert_eq!(merged.row_count(), 3);
+        assert_eq!(merged.get_value(0, "name").unwrap(), "John Smith");
+    }
+
+    #[test]
+    fn test_report_lists_clusters() {
+        let table = sample_table();
+        let exact = find_duplicates(&table, &columns(), None).unwrap();
+        let report = duplicates_report(&table, &exact).unwrap();
+        assert_eq!(
+            report.header(),
+            vec!["name", "address", "cluster_id", "similarity"]
+        );
+        assert_eq!(
+            report.rows()[1],
+            vec!["john  smith", "1 Main St", "1", "1.000"]
+        );
+    }
+
+    #[test]
+    fn test_similarity() {
+        assert_eq!(similarity("john smith", "smith john"), 1.0);
+        assert_eq!(similarity("", ""), 1.0);
+        assert_eq!(similarity("abc", "xyz"), 0.0);
+    }
+
+    #[test]
+    fn test_duplicates_unknown_column() {
+        let columns = vec!["phone".to_string()];
+        assert!(matches!(
+            find_duplicates(&sample_table(), &columns, None),
+            Err(TableError::MissingColumn(column)) if column == "phone"
+        ));
+    }
+
+    #[test]
+    fn test_report_without_header() {
+        let table = Table::with_data(vec![
+            vec!["a".to_string(), "1".to_string()],
+            vec!["a".to_string(), "2".to_string()],
+        ])
+        .unwrap();
+        let clusters = DuplicateCluster {
+            rows: vec![(0, 1.0), (1, 1.0)],
+        };
+        let report = duplicates_report(&table, &[clusters]).unwrap();
+        assert!(report.header().is_empty());
+        assert_eq!(report.rows()[1], vec!["a", "2", "1", "1.000"]);
+    }
+}
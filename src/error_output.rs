@@ -0,0 +1,140 @@
+use std::{error::Error, io, path::Path};
+
+use clap::ValueEnum;
+use serde_json::json;
+
+use crate::table::TableError;
+
+/// How errors are reported on stderr
+#[derive(Debug, Clone, Copy, PartialEq, Default, ValueEnum)]
+pub enum ErrorFormat {
+    /// `file: message` lines for people
+    #[default]
+    Human,
+    /// One JSON object per error for editors and CI wrappers
+    Json,
+}
+
+/// Formats an error for stderr
+///
+/// JSON errors carry a stable `code`, the `message`, the `file` and, where
+/// the error pins it down, the 0-based data `row` and the `column` name.
+/// `line` is reserved for errors that know their source line and is
+/// `null` otherwise.
+///
+/// # Arguments
+/// * `format` - Output format
+/// * `file` - Input the error relates to, if any
+/// * `error` - The error to report
+pub fn format_error(
+    format: ErrorFormat,
+    file: Option<&Path>,
+    error: &(dyn Error + 'static),
+) -> String {
+    match format {
+        ErrorFormat::Human => match file {
+            Some(file) => format!("{}: {}", file.display(), error),
+            None => error.to_string(),
+        },
+        ErrorFormat::Json => {
+            let (code, row, column) = describe(error);
+            json!({
+                "code": code,
+                "message": error.to_string(),
+                "file": file.map(|file| file.display().to_string()),
+                "line": null,
+                "row": row,
+                "column": column,
+            })
+            .to_string()
+        }
+    }
+}
+
+/// Returns the error code and, where known, the row and column it points at.
+fn describe(error: &(dyn Error + 'static)) -> (&'static str, Option<usize>, Option<String>) {
+    if let Some(error) = error.downcast_ref::<TableError>() {
+        return match error {
+            TableError::EmptyHeader => ("empty_header", None, None),
+            TableError::DuplicateColumn(column) => ("duplicate_column", None, Some(column.clone())),
+            TableError::RowLengthMismatch { row_index, .. } => {
+                ("row_length_mismatch", Some(*row_index), None)
+            }
+            TableError::InvalidRowIndex(row_index) => ("invalid_row_index", Some(*row_index), None),
+            TableError::InvalidTableSize => ("unknown_format", None, None),
+            TableError::MissingColumn(column) => ("missing_column", None, Some(column.clone())),
+            TableError::InvalidCell { row_index, column } => {
+                ("invalid_cell", Some(*row_index), Some(column.clone()))
+            }
+            TableError::InvalidArgument(_) => ("invalid_argument", None, None),
+        };
+    }
+
+    if let Some(error) = error.downcast_ref::<io::Error>() {
+        let code = match error.kind() {
+            io::ErrorKind::NotFound => "not_found",
+            io::ErrorKind::PermissionDenied => "permission_denied",
+            io::ErrorKind::InvalidData => "invalid_encoding",
+            _ => "io",
+        };
+        return (code, None, None);
+    }
+
+    ("error", None, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_error() {
+        let error = TableError::InvalidCell {
+            row_index: 3,
+            column: "value".to_string(),
+        };
+        let output = format_error(ErrorFormat::Json, Some(Path::new("metrics.csv")), &error);
+        let parsed: serde_json::Value = serde_json::from_str(&output).unwrap();
+
+        assert_eq!(parsed["code"], "invalid_cell");
+        assert_eq!(parsed["file"], "metrics.csv");
+        assert_eq!(parsed["row"], 3);
+        assert_eq!(parsed["column"], "value");
+        assert!(parsed["line"].is_null());
+    }
+
+    #[test]
+    fn test_human_error() {
+        let error = TableError::MissingColumn("id".to_string());
+        assert_eq!(
+            format_error(ErrorFormat::Human, Some(Path::new("a.csv")), &error),
+            format!("a.csv: {}", error)
+        );
+        assert_eq!(
+            format_error(ErrorFormat::Human, None, &error),
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn test_json_io_error() {
+        let error = io::Error::new(io::ErrorKind::NotFound, "no such file");
+        let output = format_error(ErrorFormat::Json, None, &error);
+        let parsed: serde_json::Value = serde_json::from_str(&output).unwrap();
+
+        assert_eq!(parsed["code"], "not_found");
+        assert!(parsed["file"].is_null());
+        assert!(parsed["row"].is_null());
+    }
+
+    #[test]
+    fn test_json_error_without_position() {
+        let error = TableError::InvalidArgument("bad".to_string());
+        let output = format_error(ErrorFormat::Json, None, &error);
+        let parsed: serde_json::Value = serde_json::from_str(&output).unwrap();
+
+        assert_eq!(parsed["code"], "invalid_argument");
+        assert_eq!(parsed["message"], error.to_string());
+        assert!(parsed["column"].is_null());
+    }
+}
@@ -0,0 +1,127 @@
+use std::fmt::Write;
+
+use crate::cell_value::CellValue;
+use crate::load::{LoadOptions, LoadedTable};
+use crate::table::Table;
+use crate::table_parser::TableType;
+
+/// Describes how a table was read, without running any operation on it
+///
+/// # Arguments
+/// * `name` - Label shown for the table, usually its path
+/// * `loaded` - The loaded table
+/// * `options` - Options the table was loaded with
+///
+/// # Returns
+/// * `String` - Detected format, header decision, row count and column types
+pub fn explain_table(name: &str, loaded: &LoadedTable, options: &LoadOptions) -> String {
+    let LoadedTable {
+        table,
+        table_type,
+        has_header,
+        row_errors,
+        renamed_columns,
+    } = loaded;
+
+    let mut report = String::new();
+    // Writing to a String never fails.
+    writeln!(report, "{}:", name).unwrap();
+    let forced_by = match (options.format, options.delimiter) {
+        (Some(_), _) => " (forced by --from)",
+        (None, Some(_)) => " (forced by --delimiter)",
+        (None, None) => "",
+    };
+    writeln!(report, "  Format: {}{}", table_type, forced_by).unwrap();
+    if let TableType::CsvTable { delimiter, quote } = *table_type {
+        writeln!(report, "  Delimiter: {}", delimiter_name(delimiter)).unwrap();
+        let quote = quote.map_or("none".to_string(), |quote| quote.to_string());
+        writeln!(report, "  Quote: {}", quote).unwrap();
+    }
+    let header_source = match table_type {
+        TableType::KeyValueTable | TableType::PrometheusTable => "built from record keys",
+        _ if options.has_header.is_some() && *has_header => "first line (forced by --header)",
+        _ if options.has_header.is_some() => "none (forced by --no-header)",
+        _ if *has_header => "first line",
+        _ => "none",
+    };
+    writeln!(report, "  Header: {}", header_source).unwrap();
+    writeln!(report, "  Rows: {}", table.row_count()).unwrap();
+    if options.skip_bad_rows {
+        writeln!(report, "  Skipped rows: {}", row_errors.len()).unwrap();
+    }
+    for (incoming, canonical) in renamed_columns {
+        writeln!(report, "  Renamed: {} -> {}", incoming, canonical).unwrap();
+    }
+    writeln!(report, "  Columns:").unwrap();
+
+    let header = table.header();
+    for column_index in 0..table.column_count() {
+        let column_name = header
+            .get(column_index)
+            .map_or_else(|| format!("#{}", column_index + 1), |name| name.to_string());
+        let metadata = table.column_metadata(&column_name);
+        write!(
+            report,
+            "    {}: {}",
+            metadata.map_or(column_name.clone(), |metadata| metadata
+                .display_name(&column_name)),
+            infer_column_type(table, column_index)
+        )
+        .unwrap();
+        match metadata.and_then(|metadata| metadata.description.as_deref()) {
+            Some(description) => writeln!(report, " - {}", description).unwrap(),
+            None => writeln!(report).unwrap(),
+        }
+    }
+
+    report
+}
+
+fn delimiter_name(delimiter: char) -> String {
+    match delimiter {
+        '\t' => "tab".to_string(),
+        ' ' => "space".to_string(),
+        delimiter => format!("'{}'", delimiter),
+    }
+}
+
+/// Picks the narrowest type that fits every non-empty cell of a column.
+pub fn infer_column_type(table: &Table, column_index: usize) -> &'static str {
+    let mut values = (0..table.row_count())
+        .filter_map(|row_index| table.get(row_index)?.get(column_index))
+        .filter(|value| !value.is_empty())
+        .peekable();
+
+    if values.peek().is_none() {
+        return "empty";
+    }
+
+    let values: Vec<&String> = values.collect();
+    if values.iter().all(|value| value.parse::<i64>().is_ok()) {
+        "integer"
+    } else if values.iter().all(|value| value.parse::<f64>().is_ok()) {
+        "float"
+    } else if values
+        .iter()
+        .all(|value| matches!(value.to_lowercase().as_str(), "true" | "false"))
+    {
+        "boolean"
+    } else if values
+        .iter()
+        .all(|value| matches!(CellValue::parse(value), CellValue::Duration(_)))
+    {
+        "duration"
+    } else if values
+        .iter()
+        .all(|value| matches!(CellValue::parse(value), CellValue::Ip(_)))
+    {
+        "ip"
+    } else if values
+        .iter()
+        .all(|value| matches!(CellValue::parse(value), CellValue::Version(_)))
+    {
+        "version"
+    } else {
+        "text"
+    }
+}
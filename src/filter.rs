@@ -0,0 +1,311 @@
+use std::{cmp::Ordering, net::IpAddr, sync::LazyLock};
+
+use regex::Regex;
+
+use crate::cell_value::CellValue;
+use crate::table::{Table, TableError};
+
+static COMPARISON: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\s*(.+?)\s*(==|!=|<=|>=|=|<|>)\s*(.*?)\s*$").unwrap());
+// The column of `in` has no comparison operator, so `note = logged in x`
+// stays a comparison.
+static MEMBERSHIP: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\s*([^=!<>]+?)\s+in\s+(\S+)\s*$").unwrap());
+
+/// An IPv4 or IPv6 network in CIDR notation, e.g. `10.0.0.0/8`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IpNetwork {
+    address: IpAddr,
+    prefix_len: u8,
+}
+
+impl IpNetwork {
+    /// Parses `address/prefix`; a bare address is a network of one
+    pub fn parse(value: &str) -> Option<Self> {
+        let (address, prefix_len) = match value.split_once('/') {
+            Some((address, prefix_len)) => (address.parse().ok()?, prefix_len.parse().ok()?),
+            None => {
+                let address: IpAddr = value.parse().ok()?;
+                (address, if address.is_ipv4() { 32 } else { 128 })
+            }
+        };
+        let max_len = if address.is_ipv4() { 32 } else { 128 };
+        (prefix_len <= max_len).then_some(IpNetwork {
+            address,
+            prefix_len,
+        })
+    }
+
+    /// Checks whether an address lies in the network
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        match (self.address, ip) {
+            (IpAddr::V4(network), IpAddr::V4(ip)) => {
+                let mask = u32::MAX
+                    .checked_shl(32 - u32::from(self.prefix_len))
+                    .unwrap_or(0);
+                u32::from(network) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(network), IpAddr::V6(ip)) => {
+                let mask = u128::MAX
+                    .checked_shl(128 - u32::from(self.prefix_len))
+                    .unwrap_or(0);
+                u128::from(network) & mask == u128::from(ip) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Operator {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Test {
+    Compare(Operator, String),
+    In(IpNetwork),
+}
+
+/// A `--where` condition on one column
+///
+/// Written as `column op value` with `op` one of `=`, `!=`, `<`, `<=`,
+/// `>`, `>=`, or as `column in network` for IP addresses in a CIDR
+/// network. Values compare by type, see `compare_cells`; ordering a
+/// number against text is never true. The value may be quoted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Condition {
+    column: String,
+    test: Test,
+}
+
+impl Condition {
+    /// Parses a condition such as `amount > 100` or `ip in 10.0.0.0/8`
+    pub fn parse(expression: &str) -> Result<Self, TableError> {
+        let invalid = |reason: &str| {
+            TableError::InvalidArgument(format!("invalid condition '{}': {}", expression, reason))
+        };
+
+        if let Some(captures) = MEMBERSHIP.captures(expression) {
+            let network = IpNetwork::parse(&captures[2])
+                .ok_or_else(|| invalid("'in' expects a CIDR network like 10.0.0.0/8"))?;
+            return Ok(Condition {
+                column: unquote(&captures[1]).to_string(),
+                test: Test::In(network),
+            });
+        }
+
+        let captures = COMPARISON
+            .captures(expression)
+            .ok_or_else(|| invalid("expected 'column op value' or 'column in network'"))?;
+        let operator = match &captures[2] {
+            "=" | "==" => Operator::Eq,
+            "!=" => Operator::Ne,
+            "<" => Operator::Lt,
+            "<=" => Operator::Le,
+            ">" => Operator::Gt,
+            _ => Operator::Ge,
+        };
+        Ok(Condition {
+            column: unquote(&captures[1]).to_string(),
+            test: Test::Compare(operator, unquote(&captures[3]).to_string()),
+        })
+    }
+
+    /// Checks a cell of the condition's column
+    pub fn matches(&self, cell: &str) -> bool {
+        self.check(cell, false)
+    }
+
+    /// The column whose cells the condition checks
+    pub fn column(&self) -> &str {
+        &self.column
+    }
+
+    /// Checks a cell, comparing it with the value as text when `as_text`
+    /// is set, so `10 > 9` is false; `in` still reads IP addresses
+    pub fn check(&self, cell: &str, as_text: bool) -> bool {
+        match &self.test {
+            Test::In(network) => {
+                matches!(CellValue::parse(cell), CellValue::Ip(ip) if network.contains(ip))
+            }
+            Test::Compare(operator, value) => {
+                let ordering = if as_text {
+                    Some(cell.cmp(value.as_str()))
+                } else {
+                    let (cell, value) = CellValue::parse_pair(cell, value);
+                    cell.compare(&value)
+                };
+                match operator {
+                    Operator::Eq => ordering == Some(Ordering::Equal),
+                    Operator::Ne => ordering != Some(Ordering::Equal),
+                    Operator::Lt => ordering == Some(Ordering::Less),
+                    Operator::Le => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+                    Operator::Gt => ordering == Some(Ordering::Greater),
+                    Operator::Ge => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+                }
+            }
+        }
+    }
+}
+
+/// Keeps the rows matching every condition
+///
+/// # Arguments
+/// * `table` - Table to filter
+/// * `conditions` - Conditions a row must all satisfy
+/// * `as_text` - Compare cells as text instead of by type, for `--strict`
+///
+/// # Returns
+/// * `Table` - The matching rows, in their original order
+pub fn filter_rows(
+    table: &Table,
+    conditions: &[Condition],
+    as_text: bool,
+) -> Result<Table, TableError> {
+    let columns = conditions
+        .iter()
+        .map(|condition| {
+            table
+                .column_index(&condition.column)
+                .ok_or_else(|| TableError::MissingColumn(condition.column.clone()))
+        })
+        .collect::<Result<Vec<usize>, TableError>>()?;
+
+    let rows = table
+        .rows()
+        .iter()
+        .filter(|row| {
+            conditions
+                .iter()
+                .zip(&columns)
+                .all(|(condition, index)| condition.check(&row[*index], as_text))
+        })
+        .cloned()
+        .collect();
+    table.with_rows(rows)
+}
+
+fn unquote(value: &str) -> &str {
+    ['"', '\'']
+        .iter()
+        .find_map(|quote| {
+            value
+                .strip_prefix(*quote)
+                .and_then(|value| value.strip_suffix(*quote))
+        })
+        .unwrap_or(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    use super::*;
+
+    #[test]
+    fn test_filter_by_network_and_value() {
+        let table = Table::with_header_and_data(
+            vec!["ip".to_string(), "hits".to_string()],
+            [
+                ("10.1.2.3", "5"),
+                ("192.168.0.1", "500"),
+                ("fd00::1", "50"),
+                ("n/a", "7"),
+            ]
+            .iter()
+            .map(|(ip, hits)| vec![ip.to_string(), hits.to_string()])
+            .collect(),
+        )
+        .unwrap();
+
+        let private = [
+            Condition::parse("ip in 10.0.0.0/8").unwrap(),
+            Condition::parse("hits >= 5").unwrap(),
+        ];
+        let filtered = filter_rows(&table, &private, false).unwrap();
+        assert_eq!(filtered.rows(), &[vec!["10.1.2.3", "5"]]);
+    }
+
+    #[test]
+    fn test_ipv6_networks() {
+        let v6 = Condition::parse("ip in fd00::/8").unwrap();
+        assert!(v6.matches("fd00::1"));
+        assert!(!v6.matches("10.1.2.3"));
+    }
+
+    #[test]
+    fn test_network_bounds() {
+        assert!(IpNetwork::parse("0.0.0.0/0")
+            .unwrap()
+            .contains(IpAddr::V4(Ipv4Addr::BROADCAST)));
+        assert!(!IpNetwork::parse("::1")
+            .unwrap()
+            .contains(IpAddr::V6(Ipv6Addr::UNSPECIFIED)));
+    }
+
+    #[test]
+    fn test_invalid_network() {
+        assert!(Condition::parse("ip in 10.0.0.0/33").is_err());
+    }
+
+    #[test]
+    fn test_quoted_and_spaced_values() {
+        assert!(Condition::parse("name = 'a b'").unwrap().matches("a b"));
+        let note = Condition::parse("note = logged in today").unwrap();
+        assert!(note.matches("logged in today"));
+        assert!(Condition::parse("note != 'sign in' ").is_ok());
+    }
+
+    #[test]
+    fn test_numbers_never_match_text() {
+        assert!(!Condition::parse("hits > 100").unwrap().matches("n/a"));
+    }
+
+    #[test]
+    fn test_version_comparisons() {
+        let at_least = Condition::parse("version >= 2.0.0").unwrap();
+        assert!(at_least.matches("2.10.1"));
+        assert!(!at_least.matches("2.0.0-beta"));
+        assert!(Condition::parse("version >= 2.0")
+            .unwrap()
+            .matches("2.10.1"));
+        assert!(!Condition::parse("version < 2").unwrap().matches("2.0.1"));
+    }
+
+    #[test]
+    fn test_filter_unknown_column() {
+        let table = Table::with_header_and_data(vec!["n".to_string()], Vec::new()).unwrap();
+        let conditions = [Condition::parse("hits > 1").unwrap()];
+        assert!(matches!(
+            filter_rows(&table, &conditions, false),
+            Err(TableError::MissingColumn(column)) if column == "hits"
+        ));
+    }
+
+    #[test]
+    fn test_filter_as_text() {
+        let table = Table::with_header_and_data(
+            vec!["n".to_string()],
+            ["9", "10", "9.0"]
+                .iter()
+                .map(|n| vec![n.to_string()])
+                .collect(),
+        )
+        .unwrap();
+        let conditions = [Condition::parse("n >= 9").unwrap()];
+
+        let typed = filter_rows(&table, &conditions, false).unwrap();
+        assert_eq!(typed.rows(), &[vec!["9"], vec!["10"], vec!["9.0"]]);
+        let text = filter_rows(&table, &conditions, true).unwrap();
+        assert_eq!(text.rows(), &[vec!["9"], vec!["9.0"]]);
+
+        let equal = [Condition::parse("n = 9").unwrap()];
+        assert_eq!(filter_rows(&table, &equal, true).unwrap().row_count(), 1);
+    }
+}
@@ -0,0 +1,266 @@
+use crate::table::{Table, TableError};
+
+/// Mean Earth radius used by the haversine formula
+const EARTH_RADIUS_KM: f64 = 6371.0088;
+
+/// A latitude/longitude pair in decimal degrees
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GeoPoint {
+    pub lat: f64,
+    pub lon: f64,
+}
+
+impl GeoPoint {
+    /// Reads a point, rejecting coordinates outside ±90° / ±180°
+    pub fn new(lat: &str, lon: &str) -> Option<Self> {
+        let lat: f64 = lat.trim().parse().ok()?;
+        let lon: f64 = lon.trim().parse().ok()?;
+        ((-90.0..=90.0).contains(&lat) && (-180.0..=180.0).contains(&lon))
+            .then_some(GeoPoint { lat, lon })
+    }
+
+    /// Great-circle distance to another point in kilometres
+    pub fn distance_km(&self, other: &GeoPoint) -> f64 {
+        let (lat1, lat2) = (self.lat.to_radians(), other.lat.to_radians());
+        let half_dlat = (lat2 - lat1) / 2.0;
+        let half_dlon = (other.lon - self.lon).to_radians() / 2.0;
+        let a = half_dlat.sin().powi(2) + lat1.cos() * lat2.cos() * half_dlon.sin().powi(2);
+        2.0 * EARTH_RADIUS_KM * a.sqrt().min(1.0).asin()
+    }
+}
+
+/// Latitude and longitude column names, parsed from `lat,lon`
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoordinateColumns {
+    pub lat: String,
+    pub lon: String,
+}
+
+impl CoordinateColumns {
+    pub fn parse(value: &str) -> Result<Self, TableError> {
+        match value.split_once(',') {
+            Some((lat, lon)) if !lat.is_empty() && !lon.is_empty() && !lon.contains(',') => {
+                Ok(CoordinateColumns {
+                    lat: lat.to_string(),
+                    lon: lon.to_string(),
+                })
+            }
+            _ => Err(TableError::InvalidArgument(format!(
+                "expected latitude and longitude columns as 'lat,lon', got '{}'",
+                value
+            ))),
+        }
+    }
+
+    fn indices(&self, table: &Table) -> Result<(usize, usize), TableError> {
+        let index = |column: &String| {
+            table
+                .column_index(column)
+                .ok_or_else(|| TableError::MissingColumn(column.clone()))
+        };
+        Ok((index(&self.lat)?, index(&self.lon)?))
+    }
+}
+
+/// A circle around a point, parsed from `lat,lon,radius_km`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Circle {
+    pub center: GeoPoint,
+    pub radius_km: f64,
+}
+
+impl Circle {
+    pub fn parse(value: &str) -> Result<Self, TableError> {
+        let invalid = || {
+            TableError::InvalidArgument(format!(
+                "expected 'lat,lon,radius_km' with a valid point and a non-negative radius, got '{}'",
+                value
+            ))
+        };
+        let parts: Vec<&str> = value.split(',').collect();
+        let [lat, lon, radius] = parts[..] else {
+            return Err(invalid());
+        };
+        let center = GeoPoint::new(lat, lon).ok_or_else(invalid)?;
+        let radius_km = radius
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|radius| *radius >= 0.0)
+            .ok_or_else(invalid)?;
+        Ok(Circle { center, radius_km })
+    }
+}
+
+/// Where distances are measured to
+#[derive(Debug, Clone, PartialEq)]
+pub enum DistanceTarget {
+    /// A second point in each row
+    Columns(CoordinateColumns),
+    /// The center of a circle; rows outside it are dropped
+    Near(Circle),
+}
+
+/// Appends the haversine distance between a row's point and a target
+///
+/// Distances are in kilometres with three decimals. Rows with an empty
+/// coordinate get an empty distance and never fall within a circle;
+/// coordinates that are not numbers or are out of range are an error.
+///
+/// # Arguments
+/// * `table` - Table to extend
+/// * `from` - Columns holding each row's point
+/// * `target` - Second point per row, or a circle to filter by
+/// * `into` - Name of the distance column
+///
+/// # Returns
+/// * `Table` - The table with the distance column appended
+pub fn add_distance(
+    table: &Table,
+    from: &CoordinateColumns,
+    target: &DistanceTarget,
+    into: &str,
+) -> Result<Table, TableError> {
+    let (lat, lon) = from.indices(table)?;
+    let target_indices = match target {
+        DistanceTarget::Columns(columns) => Some(columns.indices(table)?),
+        DistanceTarget::Near(_) => None,
+    };
+
+    let point = |row: &[String], row_index: usize, (lat, lon): (usize, usize)| {
+        if row[lat].trim().is_empty() || row[lon].trim().is_empty() {
+            return Ok(None);
+        }
+        GeoPoint::new(&row[lat], &row[lon])
+            .map(Some)
+            .ok_or_else(|| TableError::InvalidCell {
+                row_index,
+                column: table.header()[lat].to_string(),
+            })
+    };
+
+    let mut rows = Vec::new();
+    let mut distances = Vec::new();
+    for (row_index, row) in table.rows().iter().enumerate() {
+        let start = point(row, row_index, (lat, lon))?;
+        let end = match (target, target_indices) {
+            (DistanceTarget::Columns(_), Some(indices)) => point(row, row_index, indices)?,
+            (DistanceTarget::Near(circle), _) => Some(circle.center),
+            _ => unreachable!("column targets have indices"),
+        };
+        let distance = start.zip(end).map(|(start, end)| start.distance_km(&end));
+
+        if let DistanceTarget::Near(circle) = target {
+            if !distance.is_some_and(|distance| distance <= circle.radius_km) {
+                continue;
+            }
+        }
+        rows.push(row.clone());
+        distances.push(distance.map_or(String::new(), |distance| format!("{:.3}", distance)));
+    }
+
+    table.with_rows(rows)?.append_column(into, distances)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cities() -> Table {
+        Table::with_header_and_data(
+            ["city", "lat", "lon", "lat2", "lon2"]
+                .iter()
+                .map(|column| column.to_string())
+                .collect(),
+            [
+                ["Paris", "48.8566", "2.3522", "51.5074", "-0.1278"],
+                ["Berlin", "52.52", "13.405", "52.52", "13.405"],
+                ["Unknown", "", "", "0", "0"],
+            ]
+            .iter()
+            .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+            .collect(),
+        )
+        .unwrap()
+    }
+
+    fn from() -> CoordinateColumns {
+        CoordinateColumns::parse("lat,lon").unwrap()
+    }
+
+    #[test]
+    fn test_distance_between_columns() {
+        let to = DistanceTarget::Columns(CoordinateColumns::parse("lat2,lon2").unwrap());
+        let with_distance = add_distance(&cities(), &from(), &to, "km").unwrap();
+        assert_eq!(with_distance.header()[5], "km");
+        let distances: Vec<&str> = with_distance
+            .rows()
+            .iter()
+            .map(|row| row[5].as_str())
+            .collect();
+        assert_eq!(distances, vec!["343.557", "0.000", ""]);
+    }
+
+    #[test]
+    fn test_near_keeps_rows_within_radius() {
+        let near_paris = DistanceTarget::Near(Circle::parse("48.85,2.35,50").unwrap());
+        let near = add_distance(&cities(), &from(), &near_paris, "km").unwrap();
+        assert_eq!(near.rows().len(), 1);
+        assert_eq!(near.rows()[0][0], "Paris");
+    }
+
+    #[test]
+    fn test_out_of_range_coordinates() {
+        assert_eq!(GeoPoint::new("91", "0"), None);
+        assert_eq!(GeoPoint::new("0", "-181"), None);
+    }
+
+    #[test]
+    fn test_circle_out_of_range_center() {
+        assert!(matches!(
+            Circle::parse("91,0,10"),
+            Err(TableError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn test_circle_negative_radius() {
+        assert!(matches!(
+            Circle::parse("0,0,-1"),
+            Err(TableError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn test_invalid_coordinate_cells() {
+        let table = Table::with_header_and_data(
+            vec!["lat".to_string(), "lon".to_string()],
+            vec![vec!["north".to_string(), "0".to_string()]],
+        )
+        .unwrap();
+        let near = DistanceTarget::Near(Circle::parse("0,0,10").unwrap());
+        assert!(matches!(
+            add_distance(&table, &from(), &near, "km"),
+            Err(TableError::InvalidCell { row_index: 0, column }) if column == "lat"
+        ));
+    }
+
+    #[test]
+    fn test_coordinate_columns_need_two_names() {
+        for value in ["lat", "lat,lon,alt"] {
+            assert!(matches!(
+                CoordinateColumns::parse(value),
+                Err(TableError::InvalidArgument(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_unknown_coordinate_columns() {
+        let to = DistanceTarget::Columns(CoordinateColumns::parse("y,x").unwrap());
+        assert!(matches!(
+            add_distance(&cities(), &from(), &to, "km"),
+            Err(TableError::MissingColumn(column)) if column == "y"
+        ));
+    }
+}
@@ -0,0 +1,166 @@
+use std::{collections::BTreeMap, fs, io, path::Path};
+
+use serde::Deserialize;
+
+use crate::table::{Table, TableError};
+
+/// Maps incoming column names to canonical ones
+///
+/// Loaded from a TOML file with one section per canonical column:
+///
+/// ```toml
+/// [columns.customer_id]
+/// from = ["CustID", "Customer ID"]
+/// required = true
+///
+/// [columns.email]
+/// from = ["E-mail"]
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HeaderMap {
+    #[serde(default)]
+    pub columns: BTreeMap<String, ColumnMapping>,
+}
+
+/// Where a canonical column may come from
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ColumnMapping {
+    /// Incoming names accepted for the column, in order of preference
+    #[serde(default)]
+    pub from: Vec<String>,
+    /// Fail when none of the names is present
+    #[serde(default)]
+    pub required: bool,
+}
+
+impl HeaderMap {
+    /// Reads a header map from a TOML file
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let contents = fs::read_to_string(path)?;
+        toml::from_str(&contents)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error.message()))
+    }
+
+    /// Renames the table's columns to their canonical names
+    ///
+    /// A column already carrying the canonical name is kept. Otherwise the
+    /// first `from` name present is used, matching exactly first and then
+    /// ignoring case and surrounding whitespace. Columns the map doesn't
+    /// mention pass through unchanged.
+    ///
+    /// # Returns
+    /// * `Vec<(String, String)>` - The renames applied, as (incoming, canonical)
+    pub fn apply(&self, table: &mut Table) -> Result<Vec<(String, String)>, TableError> {
+        let mut renames = Vec::new();
+
+        for (canonical, mapping) in &self.columns {
+            if table.column_index(canonical).is_some() {
+                continue;
+            }
+
+            let header: Vec<String> = table.header().into_iter().map(String::from).collect();
+            let normalize = |name: &str| name.trim().to_lowercase();
+            let incoming = mapping
+                .from
+                .iter()
+                .find(|name| header.contains(name))
+                .cloned()
+                .or_else(|| {
+                    mapping.from.iter().find_map(|name| {
+                        header
+                            .iter()
+                            .find(|column| normalize(column) == normalize(name))
+                            .cloned()
+                    })
+                });
+
+            match incoming {
+                Some(incoming) => {
+                    table.rename_column(&incoming, canonical)?;
+                    renames.push((incoming, canonical.clone()));
+                }
+                None if mapping.required => {
+                    return Err(TableError::MissingColumn(canonical.clone()));
+                }
+                None => {}
+            }
+        }
+
+        Ok(renames)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header_map() -> HeaderMap {
+        toml::from_str(
+            r#"
+            [columns.customer_id]
+            from = ["CustID", "Customer ID"]
+            required = true
+
+            [columns.email]
+            from = ["E-mail"]
+
+            [columns.phone]
+            from = ["Phone"]
+            "#,
+        )
+        .unwrap()
+    }
+
+    fn table(header: &[&str]) -> Table {
+        let header: Vec<String> = header.iter().map(|name| name.to_string()).collect();
+        let row = vec![String::new(); header.len()];
+        Table::with_header_and_data(header, vec![row]).unwrap()
+    }
+
+    #[test]
+    fn test_exact_names_renamed() {
+        let mut table = table(&["CustID", "E-mail"]);
+        let renames = header_map().apply(&mut table).unwrap();
+        assert_eq!(table.header(), vec!["customer_id", "email"]);
+        assert_eq!(
+            renames,
+            vec![
+                ("CustID".to_string(), "customer_id".to_string()),
+                ("E-mail".to_string(), "email".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_names_matched_ignoring_case_and_whitespace() {
+        let mut table = table(&[" customer id", "e-mail", "name"]);
+        let renames = header_map().apply(&mut table).unwrap();
+        assert_eq!(table.header(), vec!["customer_id", "email", "name"]);
+        assert_eq!(renames.len(), 2);
+    }
+
+    #[test]
+    fn test_canonical_names_kept() {
+        let mut table = table(&["customer_id", "CustID"]);
+        let renames = header_map().apply(&mut table).unwrap();
+        assert_eq!(table.header(), vec!["customer_id", "CustID"]);
+        assert!(renames.is_empty());
+    }
+
+    #[test]
+    fn test_missing_required_column() {
+        let mut missing = table(&["name"]);
+        assert!(matches!(
+            header_map().apply(&mut missing),
+            Err(TableError::MissingColumn(column)) if column == "customer_id"
+        ));
+    }
+
+    #[test]
+    fn test_unknown_fields_rejected() {
+        let parsed: Result<HeaderMap, _> = toml::from_str("[columns.id]\nform = [\"ID\"]\n");
+        assert!(parsed.is_err());
+    }
+}
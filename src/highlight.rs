@@ -0,0 +1,176 @@
+use std::collections::HashMap;
+
+use crate::filter::Condition;
+use crate::table::{Table, TableError};
+
+/// Style names `--highlight` takes and their ANSI SGR parameters
+const STYLES: [(&str, &str); 10] = [
+    ("bold", "1"),
+    ("dim", "2"),
+    ("italic", "3"),
+    ("underline", "4"),
+    ("red", "31"),
+    ("green", "32"),
+    ("yellow", "33"),
+    ("blue", "34"),
+    ("magenta", "35"),
+    ("cyan", "36"),
+];
+
+/// A rule showing the cells that match a condition in a style
+#[derive(Debug, Clone, PartialEq)]
+pub struct Highlight {
+    condition: Condition,
+    /// SGR parameters of the style, e.g. `1;31` for bold red
+    style: String,
+}
+
+impl Highlight {
+    /// Parses `condition => style`, e.g. `amount < 0 => red`
+    ///
+    /// The condition is one `--where` takes. The style is one or more of
+    /// bold, dim, italic, underline, red, green, yellow, blue, magenta
+    /// and cyan, separated by spaces.
+    pub fn parse(text: &str) -> Result<Self, TableError> {
+        let invalid = |reason: &str| {
+            TableError::InvalidArgument(format!("invalid highlight '{}': {}", text, reason))
+        };
+        let (condition, style) = text
+            .rsplit_once("=>")
+            .ok_or_else(|| invalid("expected 'condition => style'"))?;
+        let style = style
+            .split_whitespace()
+            .map(|name| {
+                STYLES
+                    .iter()
+                    .find(|(style, _)| style.eq_ignore_ascii_case(name))
+                    .map(|(_, parameters)| *parameters)
+                    .ok_or_else(|| invalid(&format!("unknown style '{}'", name)))
+            })
+            .collect::<Result<Vec<&str>, TableError>>()?
+            .join(";");
+        if style.is_empty() {
+            return Err(invalid("expected a style after '=>'"));
+        }
+        Ok(Highlight {
+            condition: Condition::parse(condition)?,
+            style,
+        })
+    }
+}
+
+/// Finds the cells highlight rules apply to
+///
+/// A rule applies to the cells of its condition's column that match it.
+/// The styles of several rules matching one cell combine.
+///
+/// # Arguments
+/// * `table` - Table being rendered
+/// * `highlights` - Rules to apply
+/// * `as_text` - Compare cells as text instead of by type, for `--strict`
+///
+/// # Returns
+/// * `HashMap<(usize, usize), String>` - SGR parameters by row and column
+///   index
+pub fn highlight_cells(
+    table: &Table,
+    highlights: &[Highlight],
+    as_text: bool,
+) -> Result<HashMap<(usize, usize), String>, TableError> {
+    let mut styles: HashMap<(usize, usize), String> = HashMap::new();
+    for highlight in highlights {
+        let column = highlight.condition.column();
+        let column_index = table
+            .column_index(column)
+            .ok_or_else(|| TableError::MissingColumn(column.to_string()))?;
+        for (row_index, row) in table.rows().iter().enumerate() {
+            if highlight.condition.check(&row[column_index], as_text) {
+                styles
+                    .entry((row_index, column_index))
+                    .and_modify(|style| {
+                        style.push(';');
+                        style.push_str(&highlight.style);
+                    })
+                    .or_insert_with(|| highlight.style.clone());
+            }
+        }
+    }
+    Ok(styles)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table() -> Table {
+        Table::with_header_and_data(
+            vec!["status".to_string(), "amount".to_string()],
+            [["OK", "10"], ["FAILED", "-5"], ["OK", "9"]]
+                .iter()
+                .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+                .collect(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_highlight_cells() {
+        let highlights = [
+            Highlight::parse("amount < 0 => red").unwrap(),
+            Highlight::parse("status == \"FAILED\" => bold").unwrap(),
+        ];
+        let styles = highlight_cells(&table(), &highlights, false).unwrap();
+        assert_eq!(
+            styles,
+            HashMap::from([((1, 1), "31".to_string()), ((1, 0), "1".to_string())])
+        );
+    }
+
+    #[test]
+    fn test_styles_combine() {
+        let highlights = [
+            Highlight::parse("amount >= 10 => Green Underline").unwrap(),
+            Highlight::parse("amount > 0 => bold").unwrap(),
+        ];
+        let styles = highlight_cells(&table(), &highlights, false).unwrap();
+        assert_eq!(styles[&(0, 1)], "32;4;1");
+        assert_eq!(styles[&(2, 1)], "1");
+    }
+
+    #[test]
+    fn test_highlight_as_text() {
+        let highlights = [Highlight::parse("amount >= 10 => green").unwrap()];
+        let as_text = highlight_cells(&table(), &highlights, true).unwrap();
+        let mut cells: Vec<(usize, usize)> = as_text.into_keys().collect();
+        cells.sort();
+        assert_eq!(cells, [(0, 1), (2, 1)]);
+    }
+
+    #[test]
+    fn test_rule_without_style() {
+        for text in ["amount < 0", "amount < 0 => "] {
+            assert!(matches!(
+                Highlight::parse(text),
+                Err(TableError::InvalidArgument(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_rule_with_unknown_style() {
+        assert!(matches!(
+            Highlight::parse("amount < 0 => blink"),
+            Err(TableError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn test_highlight_unknown_column() {
+        let table = Table::with_header_and_data(vec!["amount".to_string()], vec![]).unwrap();
+        let highlights = [Highlight::parse("price < 0 => red").unwrap()];
+        assert!(matches!(
+            highlight_cells(&table, &highlights, false),
+            Err(TableError::MissingColumn(column)) if column == "price"
+        ));
+    }
+}
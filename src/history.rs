@@ -0,0 +1,192 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::table::{Table, TableError};
+
+/// Traces how a column's value evolves per key across ordered snapshots
+///
+/// A key's first appearance is reported as `added`, every later value
+/// that differs from the previous snapshot as `changed`, and a key
+/// missing from a snapshot after being seen as `removed` (a later
+/// reappearance is `added` again). Within a snapshot the first row with a
+/// given key wins.
+///
+/// # Arguments
+/// * `snapshots` - Snapshot names with their tables, oldest first
+/// * `key_column` - Column identifying a record
+/// * `value_column` - Column whose changes are traced
+///
+/// # Returns
+/// * `Table` - Columns `<key_column>`, `snapshot`, `change`, `from`, `to`,
+///   grouped by key in order of first appearance
+pub fn value_history(
+    snapshots: &[(String, Table)],
+    key_column: &str,
+    value_column: &str,
+) -> Result<Table, TableError> {
+    let mut keys: Vec<String> = Vec::new();
+    let mut histories: HashMap<String, Vec<Vec<String>>> = HashMap::new();
+    let mut current: HashMap<String, String> = HashMap::new();
+
+    for (snapshot, table) in snapshots {
+        let key_index = table
+            .column_index(key_column)
+            .ok_or_else(|| TableError::MissingColumn(key_column.to_string()))?;
+        let value_index = table
+            .column_index(value_column)
+            .ok_or_else(|| TableError::MissingColumn(value_column.to_string()))?;
+
+        let mut values: HashMap<&String, &String> = HashMap::new();
+        for row in table.rows() {
+            values.entry(&row[key_index]).or_insert(&row[value_index]);
+        }
+
+        for row in table.rows() {
+            let key = &row[key_index];
+            let Some(value) = values.remove(key) else {
+                continue;
+            };
+
+            let change = match current.get(key) {
+                None => Some(("added", String::new())),
+                Some(previous) if previous != value => Some(("changed", previous.clone())),
+                Some(_) => None,
+            };
+
+            if let Some((change, from)) = change {
+                if !histories.contains_key(key) {
+                    keys.push(key.clone());
+                }
+                histories.entry(key.clone()).or_default().push(vec![
+                    key.clone(),
+                    snapshot.clone(),
+                    change.to_string(),
+                    from,
+                    value.clone(),
+                ]);
+                current.insert(key.clone(), value.clone());
+            }
+        }
+
+        let present: HashSet<&String> = table.rows().iter().map(|row| &row[key_index]).collect();
+        current.retain(|key, previous| {
+            if present.contains(key) {
+                return true;
+            }
+            histories.entry(key.clone()).or_default().push(vec![
+                key.clone(),
+                snapshot.clone(),
+                "removed".to_string(),
+                previous.clone(),
+                String::new(),
+            ]);
+            false
+        });
+    }
+
+    let rows = keys
+        .iter()
+        .flat_map(|key| histories.remove(key).unwrap_or_default())
+        .collect();
+
+    Table::with_header_and_data(
+        vec![
+            key_column.to_string(),
+            "snapshot".to_string(),
+            "change".to_string(),
+            "from".to_string(),
+            "to".to_string(),
+        ],
+        rows,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(name: &str, rows: &[(&str, &str)]) -> (String, Table) {
+        let table = Table::with_header_and_data(
+            vec!["id".to_string(), "status".to_string()],
+            rows.iter()
+                .map(|(id, status)| vec![id.to_string(), status.to_string()])
+                .collect(),
+        )
+        .unwrap();
+        (name.to_string(), table)
+    }
+
+    fn history(snapshots: &[(String, Table)]) -> Vec<String> {
+        value_history(snapshots, "id", "status")
+            .unwrap()
+            .rows()
+            .iter()
+            .map(|row| row.join("|"))
+            .collect()
+    }
+
+    #[test]
+    fn test_added_and_changed_values() {
+        let snapshots = vec![
+            snapshot("2024-01", &[("1", "new")]),
+            snapshot("2024-02", &[("1", "open")]),
+        ];
+        assert_eq!(
+            history(&snapshots),
+            vec!["1|2024-01|added||new", "1|2024-02|changed|new|open"]
+        );
+    }
+
+    #[test]
+    fn test_unchanged_values_are_skipped() {
+        let snapshots = vec![
+            snapshot("2024-01", &[("1", "new")]),
+            snapshot("2024-02", &[("1", "new")]),
+        ];
+        assert_eq!(history(&snapshots), vec!["1|2024-01|added||new"]);
+    }
+
+    #[test]
+    fn test_removed_and_readded_keys() {
+        let snapshots = vec![
+            snapshot("2024-01", &[("1", "new")]),
+            snapshot("2024-02", &[]),
+            snapshot("2024-03", &[("1", "open")]),
+        ];
+        assert_eq!(
+            history(&snapshots),
+            vec![
+                "1|2024-01|added||new",
+                "1|2024-02|removed|new|",
+                "1|2024-03|added||open",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_grouped_by_key() {
+        let snapshots = vec![
+            snapshot("2024-01", &[("2", "new"), ("1", "new")]),
+            snapshot("2024-02", &[("1", "open"), ("2", "open")]),
+        ];
+        let keys: Vec<String> = history(&snapshots)
+            .iter()
+            .map(|row| row[..1].to_string())
+            .collect();
+        assert_eq!(keys, vec!["2", "2", "1", "1"]);
+    }
+
+    #[test]
+    fn test_first_row_of_a_key_wins() {
+        let snapshots = vec![snapshot("2024-01", &[("1", "new"), ("1", "open")])];
+        assert_eq!(history(&snapshots), vec!["1|2024-01|added||new"]);
+    }
+
+    #[test]
+    fn test_history_unknown_column() {
+        let snapshots = vec![snapshot("2024-01", &[("1", "new")])];
+        assert!(matches!(
+            value_history(&snapshots, "id", "state"),
+            Err(TableError::MissingColumn(column)) if column == "state"
+        ));
+    }
+}
@@ -0,0 +1,229 @@
+use std::{
+    fs::{self, File},
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
+
+use zip::{result::ZipError, ZipArchive};
+
+/// Separates an archive path from the entry to read out of it
+pub const ARCHIVE_ENTRY_SEPARATOR: &str = "::";
+
+/// Path standing for standard input
+pub const STDIN_PATH: &str = "-";
+
+/// Reads table contents from a file or from a file inside a `.zip` archive
+///
+/// `data.zip::report.csv` reads `report.csv` out of `data.zip`; a bare
+/// `data.zip` is accepted when the archive holds exactly one file. `-`
+/// reads standard input.
+///
+/// # Arguments
+/// * `path` - File path, optionally with an `::entry` suffix, or `-`
+/// * `password` - Password for encrypted archive entries
+///
+/// # Returns
+/// * `String` - Contents of the file or archive entry
+pub fn read_input(path: &Path, password: Option<&str>) -> io::Result<String> {
+    if is_stdin(path) {
+        return read_from(io::stdin().lock());
+    }
+
+    let path_str = path.to_string_lossy();
+
+    if let Some((archive, entry)) = path_str.split_once(ARCHIVE_ENTRY_SEPARATOR) {
+        if is_zip_path(archive) {
+            return read_archive_entry(Path::new(archive), Some(entry), password);
+        }
+    }
+
+    if is_zip_path(&path_str) {
+        return read_archive_entry(path, None, password);
+    }
+
+    fs::read_to_string(path)
+}
+
+/// Expands glob patterns such as `reports/*.csv` into the files they match
+///
+/// Paths without `*`, `?` or `[`, paths that exist and `-` are kept as
+/// they are, so patterns a shell already expanded are left alone. Matches
+/// of a pattern are sorted; a pattern matching nothing is an error.
+///
+/// # Arguments
+/// * `paths` - Paths and patterns, in order
+///
+/// # Returns
+/// * `Vec<PathBuf>` - The paths with every pattern replaced by its matches
+pub fn expand_globs(paths: &[PathBuf]) -> io::Result<Vec<PathBuf>> {
+    let mut expanded = Vec::with_capacity(paths.len());
+    for path in paths {
+        let pattern = path.to_string_lossy();
+        if is_stdin(path) || path.exists() || !pattern.contains(['*', '?', '[']) {
+            expanded.push(path.clone());
+            continue;
+        }
+
+        let invalid = |error: String| io::Error::new(io::ErrorKind::InvalidInput, error);
+        let mut matches = glob::glob(&pattern)
+            .map_err(|error| invalid(format!("invalid pattern '{}': {}", pattern, error)))?
+            .collect::<Result<Vec<PathBuf>, _>>()
+            .map_err(|error| io::Error::new(error.error().kind(), error.to_string()))?;
+        if matches.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("no files match '{}'", pattern),
+            ));
+        }
+        matches.sort();
+        expanded.extend(matches);
+    }
+    Ok(expanded)
+}
+
+/// Reads table contents from any reader, e.g. a pipe
+pub fn read_from(mut reader: impl Read) -> io::Result<String> {
+    let mut data = String::new();
+    reader.read_to_string(&mut data)?;
+    Ok(data)
+}
+
+/// Checks whether a path stands for standard input
+pub fn is_stdin(path: &Path) -> bool {
+    path.as_os_str() == STDIN_PATH
+}
+
+fn is_zip_path(path: &str) -> bool {
+    path.to_lowercase().ends_with(".zip")
+}
+
+fn read_archive_entry(
+    archive_path: &Path,
+    entry: Option<&str>,
+    password: Option<&str>,
+) -> io::Result<String> {
+    let mut archive = ZipArchive::new(File::open(archive_path)?)?;
+
+    let entry = match entry {
+        Some(entry) => entry.to_string(),
+        None => {
+            let files: Vec<&str> = archive
+                .file_names()
+                .filter(|name| !name.ends_with('/'))
+                .collect();
+            match files.as_slice() {
+                [single] => single.to_string(),
+                _ => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!(
+                            "archive contains {} files, select one with {}{}<file>",
+                            files.len(),
+                            archive_path.display(),
+                            ARCHIVE_ENTRY_SEPARATOR
+                        ),
+                    ))
+                }
+            }
+        }
+    };
+
+    let file = match password {
+        Some(password) => archive.by_name_decrypt(&entry, password.as_bytes()),
+        None => archive.by_name(&entry),
+    };
+
+    let file = file.map_err(|error| match error {
+        ZipError::UnsupportedArchive(ZipError::PASSWORD_REQUIRED) => io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!("'{}' is encrypted, a password is required", entry),
+        ),
+        ZipError::FileNotFound => io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("'{}' not found in {}", entry, archive_path.display()),
+        ),
+        error => error.into(),
+    })?;
+
+    read_from(file)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use zip::{write::SimpleFileOptions, ZipWriter};
+
+    use super::*;
+
+    #[test]
+    fn test_read_input_from_archive() {
+        let archive_path =
+            std::env::temp_dir().join(format!("tables-input-{}.zip", std::process::id()));
+
+        let mut writer = ZipWriter::new(File::create(&archive_path).unwrap());
+        writer
+            .start_file("report.csv", SimpleFileOptions::default())
+            .unwrap();
+        writer.write_all(b"id,name\n1,alice\n").unwrap();
+        writer.finish().unwrap();
+
+        let entry_path = format!("{}::report.csv", archive_path.display());
+        let from_entry = read_input(Path::new(&entry_path), None).unwrap();
+        let from_single = read_input(&archive_path, None).unwrap();
+        let missing_path = format!("{}::other.csv", archive_path.display());
+        let missing = read_input(Path::new(&missing_path), None);
+
+        fs::remove_file(&archive_path).unwrap();
+
+        assert_eq!(from_entry, "id,name\n1,alice\n");
+        assert_eq!(from_single, from_entry);
+        assert_eq!(missing.unwrap_err().kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn test_expand_globs() {
+        let dir = std::env::temp_dir().join(format!("tables-glob-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        for name in ["b.csv", "a.csv", "notes.txt"] {
+            fs::write(dir.join(name), "x\n").unwrap();
+        }
+
+        let expanded = expand_globs(&[
+            dir.join("*.csv"),
+            PathBuf::from(STDIN_PATH),
+            dir.join("notes.txt"),
+        ]);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            expanded.unwrap(),
+            vec![
+                dir.join("a.csv"),
+                dir.join("b.csv"),
+                PathBuf::from(STDIN_PATH),
+                dir.join("notes.txt")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_expand_glob_without_matches() {
+        let dir = std::env::temp_dir().join(format!("tables-no-glob-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let missing = expand_globs(&[dir.join("*.tsv")]);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(missing.unwrap_err().kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn test_read_from_reader() {
+        let data = read_from(io::Cursor::new(b"a;b\n1;2\n")).unwrap();
+
+        assert_eq!(data, "a;b\n1;2\n");
+        assert!(is_stdin(Path::new(STDIN_PATH)));
+        assert!(!is_stdin(Path::new("./-")));
+    }
+}
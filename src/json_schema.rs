@@ -0,0 +1,170 @@
+use std::collections::BTreeSet;
+
+use clap::ValueEnum;
+use serde_json::{json, Map, Value};
+
+use crate::cell_value::CellValue;
+use crate::explain::infer_column_type;
+use crate::table::Table;
+
+/// Schema languages the `schema` command can write
+#[derive(Debug, Clone, Copy, PartialEq, Default, ValueEnum)]
+pub enum SchemaFormat {
+    /// JSON Schema (draft 2020-12) of one row as an object
+    #[default]
+    JsonSchema,
+}
+
+/// Describes a row of the table as a JSON Schema object
+///
+/// Property types follow the types `--explain` infers. Columns without
+/// empty cells are required. Text columns with at most `max_enum`
+/// distinct values, each seen at least twice on average, get an `enum`
+/// of those values. Column descriptions from metadata sidecars are kept.
+///
+/// # Arguments
+/// * `title` - Schema title, usually the input path
+/// * `table` - Table to describe
+/// * `max_enum` - Largest number of distinct values listed as an enum
+///
+/// # Returns
+/// * `Value` - The JSON Schema document
+pub fn json_schema(title: &str, table: &Table, max_enum: usize) -> Value {
+    let header = table.header();
+    let mut properties = Map::new();
+    let mut required = Vec::new();
+
+    for column_index in 0..table.column_count() {
+        let column_name = header
+            .get(column_index)
+            .map_or_else(|| format!("#{}", column_index + 1), |name| name.to_string());
+        let values: Vec<&str> = table
+            .rows()
+            .iter()
+            .filter_map(|row| row.get(column_index))
+            .map(|value| value.as_str())
+            .filter(|value| !value.is_empty())
+            .collect();
+
+        let mut property = match infer_column_type(table, column_index) {
+            "integer" => json!({"type": "integer"}),
+            "float" => json!({"type": "number"}),
+            "boolean" => json!({"type": "boolean"}),
+            "empty" => json!({"type": "null"}),
+            "ip" => {
+                let all_v4 = values.iter().all(
+                    |value| matches!(CellValue::parse(value), CellValue::Ip(ip) if ip.is_ipv4()),
+                );
+                let all_v6 = values.iter().all(
+                    |value| matches!(CellValue::parse(value), CellValue::Ip(ip) if ip.is_ipv6()),
+                );
+                match (all_v4, all_v6) {
+                    (true, _) => json!({"type": "string", "format": "ipv4"}),
+                    (_, true) => json!({"type": "string", "format": "ipv6"}),
+                    _ => json!({"type": "string"}),
+                }
+            }
+            _ => {
+                let distinct: BTreeSet<&str> = values.iter().copied().collect();
+                if !distinct.is_empty()
+                    && distinct.len() <= max_enum
+                    && distinct.len() * 2 <= values.len()
+                {
+                    json!({"type": "string", "enum": distinct})
+                } else {
+                    json!({"type": "string"})
+                }
+            }
+        };
+        if let Some(description) = table
+            .column_metadata(&column_name)
+            .and_then(|metadata| metadata.description.as_deref())
+        {
+            property["description"] = json!(description);
+        }
+
+        if values.len() == table.row_count() && table.row_count() > 0 {
+            required.push(column_name.clone());
+        }
+        properties.insert(column_name, property);
+    }
+
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": title,
+        "type": "object",
+        "properties": properties,
+        "required": required,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tickets() -> Table {
+        Table::with_header_and_data(
+            ["id", "status", "ip", "note"]
+                .iter()
+                .map(|column| column.to_string())
+                .collect(),
+            [
+                ["1", "open", "10.0.0.1", "first"],
+                ["2", "closed", "10.0.0.2", ""],
+                ["3", "open", "10.0.0.3", "third"],
+                ["4", "open", "10.0.0.4", "fourth"],
+            ]
+            .iter()
+            .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+            .collect(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_schema_document() {
+        let schema = json_schema("tickets.csv", &tickets(), 10);
+        assert_eq!(schema["title"], "tickets.csv");
+        assert_eq!(schema["type"], "object");
+        assert_eq!(schema["properties"]["id"], json!({"type": "integer"}));
+    }
+
+    #[test]
+    fn test_enum_of_repeated_values() {
+        let schema = json_schema("tickets.csv", &tickets(), 10);
+        assert_eq!(
+            schema["properties"]["status"],
+            json!({"type": "string", "enum": ["closed", "open"]})
+        );
+        assert_eq!(schema["properties"]["note"], json!({"type": "string"}));
+    }
+
+    #[test]
+    fn test_enum_limit() {
+        let schema = json_schema("tickets.csv", &tickets(), 1);
+        assert_eq!(schema["properties"]["status"], json!({"type": "string"}));
+    }
+
+    #[test]
+    fn test_ip_formats() {
+        let schema = json_schema("tickets.csv", &tickets(), 10);
+        assert_eq!(
+            schema["properties"]["ip"],
+            json!({"type": "string", "format": "ipv4"})
+        );
+    }
+
+    #[test]
+    fn test_required_columns() {
+        let schema = json_schema("tickets.csv", &tickets(), 10);
+        assert_eq!(schema["required"], json!(["id", "status", "ip"]));
+    }
+
+    #[test]
+    fn test_headerless_columns_by_position() {
+        let table = Table::with_data(vec![vec!["1".to_string(), "x".to_string()]]).unwrap();
+        let schema = json_schema("data.csv", &table, 10);
+        assert_eq!(schema["properties"]["#1"], json!({"type": "integer"}));
+        assert_eq!(schema["required"], json!(["#1", "#2"]));
+    }
+}
@@ -0,0 +1,227 @@
+use crate::header_map::HeaderMap;
+use crate::table::{Table, TableError};
+use crate::table_parser::{
+    deduct_table_type, delimited_table_type, detect_header, parse_table, parse_table_lenient,
+    InputFormat, RowError, TableType,
+};
+
+/// How raw input is turned into a table
+#[derive(Debug, Default)]
+pub struct LoadOptions {
+    /// Leave out malformed rows instead of failing
+    pub skip_bad_rows: bool,
+    /// Canonical column names applied after parsing
+    pub header_map: Option<HeaderMap>,
+    /// Format forced with `--from` instead of detecting it
+    pub format: Option<InputFormat>,
+    /// Delimiter forced with `--delimiter`; reads input as CSV unless
+    /// another format is forced
+    pub delimiter: Option<char>,
+    /// Header decision forced with `--header`/`--no-header`
+    pub has_header: Option<bool>,
+    /// Fail instead of detecting anything, see `check_strict`
+    pub strict: bool,
+}
+
+/// A parsed table together with the decisions made while reading it
+#[derive(Debug)]
+pub struct LoadedTable {
+    pub table: Table,
+    pub table_type: TableType,
+    pub has_header: bool,
+    /// Rows skipped because they were malformed
+    pub row_errors: Vec<RowError>,
+    /// Columns renamed by the header map, as (incoming, canonical)
+    pub renamed_columns: Vec<(String, String)>,
+}
+
+/// Detects the format and header of raw input and parses it
+///
+/// # Arguments
+/// * `data` - Raw table contents
+/// * `options` - Parsing options
+///
+/// # Returns
+/// * `LoadedTable` - The table and how it was read
+pub fn load_table(data: &str, options: &LoadOptions) -> Result<LoadedTable, TableError> {
+    check_strict(options)?;
+    let (table_type, has_header) = detect_layout(data, options);
+    load_with_layout(data, table_type, has_header, options)
+}
+
+/// Checks that a `--strict` run states everything detection would guess
+///
+/// Strict runs need the format, or a delimiter for CSV, and the header
+/// decision. CSV fields are then quoted with `"` rather than a sniffed
+/// quote character.
+pub fn check_strict(options: &LoadOptions) -> Result<(), TableError> {
+    if !options.strict {
+        return Ok(());
+    }
+    let missing = match (options.format, options.delimiter, options.has_header) {
+        (None, None, _) => "--from or --delimiter",
+        (Some(InputFormat::Csv), None, _) => "--delimiter for CSV input",
+        (_, _, None) => "--header or --no-header",
+        _ => return Ok(()),
+    };
+    Err(TableError::InvalidArgument(format!(
+        "--strict requires {}",
+        missing
+    )))
+}
+
+/// Detects the format of raw input and whether its first line is a header
+///
+/// A forced format or delimiter skips format detection, and a forced
+/// header decision skips header detection.
+pub fn detect_layout(data: &str, options: &LoadOptions) -> (TableType, bool) {
+    let table_type = match (options.format, options.delimiter) {
+        (None | Some(InputFormat::Csv), Some(delimiter)) if options.strict => TableType::CsvTable {
+            delimiter,
+            quote: Some('"'),
+        },
+        (None | Some(InputFormat::Csv), Some(delimiter)) => delimited_table_type(data, delimiter),
+        (Some(format), _) => format.table_type(data),
+        (None, None) => deduct_table_type(data),
+    };
+    let has_header = options
+        .has_header
+        .unwrap_or_else(|| detect_header(table_type, data));
+    (table_type, has_header)
+}
+
+/// Parses raw input whose layout is already known
+///
+/// # Arguments
+/// * `data` - Raw table contents
+/// * `table_type` - Format to parse the input as
+/// * `has_header` - Whether the first line holds column names
+/// * `options` - Parsing options
+///
+/// # Returns
+/// * `LoadedTable` - The table and how it was read
+pub fn load_with_layout(
+    data: &str,
+    table_type: TableType,
+    has_header: bool,
+    options: &LoadOptions,
+) -> Result<LoadedTable, TableError> {
+    let (mut table, row_errors) = if options.skip_bad_rows {
+        parse_table_lenient(table_type, data, has_header)?
+    } else {
+        (parse_table(table_type, data, has_header)?, Vec::new())
+    };
+
+    let renamed_columns = match &options.header_map {
+        Some(header_map) => header_map.apply(&mut table)?,
+        None => Vec::new(),
+    };
+
+    Ok(LoadedTable {
+        table,
+        table_type,
+        has_header,
+        row_errors,
+        renamed_columns,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_forced_layout_skips_detection() {
+        let data = "id;name\n1;alice\n";
+        assert!(detect_layout(data, &LoadOptions::default()).1);
+
+        let options = LoadOptions {
+            delimiter: Some(';'),
+            has_header: Some(false),
+            ..LoadOptions::default()
+        };
+        let loaded = load_table(data, &options).unwrap();
+
+        assert!(!loaded.has_header);
+        assert_eq!(loaded.table.row_count(), 2);
+        assert_eq!(loaded.table.get(0).unwrap(), &vec!["id", "name"]);
+    }
+
+    fn strict(
+        format: Option<InputFormat>,
+        delimiter: Option<char>,
+        has_header: Option<bool>,
+    ) -> LoadOptions {
+        LoadOptions {
+            format,
+            delimiter,
+            has_header,
+            strict: true,
+            ..LoadOptions::default()
+        }
+    }
+
+    fn strict_error(options: &LoadOptions) -> String {
+        match load_table("a,b\n1,2\n", options) {
+            Err(TableError::InvalidArgument(message)) => message,
+            _ => panic!("expected a strict mode error"),
+        }
+    }
+
+    #[test]
+    fn test_strict_requires_format() {
+        assert!(strict_error(&strict(None, None, Some(true))).contains("--from or --delimiter"));
+    }
+
+    #[test]
+    fn test_strict_requires_csv_delimiter() {
+        assert!(
+            strict_error(&strict(Some(InputFormat::Csv), None, Some(true)))
+                .contains("--delimiter for CSV input")
+        );
+    }
+
+    #[test]
+    fn test_strict_requires_header() {
+        assert!(strict_error(&strict(None, Some(','), None)).contains("--header or --no-header"));
+    }
+
+    #[test]
+    fn test_strict_ascii_needs_no_delimiter() {
+        assert!(load_table(
+            "a,b\n",
+            &strict(Some(InputFormat::Ascii), None, Some(false))
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn test_sniffed_quotes() {
+        let guessed = LoadOptions {
+            delimiter: Some(','),
+            has_header: Some(true),
+            ..LoadOptions::default()
+        };
+        assert_eq!(
+            load_table("a,b\n'x,y',2\n", &guessed)
+                .unwrap()
+                .table
+                .get(0)
+                .unwrap()[0],
+            "x,y"
+        );
+    }
+
+    #[test]
+    fn test_strict_does_not_sniff_quotes() {
+        let options = strict(None, Some(','), Some(true));
+        assert!(load_table("a,b\n'x,y',2\n", &options).is_err());
+    }
+
+    #[test]
+    fn test_strict_reads_double_quotes() {
+        let options = strict(None, Some(','), Some(true));
+        let quoted = load_table("a,b\n\"x,y\",2\n", &options).unwrap();
+        assert_eq!(quoted.table.get(0).unwrap()[0], "x,y");
+    }
+}
@@ -0,0 +1,298 @@
+use std::sync::LazyLock;
+
+use chrono::NaiveDate;
+use regex::Regex;
+
+use crate::table::{Table, TableError};
+
+static NUMBER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(-?)(0|[1-9][0-9]*)(?:\.([0-9]+))?$").unwrap());
+static ISO_DATE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^([0-9]{4})-([0-9]{2})-([0-9]{2})(?:[T ](.+))?$").unwrap());
+
+/// Order of the date fields in rendered dates
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DateOrder {
+    YearMonthDay,
+    DayMonthYear,
+    MonthDayYear,
+}
+
+/// How numbers and dates are rendered for a locale
+///
+/// Only values in machine form are rewritten: plain decimal numbers such
+/// as `-1234.5` and valid ISO dates such as `2024-01-31` (optionally
+/// followed by a time, which is kept as is). Numbers with leading zeros
+/// are treated as codes and left alone.
+///
+/// Tables are localized only in the named columns, so IDs and postal
+/// codes elsewhere keep their digits.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LocaleProfile {
+    pub decimal_separator: char,
+    pub thousands_separator: Option<char>,
+    pub date_order: DateOrder,
+    pub date_separator: char,
+    /// Columns whose values are localized
+    pub columns: Vec<String>,
+}
+
+impl Default for LocaleProfile {
+    /// The machine format input is read in, which renders values unchanged
+    fn default() -> Self {
+        LocaleProfile {
+            decimal_separator: '.',
+            thousands_separator: None,
+            date_order: DateOrder::YearMonthDay,
+            date_separator: '-',
+            columns: Vec::new(),
+        }
+    }
+}
+
+/// Known locale tags with (decimal, thousands, date order, date separator)
+const LOCALES: &[(&str, char, char, DateOrder, char)] = &[
+    ("en-US", '.', ',', DateOrder::MonthDayYear, '/'),
+    ("en-GB", '.', ',', DateOrder::DayMonthYear, '/'),
+    ("de-DE", ',', '.', DateOrder::DayMonthYear, '.'),
+    ("fr-FR", ',', ' ', DateOrder::DayMonthYear, '/'),
+    ("es-ES", ',', '.', DateOrder::DayMonthYear, '/'),
+    ("it-IT", ',', '.', DateOrder::DayMonthYear, '/'),
+    ("pt-BR", ',', '.', DateOrder::DayMonthYear, '/'),
+    ("ru-RU", ',', ' ', DateOrder::DayMonthYear, '.'),
+    ("ja-JP", '.', ',', DateOrder::YearMonthDay, '/'),
+];
+
+impl LocaleProfile {
+    /// Looks up a locale by tag such as `de-DE`, `de_DE` or `de`
+    ///
+    /// # Arguments
+    /// * `tag` - Locale tag, case-insensitive
+    ///
+    /// # Returns
+    /// * `LocaleProfile` - The profile, or `InvalidArgument` listing known tags
+    pub fn from_tag(tag: &str) -> Result<Self, TableError> {
+        let tag = tag.replace('_', "-");
+        let matches = |known: &str| {
+            known.eq_ignore_ascii_case(&tag)
+                || known
+                    .split('-')
+                    .next()
+                    .is_some_and(|language| language.eq_ignore_ascii_case(&tag))
+        };
+
+        if ["C", "POSIX"].iter().any(|c| c.eq_ignore_ascii_case(&tag)) {
+            return Ok(LocaleProfile::default());
+        }
+        LOCALES
+            .iter()
+            .find(|locale| matches(locale.0))
+            .map(
+                |(_, decimal, thousands, date_order, date_separator)| LocaleProfile {
+                    decimal_separator: *decimal,
+                    thousands_separator: Some(*thousands),
+                    date_order: *date_order,
+                    date_separator: *date_separator,
+                    columns: Vec::new(),
+                },
+            )
+            .ok_or_else(|| {
+                let known: Vec<&str> = LOCALES.iter().map(|locale| locale.0).collect();
+                TableError::InvalidArgument(format!(
+                    "unknown locale '{}', expected C or one of {}",
+                    tag,
+                    known.join(", ")
+                ))
+            })
+    }
+
+    /// Renders a single value for this locale
+    pub fn format_value(&self, value: &str) -> String {
+        if let Some(captures) = NUMBER.captures(value) {
+            let mut rendered = captures[1].to_string();
+            rendered.push_str(&self.group_digits(&captures[2]));
+            if let Some(fraction) = captures.get(3) {
+                rendered.push(self.decimal_separator);
+                rendered.push_str(fraction.as_str());
+            }
+            return rendered;
+        }
+
+        if let Some(captures) = ISO_DATE
+            .captures(value)
+            .filter(|captures| is_date(&captures[1], &captures[2], &captures[3]))
+        {
+            let (year, month, day) = (&captures[1], &captures[2], &captures[3]);
+            let fields = match self.date_order {
+                DateOrder::YearMonthDay => [year, month, day],
+                DateOrder::DayMonthYear => [day, month, year],
+                DateOrder::MonthDayYear => [month, day, year],
+            };
+            let mut rendered = fields.join(&self.date_separator.to_string());
+            if let Some(time) = captures.get(4) {
+                rendered.push(' ');
+                rendered.push_str(time.as_str());
+            }
+            return rendered;
+        }
+
+        value.to_string()
+    }
+
+    /// Renders a value of `column`, which is left as is unless the column
+    /// is localized
+    pub fn format_cell(&self, column: &str, value: &str) -> String {
+        if self.columns.iter().any(|localized| localized == column) {
+            self.format_value(value)
+        } else {
+            value.to_string()
+        }
+    }
+
+    /// Returns a copy of the table with the cells of the localized columns
+    /// rendered for this locale
+    ///
+    /// Localized columns the table lacks are skipped, since one set of
+    /// columns applies to every table a command writes, e.g. a summary.
+    pub fn localize(&self, table: &Table) -> Result<Table, TableError> {
+        let indices: Vec<usize> = self
+            .columns
+            .iter()
+            .filter_map(|column| table.column_index(column))
+            .collect();
+        table.with_rows(
+            table
+                .rows()
+                .iter()
+                .map(|row| {
+                    let mut row = row.clone();
+                    for index in &indices {
+                        row[*index] = self.format_value(&row[*index]);
+                    }
+                    row
+                })
+                .collect(),
+        )
+    }
+
+    fn group_digits(&self, digits: &str) -> String {
+        let Some(separator) = self.thousands_separator else {
+            return digits.to_string();
+        };
+
+        let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+        for (index, digit) in digits.chars().enumerate() {
+            if index > 0 && (digits.len() - index).is_multiple_of(3) {
+                grouped.push(separator);
+            }
+            grouped.push(digit);
+        }
+        grouped
+    }
+}
+
+/// Whether the fields of an ISO date name a day of the calendar
+fn is_date(year: &str, month: &str, day: &str) -> bool {
+    match (year.parse(), month.parse(), day.parse()) {
+        (Ok(year), Ok(month), Ok(day)) => NaiveDate::from_ymd_opt(year, month, day).is_some(),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_number_grouping() {
+        let german = LocaleProfile::from_tag("de-DE").unwrap();
+        assert_eq!(german.format_value("-1234567.5"), "-1.234.567,5");
+        assert_eq!(german.format_value("123"), "123");
+    }
+
+    #[test]
+    fn test_codes_and_text_stay() {
+        let german = LocaleProfile::from_tag("de-DE").unwrap();
+        assert_eq!(german.format_value("007"), "007");
+        assert_eq!(german.format_value("n/a"), "n/a");
+    }
+
+    #[test]
+    fn test_date_orders() {
+        let german = LocaleProfile::from_tag("de-DE").unwrap();
+        assert_eq!(german.format_value("2024-01-31"), "31.01.2024");
+        let japanese = LocaleProfile::from_tag("ja-JP").unwrap();
+        assert_eq!(japanese.format_value("2024-01-31"), "2024/01/31");
+    }
+
+    #[test]
+    fn test_date_time_keeps_time() {
+        let american = LocaleProfile::from_tag("en-US").unwrap();
+        assert_eq!(
+            american.format_value("2024-01-31T08:00:00"),
+            "01/31/2024 08:00:00"
+        );
+    }
+
+    #[test]
+    fn test_tag_spellings() {
+        let german = LocaleProfile::from_tag("de-DE").unwrap();
+        assert_eq!(LocaleProfile::from_tag("de_de").unwrap(), german);
+        assert_eq!(LocaleProfile::from_tag("DE").unwrap(), german);
+        assert_eq!(
+            LocaleProfile::from_tag("en").unwrap().format_value("1000"),
+            "1,000"
+        );
+    }
+
+    #[test]
+    fn test_c_locale_is_unchanged() {
+        let c = LocaleProfile::from_tag("C").unwrap();
+        assert_eq!(c, LocaleProfile::default());
+        assert_eq!(c.format_value("1234.5"), "1234.5");
+        assert_eq!(c.format_value("2024-01-31"), "2024-01-31");
+    }
+
+    #[test]
+    fn test_unknown_locale() {
+        assert!(matches!(
+            LocaleProfile::from_tag("xx-YY"),
+            Err(TableError::InvalidArgument(message)) if message.contains("de-DE")
+        ));
+    }
+
+    #[test]
+    fn test_invalid_dates_stay() {
+        let german = LocaleProfile::from_tag("de-DE").unwrap();
+        assert_eq!(german.format_value("2024-13-45"), "2024-13-45");
+        assert_eq!(german.format_value("2023-02-29"), "2023-02-29");
+        assert_eq!(german.format_value("2024-02-29"), "29.02.2024");
+    }
+
+    #[test]
+    fn test_localize_named_columns() {
+        let german = LocaleProfile {
+            columns: vec!["amount".to_string(), "missing".to_string()],
+            ..LocaleProfile::from_tag("de-DE").unwrap()
+        };
+        let table = Table::with_header_and_data(
+            vec!["zip".to_string(), "amount".to_string()],
+            vec![vec!["12345".to_string(), "12345.5".to_string()]],
+        )
+        .unwrap();
+        let localized = german.localize(&table).unwrap();
+        assert_eq!(localized.rows()[0], vec!["12345", "12.345,5"]);
+        assert_eq!(german.format_cell("zip", "12345"), "12345");
+        assert_eq!(german.format_cell("amount", "12345"), "12.345");
+    }
+
+    #[test]
+    fn test_localize_without_header() {
+        let german = LocaleProfile {
+            columns: vec!["#1".to_string()],
+            ..LocaleProfile::from_tag("de-DE").unwrap()
+        };
+        let table = Table::with_data(vec![vec!["12345".to_string()]]).unwrap();
+        assert_eq!(german.localize(&table).unwrap().rows()[0], vec!["12345"]);
+    }
+}
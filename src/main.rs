@@ -1,54 +1,141 @@
-use std::{fmt, path::PathBuf};
+use std::{
+    env,
+    ffi::OsString,
+    process::{self, ExitCode},
+};
 
-use clap::{command, Parser};
+use clap::{CommandFactory, FromArgMatches, Parser};
+use commands::{Command, DiffArgs, Outcome};
+use context::{Context, GlobalArgs};
+use error_output::{format_error, ErrorFormat};
+use table::TableError;
 
+pub mod aggregate;
+#[cfg(feature = "arrow")]
+pub mod arrow_stream;
+pub mod assertions;
+pub mod atomic_write;
+pub mod audit_log;
+pub mod bars;
+pub mod binning;
+pub mod canonicalize;
+pub mod cell_value;
+pub mod checksum;
+pub mod column_metadata;
+pub mod column_order;
+pub mod commands;
+pub mod context;
+pub mod diff;
+pub mod dry_run;
+pub mod dupes;
+pub mod error_output;
+pub mod explain;
+pub mod filter;
+pub mod geo;
+pub mod header_map;
+pub mod highlight;
+pub mod history;
+pub mod input;
+pub mod json_schema;
+pub mod load;
+pub mod locale;
+pub mod man_page;
+pub mod pager;
+pub mod profile;
+pub mod repair;
+pub mod reshape;
+pub mod share;
+pub mod sort;
+pub mod split;
+pub mod stats;
 pub mod table;
 pub mod table_parser;
+pub mod table_writer;
+pub mod timezone;
+pub mod timings;
+pub mod transform;
+pub mod uuid_column;
+pub mod watch;
 
 #[derive(Parser, Debug)]
-#[command(version, about, long_about = None)]
-struct Args {
-    /// First table file path
-    #[arg(index = 1, help = "Path to the first table file")]
-    table1: Option<PathBuf>,
-
-    /// Second table file path
-    #[arg(index = 2, help = "Path to the second table file")]
-    table2: Option<PathBuf>,
-
-    /// Output file
-    #[arg(short, long, help = "Write output to file instead of stdout")]
-    output: Option<PathBuf>,
-}
+#[command(
+    version,
+    about,
+    long_about = None,
+    args_conflicts_with_subcommands = true,
+    after_help = "Global options can also be set with the TABLES_* environment variable shown next to each, e.g. TABLES_DELIMITER=';'. Options given on the command line take precedence, and --profile fills in options given neither way.\n\nExit status: 0 on success, 1 when diff finds differences or assert finds violations, 2 on errors, 130 when interrupted with Ctrl-C."
+)]
+struct Cli {
+    #[command(flatten)]
+    global: GlobalArgs,
 
-impl fmt::Display for Args {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        writeln!(f, "Arguments:")?;
-        writeln!(
-            f,
-            "{}",
-            Self::format_path(&self.table1, "Table 1", "default")
-        )?;
-        writeln!(
-            f,
-            "{}",
-            Self::format_path(&self.table2, "Table 2", "default")
-        )?;
-        writeln!(f, "{}", Self::format_path(&self.output, "Output", "stdout"))?;
-        Ok(())
-    }
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    // `tables a.csv b.csv` is shorthand for `tables diff a.csv b.csv`
+    #[command(flatten)]
+    diff: DiffArgs,
 }
 
-impl Args {
-    fn format_path(path: &Option<PathBuf>, name: &str, default: &str) -> String {
-        match path {
-            Some(path) => format!("  {}: {}", name, path.display()),
-            None => format!("  {}: {}", name, default),
+// Exits 0 on success, 1 when diff finds differences or assert finds violations,
+// 2 on errors, and 130 when interrupted.
+fn main() -> ExitCode {
+    // Without a handler, Ctrl-C during a write leaves its temp file behind.
+    let _ = ctrlc::set_handler(|| {
+        atomic_write::remove_staged();
+        process::exit(130);
+    });
+
+    // A profile may supply required options, so it is read from a lenient
+    // parse before the real one.
+    let mut arguments: Vec<OsString> = env::args_os().collect();
+    if let Ok(matches) = Cli::command()
+        .ignore_errors(true)
+        .try_get_matches_from(&arguments)
+    {
+        if let Err(error) = profile::apply(Cli::command(), &mut arguments, &matches) {
+            let format = matches
+                .get_one::<ErrorFormat>("error_format")
+                .copied()
+                .unwrap_or_default();
+            eprintln!("{}", format_error(format, None, &*error));
+            return ExitCode::from(2);
         }
     }
-}
+    let matches = Cli::command().get_matches_from(&arguments);
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|error| error.exit());
+    let command = cli.command.unwrap_or(Command::Diff(cli.diff));
 
-fn main() {
-    let args = Args::parse();
-    println!("Debug {}!", args);
+    let mut context = Context::new(cli.global);
+    context.start_audit(matches.subcommand_name().unwrap_or("diff"));
+    if context.args.watch {
+        let inputs = command.inputs();
+        if inputs.is_empty() || inputs.iter().any(|input| input::is_stdin(input)) {
+            let error = TableError::InvalidArgument(
+                "--watch needs input files, not standard input".to_string(),
+            );
+            context.fail(None, &error);
+        }
+        let Err(error) = watch::watch(&inputs);
+        context.fail(None, &error);
+    }
+    if context.args.in_place {
+        match command.in_place_input() {
+            Some(input) => context.edit_in_place(input),
+            None => {
+                let error = TableError::InvalidArgument(
+                    "--in-place only works with commands that rewrite one table".to_string(),
+                );
+                context.fail(None, &error);
+            }
+        }
+    }
+    let outcome = if context.args.explain {
+        context.explain(&command.inputs());
+        Outcome::Success
+    } else {
+        command.run(&mut context)
+    };
+    context.finish();
+    outcome.into()
 }
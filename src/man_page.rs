@@ -0,0 +1,66 @@
+use std::io;
+
+use clap_mangen::Man;
+
+/// Renders the man page of a command and of every visible subcommand
+///
+/// Subcommand pages are named like `compare_tables-diff.1`, which is where the
+/// SUBCOMMANDS section of the main page points to.
+///
+/// # Arguments
+/// * `command` - The command line definition
+///
+/// # Returns
+/// * `Vec<(String, String)>` - File name and roff source of each page, main page first
+pub fn render_pages(command: clap::Command) -> io::Result<Vec<(String, String)>> {
+    let mut command = command.disable_help_subcommand(true);
+    command.build();
+
+    let mut pages = Vec::new();
+    render_into(command, &mut pages)?;
+    Ok(pages)
+}
+
+fn render_into(command: clap::Command, pages: &mut Vec<(String, String)>) -> io::Result<()> {
+    let subcommands: Vec<clap::Command> = command
+        .get_subcommands()
+        .filter(|subcommand| !subcommand.is_hide_set())
+        .cloned()
+        .collect();
+
+    let man = Man::new(command);
+    let mut page = Vec::new();
+    man.render(&mut page)?;
+    pages.push((
+        man.get_filename(),
+        String::from_utf8_lossy(&page).into_owned(),
+    ));
+
+    for subcommand in subcommands {
+        render_into(subcommand, pages)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::CommandFactory;
+
+    use super::*;
+    use crate::Cli;
+
+    #[test]
+    fn test_pages_cover_subcommands() {
+        let pages = render_pages(Cli::command()).unwrap();
+        let name = Cli::command().get_name().to_string();
+
+        assert_eq!(pages[0].0, format!("{}.1", name));
+        assert!(pages[0].1.contains(&format!("{}\\-diff(1)", name)));
+        let (_, tz) = pages
+            .iter()
+            .find(|(file, _)| *file == format!("{}-tz.1", name))
+            .unwrap();
+        assert!(tz.contains("\\-\\-to\\-tz"));
+        assert!(!pages.iter().any(|(file, _)| file.ends_with("-man.1")));
+    }
+}
@@ -0,0 +1,101 @@
+use std::{
+    env,
+    io::{self, IsTerminal, Write},
+    process::{Command, Stdio},
+};
+
+use terminal_size::{terminal_size, Height, Width};
+
+/// Pager used when `$PAGER` is unset; `-R` keeps ANSI colors
+const DEFAULT_PAGER: &str = "less -R";
+
+/// Shows output through `$PAGER` when stdout is a terminal too small for it
+///
+/// # Arguments
+/// * `contents` - Output that would go to stdout
+///
+/// # Returns
+/// * `bool` - Whether the pager showed the output; when not, e.g. because
+///   it fits or no pager could be started, the caller prints it
+pub fn page(contents: &[u8]) -> bool {
+    if !io::stdout().is_terminal() {
+        return false;
+    }
+    let Some((Width(width), Height(height))) = terminal_size() else {
+        return false;
+    };
+    let pager = env::var("PAGER").ok();
+    let Some(command) = pager_command(pager.as_deref()) else {
+        return false;
+    };
+    if !exceeds_screen(contents, width.into(), height.into()) {
+        return false;
+    }
+
+    let Ok(mut child) = Command::new(command[0])
+        .args(&command[1..])
+        .stdin(Stdio::piped())
+        .spawn()
+    else {
+        return false;
+    };
+    if let Some(mut stdin) = child.stdin.take() {
+        // Quitting the pager early closes the pipe, which is not an error.
+        let _ = stdin.write_all(contents);
+    }
+    let _ = child.wait();
+    true
+}
+
+/// Program and arguments of the pager, `None` when paging is turned off
+/// with an empty `$PAGER` or `PAGER=cat`
+fn pager_command(pager: Option<&str>) -> Option<Vec<&str>> {
+    let command: Vec<&str> = pager.unwrap_or(DEFAULT_PAGER).split_whitespace().collect();
+    match command.first() {
+        None | Some(&"cat") => None,
+        Some(_) => Some(command),
+    }
+}
+
+/// Checks whether text needs more lines than the terminal has, counting
+/// lines wider than the terminal as wrapped
+fn exceeds_screen(contents: &[u8], width: usize, height: usize) -> bool {
+    let width = width.max(1);
+    let mut lines = 0;
+    for line in String::from_utf8_lossy(contents).lines() {
+        lines += line.chars().count().div_ceil(width).max(1);
+        if lines >= height {
+            return true;
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pager_command() {
+        assert_eq!(pager_command(None), Some(vec!["less", "-R"]));
+        assert_eq!(pager_command(Some("most -s")), Some(vec!["most", "-s"]));
+    }
+
+    #[test]
+    fn test_paging_disabled() {
+        assert_eq!(pager_command(Some("")), None);
+        assert_eq!(pager_command(Some("cat")), None);
+    }
+
+    #[test]
+    fn test_output_fitting_the_screen() {
+        assert!(!exceeds_screen(b"a\nb\n", 80, 3));
+        assert!(exceeds_screen(b"a\nb\nc\n", 80, 3));
+    }
+
+    #[test]
+    fn test_wrapped_lines_count() {
+        assert!(!exceeds_screen("x".repeat(80).as_bytes(), 80, 3));
+        assert!(exceeds_screen("x".repeat(200).as_bytes(), 80, 3));
+    }
+}
@@ -0,0 +1,256 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    env,
+    error::Error,
+    ffi::OsString,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use clap::{parser::ValueSource, ArgAction, ArgMatches};
+use serde::Deserialize;
+use toml::Value;
+
+use crate::table::TableError;
+
+/// The config file, holding named option sets:
+///
+/// ```toml
+/// [profile.finance]
+/// delimiter = ";"
+/// skip-bad-rows = true
+/// to = "ascii"
+/// ```
+///
+/// Keys are long option names of any command, with `-` or `_`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    #[serde(default)]
+    pub profile: BTreeMap<String, toml::Table>,
+}
+
+impl Config {
+    /// Reads a config file
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let contents = fs::read_to_string(path)?;
+        toml::from_str(&contents)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error.message()))
+    }
+
+    /// Where the config file is read from without `--config`:
+    /// `$XDG_CONFIG_HOME/tables/config.toml`, else `~/.config/tables/config.toml`
+    pub fn default_path() -> Option<PathBuf> {
+        env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".config")))
+            .map(|dir| dir.join("tables").join("config.toml"))
+    }
+}
+
+/// Adds the options of the `--profile` named on the command line to it
+///
+/// Options given on the command line or through their environment
+/// variable keep their value. Profile options the running command doesn't
+/// have are skipped, so one profile can serve several commands.
+///
+/// # Arguments
+/// * `command` - The command line definition
+/// * `arguments` - The command line, extended in place
+/// * `matches` - The command line as parsed without the profile
+pub fn apply(
+    command: clap::Command,
+    arguments: &mut Vec<OsString>,
+    matches: &ArgMatches,
+) -> Result<(), Box<dyn Error>> {
+    let Some(name) = matches.get_one::<String>("profile") else {
+        return Ok(());
+    };
+    let path = matches
+        .get_one::<PathBuf>("config")
+        .cloned()
+        .or_else(Config::default_path)
+        .ok_or_else(|| {
+            TableError::InvalidArgument("no config file for --profile, pass --config".to_string())
+        })?;
+    let config = Config::load(&path)
+        .map_err(|error| io::Error::new(error.kind(), format!("{}: {}", path.display(), error)))?;
+    let profile = config.profile.get(name).ok_or_else(|| {
+        TableError::InvalidArgument(format!("{}: no [profile.{}] section", path.display(), name))
+    })?;
+
+    let options = profile_arguments(command, matches, profile).map_err(|error| {
+        TableError::InvalidArgument(format!("{}: [profile.{}]: {}", path.display(), name, error))
+    })?;
+    // Options after `--` would be read as positional values.
+    let end = arguments
+        .iter()
+        .position(|argument| argument == "--")
+        .unwrap_or(arguments.len());
+    arguments.splice(end..end, options);
+    Ok(())
+}
+
+/// Turns a profile into command line options for the running command
+fn profile_arguments(
+    mut command: clap::Command,
+    matches: &ArgMatches,
+    profile: &toml::Table,
+) -> Result<Vec<OsString>, TableError> {
+    command.build();
+    let known: HashSet<&str> = command
+        .get_arguments()
+        .chain(
+            command
+                .get_subcommands()
+                .flat_map(|sub| sub.get_arguments()),
+        )
+        .filter_map(|arg| arg.get_long())
+        .collect();
+    let (running, matches) = match matches.subcommand() {
+        Some((name, sub_matches)) => (
+            command.find_subcommand(name).unwrap_or(&command),
+            sub_matches,
+        ),
+        None => (&command, matches),
+    };
+
+    let mut options = Vec::new();
+    for (key, value) in profile {
+        let long = key.replace('_', "-");
+        if long == "profile" || long == "config" {
+            return Err(TableError::InvalidArgument(format!(
+                "--{} can't be set in a profile",
+                long
+            )));
+        }
+        if !known.contains(long.as_str()) {
+            return Err(TableError::InvalidArgument(format!(
+                "unknown option '{}'",
+                key
+            )));
+        }
+        let Some(arg) = running
+            .get_arguments()
+            .find(|arg| arg.get_long() == Some(&long))
+        else {
+            continue;
+        };
+        if matches!(
+            matches.value_source(arg.get_id().as_str()),
+            Some(ValueSource::CommandLine | ValueSource::EnvVariable)
+        ) {
+            continue;
+        }
+
+        let values = match value {
+            Value::Array(values) => values.iter().collect(),
+            value => vec![value],
+        };
+        for value in values {
+            let value = match value {
+                Value::Boolean(false) => continue,
+                Value::Boolean(true) => None,
+                Value::Integer(count) if matches!(arg.get_action(), ArgAction::Count) => {
+                    options.extend((0..*count).map(|_| OsString::from(format!("--{}", long))));
+                    continue;
+                }
+                Value::String(value) => Some(value.clone()),
+                Value::Integer(value) => Some(value.to_string()),
+                Value::Float(value) => Some(value.to_string()),
+                _ => {
+                    return Err(TableError::InvalidArgument(format!(
+                        "'{}' must be a string, number, boolean or array of them",
+                        key
+                    )))
+                }
+            };
+            options.push(OsString::from(match value {
+                Some(value) => format!("--{}={}", long, value),
+                None => format!("--{}", long),
+            }));
+        }
+    }
+    Ok(options)
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::CommandFactory;
+
+    use super::*;
+    use crate::Cli;
+
+    fn arguments(command_line: &[&str], profile: &str) -> Result<Vec<OsString>, TableError> {
+        let profile: toml::Table = toml::from_str(profile).unwrap();
+        let matches = Cli::command().get_matches_from(command_line);
+        profile_arguments(Cli::command(), &matches, &profile)
+    }
+
+    #[test]
+    fn test_profile_values_become_options() {
+        assert_eq!(
+            arguments(
+                &["tables", "convert", "a.csv"],
+                "delimiter = \";\"\nskip_bad_rows = true\ntimings = false\nverbose = 2"
+            )
+            .unwrap(),
+            ["--delimiter=;", "--skip-bad-rows", "--verbose", "--verbose"]
+        );
+    }
+
+    #[test]
+    fn test_arrays_repeat_options() {
+        assert_eq!(
+            arguments(
+                &["tables", "diff", "a.csv", "b.csv"],
+                "key = [\"region\", \"id\"]"
+            )
+            .unwrap(),
+            ["--key=region", "--key=id"]
+        );
+    }
+
+    #[test]
+    fn test_command_line_wins() {
+        assert!(arguments(
+            &["tables", "sort", "a.csv", "--by", "id", "--to=csv"],
+            "by = [\"amount\"]\nto = \"ascii\""
+        )
+        .unwrap()
+        .is_empty());
+    }
+
+    #[test]
+    fn test_options_of_other_commands_skipped() {
+        assert!(
+            arguments(&["tables", "convert", "a.csv"], "by = [\"amount\"]")
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_unknown_option() {
+        assert!(matches!(
+            arguments(&["tables", "convert", "a.csv"], "delimeter = ';'"),
+            Err(TableError::InvalidArgument(message)) if message.contains("delimeter")
+        ));
+    }
+
+    #[test]
+    fn test_profile_and_config_not_settable() {
+        assert!(arguments(&["tables", "convert", "a.csv"], "config = 'other.toml'").is_err());
+        assert!(arguments(&["tables", "convert", "a.csv"], "profile = 'other'").is_err());
+    }
+
+    #[test]
+    fn test_unsupported_values() {
+        assert!(arguments(
+            &["tables", "convert", "a.csv"],
+            "delimiter = { char = ';' }"
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,332 @@
+use std::fmt;
+
+use crate::table::{Table, TableError};
+use crate::table_parser::{split_delimited, split_raw_fields, unquote_field};
+
+/// Lines a wrapped row may be spread over before it is given up on
+const MAX_WRAPPED_LINES: usize = 5;
+
+/// A fix applied to the input, tied to the line it starts on (1-based)
+#[derive(Debug, PartialEq)]
+pub struct RepairChange {
+    pub line: usize,
+    pub kind: RepairKind,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum RepairKind {
+    /// Quotes inside a quoted field were doubled
+    EscapedQuotes,
+    /// A row broken over several lines was joined back together
+    JoinedLines(usize),
+    /// Extra fields were merged back into a text column
+    MergedFields { column: usize, extra: usize },
+}
+
+impl fmt::Display for RepairChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            RepairKind::EscapedQuotes => {
+                write!(f, "line {}: escaped stray quotes", self.line)
+            }
+            RepairKind::JoinedLines(count) => {
+                write!(f, "line {}: joined {} wrapped lines", self.line, count)
+            }
+            RepairKind::MergedFields { column, extra } => write!(
+                f,
+                "line {}: merged {} stray delimiters into column {}",
+                self.line,
+                extra,
+                column + 1
+            ),
+        }
+    }
+}
+
+/// What `repair_delimited` changed and what it couldn't fix
+#[derive(Debug, Default)]
+pub struct RepairReport {
+    pub changes: Vec<RepairChange>,
+    /// Lines (1-based) dropped because no heuristic produced a valid row
+    pub unfixed_lines: Vec<usize>,
+}
+
+/// Repairs structurally broken delimited text
+///
+/// The first line is taken as the header and fixes its field count.
+/// Stray quotes inside quoted fields are escaped, rows wrapped over
+/// several lines are joined with a space, and rows with surplus fields
+/// have them merged into the column that most often holds text. Lines
+/// no heuristic can fix are left out of the table and listed in the
+/// report.
+///
+/// # Arguments
+/// * `data` - Raw delimited text
+/// * `delimiter` - Field delimiter
+/// * `quote` - Quote character used around fields
+///
+/// # Returns
+/// * `(Table, RepairReport)` - Repaired table and a log of changes
+pub fn repair_delimited(
+    data: &str,
+    delimiter: char,
+    quote: char,
+) -> Result<(Table, RepairReport), TableError> {
+    let mut report = RepairReport::default();
+    let lines: Vec<String> = data
+        .lines()
+        .enumerate()
+        .map(
+            |(index, line)| match escape_stray_quotes(line, delimiter, quote) {
+                Some(fixed) => {
+                    report.changes.push(RepairChange {
+                        line: index + 1,
+                        kind: RepairKind::EscapedQuotes,
+                    });
+                    fixed
+                }
+                None => line.to_string(),
+            },
+        )
+        .collect();
+
+    let split = |line: &str| split_delimited(line, delimiter, Some(quote));
+
+    let Some(header_line) = lines.first() else {
+        return Err(TableError::EmptyHeader);
+    };
+    let header = split(header_line);
+    let expected = header.len();
+    let text_column = text_column(lines.iter().skip(1).map(|line| split(line)), expected);
+
+    let mut rows = Vec::new();
+    let mut index = 1;
+    while index < lines.len() {
+        let line_number = index + 1;
+        let mut fields = split(&lines[index]);
+
+        if fields.len() < expected {
+            let mut joined = lines[index].clone();
+            let mut consumed = 1;
+            while fields.len() < expected
+                && consumed < MAX_WRAPPED_LINES
+                && index + consumed < lines.len()
+            {
+                joined.push(' ');
+                joined.push_str(lines[index + consumed].trim_start());
+                consumed += 1;
+                fields = split(&joined);
+            }
+
+            if fields.len() == expected {
+                report.changes.push(RepairChange {
+                    line: line_number,
+                    kind: RepairKind::JoinedLines(consumed),
+                });
+                rows.push(fields);
+                index += consumed;
+            } else {
+                report.unfixed_lines.push(line_number);
+                index += 1;
+            }
+            continue;
+        }
+
+        if fields.len() > expected {
+            let extra = fields.len() - expected;
+            match text_column {
+                Some(column) => {
+                    // Merge the raw fields so spacing around the stray
+                    // delimiters survives.
+                    let mut raw_fields = split_raw_fields(&lines[index], delimiter, Some(quote));
+                    let merged = raw_fields[column..=column + extra].join(&delimiter.to_string());
+                    raw_fields.splice(column..=column + extra, [merged.as_str()]);
+                    fields = raw_fields
+                        .into_iter()
+                        .map(|field| unquote_field(field, Some(quote)))
+                        .collect();
+                    report.changes.push(RepairChange {
+                        line: line_number,
+                        kind: RepairKind::MergedFields { column, extra },
+                    });
+                    rows.push(fields);
+                }
+                None => report.unfixed_lines.push(line_number),
+            }
+            index += 1;
+            continue;
+        }
+
+        rows.push(fields);
+        index += 1;
+    }
+
+    Ok((Table::with_header_and_data(header, rows)?, report))
+}
+
+/// Doubles quotes that appear inside a quoted field without closing it,
+/// returning `None` if the line needs no change.
+fn escape_stray_quotes(line: &str, delimiter: char, quote: char) -> Option<String> {
+    let mut fixed = String::with_capacity(line.len());
+    let mut changed = false;
+    let mut in_quotes = false;
+    let mut at_field_start = true;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        fixed.push(c);
+
+        if in_quotes {
+            if c == quote {
+                let closes = chars
+                    .clone()
+                    .find(|next| *next != ' ')
+                    .is_none_or(|next| next == delimiter);
+                if chars.peek() == Some(&quote) {
+                    fixed.push(chars.next().unwrap());
+                } else if closes {
+                    in_quotes = false;
+                } else {
+                    fixed.push(quote);
+                    changed = true;
+                }
+            }
+        } else if c == delimiter {
+            at_field_start = true;
+            continue;
+        } else if at_field_start && c == quote {
+            in_quotes = true;
+        }
+
+        if !c.is_whitespace() {
+            at_field_start = false;
+        }
+    }
+
+    changed.then_some(fixed)
+}
+
+/// Picks the column that most often holds non-numeric text among rows with
+/// the expected field count; surplus fields are merged into it.
+fn text_column(rows: impl Iterator<Item = Vec<String>>, expected: usize) -> Option<usize> {
+    let mut text_counts = vec![0usize; expected];
+    for row in rows.filter(|row| row.len() == expected) {
+        for (count, value) in text_counts.iter_mut().zip(&row) {
+            if !value.is_empty() && value.parse::<f64>().is_err() {
+                *count += 1;
+            }
+        }
+    }
+
+    text_counts
+        .iter()
+        .enumerate()
+        .filter(|(_, count)| **count > 0)
+        .max_by_key(|(index, count)| (**count, std::cmp::Reverse(*index)))
+        .map(|(index, _)| index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn repair(data: &str) -> (Table, RepairReport) {
+        repair_delimited(data, ',', '"').unwrap()
+    }
+
+    #[test]
+    fn test_intact_rows_stay() {
+        let (table, report) = repair("id,comment\n1,fine\n2,\"a, b\"\n");
+        assert_eq!(table.get_value(1, "comment").unwrap(), "a, b");
+        assert!(report.changes.is_empty());
+        assert!(report.unfixed_lines.is_empty());
+    }
+
+    #[test]
+    fn test_escapes_stray_quotes() {
+        let (table, report) = repair("id,comment\n2,\"he said \"hi\" twice\"\n");
+        assert_eq!(
+            table.get_value(0, "comment").unwrap(),
+            "he said \"hi\" twice"
+        );
+        assert_eq!(
+            report.changes,
+            vec![RepairChange {
+                line: 2,
+                kind: RepairKind::EscapedQuotes
+            }]
+        );
+    }
+
+    #[test]
+    fn test_joins_wrapped_lines() {
+        let (table, report) = repair("id,comment,amount\n3,wrapped\n  text,30\n");
+        assert_eq!(table.get_value(0, "comment").unwrap(), "wrapped text");
+        assert_eq!(
+            report.changes,
+            vec![RepairChange {
+                line: 2,
+                kind: RepairKind::JoinedLines(2)
+            }]
+        );
+    }
+
+    #[test]
+    fn test_merges_stray_delimiters_into_text_column() {
+        let (table, report) = repair("id,comment,amount\n1,fine,10\n4,one, two, three,40\n");
+        assert_eq!(table.get_value(1, "comment").unwrap(), "one, two, three");
+        assert_eq!(
+            report.changes,
+            vec![RepairChange {
+                line: 3,
+                kind: RepairKind::MergedFields {
+                    column: 1,
+                    extra: 2
+                }
+            }]
+        );
+    }
+
+    #[test]
+    fn test_reports_unfixable_lines() {
+        let (table, report) = repair("id,comment,amount\n1,fine,10\n5\n");
+        assert_eq!(table.row_count(), 1);
+        assert_eq!(report.unfixed_lines, vec![3]);
+    }
+
+    #[test]
+    fn test_line_numbers_count_joined_lines() {
+        let (_, report) = repair("id,comment,amount\n3,wrapped\ntext,30\n5\n");
+        assert_eq!(report.unfixed_lines, vec![4]);
+    }
+
+    #[test]
+    fn test_surplus_fields_without_text_column() {
+        let (table, report) = repair("a,b\n1,2\n1,2,3\n");
+        assert_eq!(table.row_count(), 1);
+        assert_eq!(report.unfixed_lines, vec![3]);
+    }
+
+    #[test]
+    fn test_empty_input() {
+        assert!(matches!(
+            repair_delimited("", ',', '"'),
+            Err(TableError::EmptyHeader)
+        ));
+    }
+
+    #[test]
+    fn test_change_descriptions() {
+        let change = RepairChange {
+            line: 6,
+            kind: RepairKind::MergedFields {
+                column: 1,
+                extra: 2,
+            },
+        };
+        assert_eq!(
+            change.to_string(),
+            "line 6: merged 2 stray delimiters into column 2"
+        );
+    }
+}
@@ -0,0 +1,257 @@
+use std::collections::HashMap;
+
+use crate::table::{Table, TableError};
+
+/// Moves columns into rows: each row becomes one row per stacked column,
+/// holding the index cells, the column name and its cell
+///
+/// Empty cells are kept, so `unstack` restores the table. Columns of a
+/// table without a header are stacked as `#1`, `#2`, and so on.
+///
+/// # Arguments
+/// * `table` - Table to reshape
+/// * `index` - Columns identifying a row, kept as they are
+/// * `columns` - Columns to stack, every other column when empty
+/// * `into` - Name of the column receiving the stacked column names
+/// * `values` - Name of the column receiving their cells
+///
+/// # Returns
+/// * `Table` - Index columns, then `into` and `values`
+pub fn stack(
+    table: &Table,
+    index: &[String],
+    columns: &[String],
+    into: &str,
+    values: &str,
+) -> Result<Table, TableError> {
+    let index_positions = column_indices(table, index)?;
+    let header = table.header();
+    let stacked_positions = if columns.is_empty() {
+        (0..table.column_count())
+            .filter(|position| !index_positions.contains(position))
+            .collect()
+    } else {
+        column_indices(table, columns)?
+    };
+
+    let mut stacked_header = index.to_vec();
+    stacked_header.push(into.to_string());
+    stacked_header.push(values.to_string());
+    let mut rows = Vec::with_capacity(table.row_count() * stacked_positions.len());
+    for row in table.rows() {
+        for position in &stacked_positions {
+            let mut stacked: Vec<String> = index_positions
+                .iter()
+                .map(|index| row[*index].clone())
+                .collect();
+            stacked.push(
+                header
+                    .get(*position)
+                    .map_or_else(|| format!("#{}", position + 1), |name| name.to_string()),
+            );
+            stacked.push(row[*position].clone());
+            rows.push(stacked);
+        }
+    }
+    Table::with_header_and_data(stacked_header, rows)
+}
+
+/// Moves a column out of the row key: its values become columns
+///
+/// Rows sharing the index cells merge into one, in order of first
+/// appearance; new columns are ordered the same way. With one value
+/// column the new columns are named after the level values, with several
+/// as `<value column>_<level value>`. Missing combinations are empty.
+///
+/// # Arguments
+/// * `table` - Table to reshape
+/// * `index` - Columns identifying an output row
+/// * `level` - Column whose values become column names
+///
+/// # Returns
+/// * `Table` - Index columns, then one column per value column and level
+pub fn unstack(table: &Table, index: &[String], level: &str) -> Result<Table, TableError> {
+    let index_positions = column_indices(table, index)?;
+    let level_position = table
+        .column_index(level)
+        .ok_or_else(|| TableError::MissingColumn(level.to_string()))?;
+    if index_positions.contains(&level_position) {
+        return Err(TableError::InvalidArgument(format!(
+            "'{}' can't be both the level and part of the index",
+            level
+        )));
+    }
+    let header = table.header();
+    let value_positions: Vec<usize> = (0..table.column_count())
+        .filter(|position| *position != level_position && !index_positions.contains(position))
+        .collect();
+
+    let mut levels: Vec<&str> = Vec::new();
+    let mut keys: Vec<Vec<&str>> = Vec::new();
+    let mut cells: HashMap<(usize, usize), &Vec<String>> = HashMap::new();
+    let mut key_positions: HashMap<Vec<&str>, usize> = HashMap::new();
+    for row in table.rows() {
+        let key: Vec<&str> = index_positions
+            .iter()
+            .map(|index| row[*index].as_str())
+            .collect();
+        let key_position = *key_positions.entry(key.clone()).or_insert_with(|| {
+            keys.push(key);
+            keys.len() - 1
+        });
+        let level_value = row[level_position].as_str();
+        let level_index = match levels.iter().position(|level| *level == level_value) {
+            Some(level_index) => level_index,
+            None => {
+                levels.push(level_value);
+                levels.len() - 1
+            }
+        };
+        if cells.insert((key_position, level_index), row).is_some() {
+            return Err(TableError::InvalidArgument(format!(
+                "several rows have the index {:?} and {} '{}'",
+                keys[key_position], level, level_value
+            )));
+        }
+    }
+
+    let mut unstacked_header = index.to_vec();
+    for value_position in &value_positions {
+        for level_value in &levels {
+            unstacked_header.push(if value_positions.len() == 1 {
+                level_value.to_string()
+            } else {
+                format!("{}_{}", header[*value_position], level_value)
+            });
+        }
+    }
+    let rows = keys
+        .iter()
+        .enumerate()
+        .map(|(key_position, key)| {
+            let mut unstacked: Vec<String> = key.iter().map(|cell| cell.to_string()).collect();
+            for value_position in &value_positions {
+                for level_index in 0..levels.len() {
+                    unstacked.push(
+                        cells
+                            .get(&(key_position, level_index))
+                            .map_or_else(String::new, |row| row[*value_position].clone()),
+                    );
+                }
+            }
+            unstacked
+        })
+        .collect();
+    Table::with_header_and_data(unstacked_header, rows)
+}
+
+fn column_indices(table: &Table, columns: &[String]) -> Result<Vec<usize>, TableError> {
+    columns
+        .iter()
+        .map(|column| {
+            table
+                .column_index(column)
+                .ok_or_else(|| TableError::MissingColumn(column.clone()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(cells: &[&str]) -> Vec<String> {
+        cells.iter().map(|cell| cell.to_string()).collect()
+    }
+
+    fn wide() -> Table {
+        Table::with_header_and_data(
+            strings(&["region", "2023", "2024"]),
+            vec![
+                strings(&["north", "10", "12"]),
+                strings(&["south", "7", ""]),
+            ],
+        )
+        .unwrap()
+    }
+
+    fn index() -> Vec<String> {
+        strings(&["region"])
+    }
+
+    #[test]
+    fn test_stack() {
+        let long = stack(&wide(), &index(), &[], "year", "sales").unwrap();
+        assert_eq!(long.header(), vec!["region", "year", "sales"]);
+        assert_eq!(long.rows()[1], strings(&["north", "2024", "12"]));
+        assert_eq!(long.rows()[3], strings(&["south", "2024", ""]));
+        assert_eq!(long.row_count(), 4);
+    }
+
+    #[test]
+    fn test_stack_selected_columns() {
+        let long = stack(&wide(), &index(), &strings(&["2024"]), "year", "sales").unwrap();
+        assert_eq!(long.row_count(), 2);
+        assert_eq!(long.rows()[0], strings(&["north", "2024", "12"]));
+    }
+
+    #[test]
+    fn test_unstack_restores_stack() {
+        let long = stack(&wide(), &index(), &[], "year", "sales").unwrap();
+        let restored = unstack(&long, &index(), "year").unwrap();
+        assert_eq!(restored.header(), wide().header());
+        assert_eq!(restored.rows(), wide().rows());
+    }
+
+    #[test]
+    fn test_unstack_several_value_columns() {
+        let long = Table::with_header_and_data(
+            strings(&["region", "year", "sales", "returns"]),
+            vec![strings(&["north", "2024", "12", "1"])],
+        )
+        .unwrap();
+        let unstacked = unstack(&long, &index(), "year").unwrap();
+        assert_eq!(
+            unstacked.header(),
+            vec!["region", "sales_2024", "returns_2024"]
+        );
+    }
+
+    #[test]
+    fn test_unstack_duplicate_rows() {
+        let long = stack(&wide(), &index(), &[], "year", "sales").unwrap();
+        let duplicated = long.with_rows([long.rows(), &long.rows()[..1]].concat());
+        assert!(matches!(
+            unstack(&duplicated.unwrap(), &index(), "year"),
+            Err(TableError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn test_unstack_level_in_index() {
+        assert!(matches!(
+            unstack(&wide(), &index(), "region"),
+            Err(TableError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn test_reshape_unknown_columns() {
+        assert!(matches!(
+            stack(&wide(), &strings(&["country"]), &[], "year", "sales"),
+            Err(TableError::MissingColumn(column)) if column == "country"
+        ));
+        assert!(matches!(
+            unstack(&wide(), &index(), "month"),
+            Err(TableError::MissingColumn(column)) if column == "month"
+        ));
+    }
+
+    #[test]
+    fn test_stack_without_header() {
+        let table = Table::with_data(vec![strings(&["10", "12"])]).unwrap();
+        let long = stack(&table, &[], &[], "column", "value").unwrap();
+        assert_eq!(long.header(), vec!["column", "value"]);
+        assert_eq!(long.rows()[1], strings(&["#2", "12"]));
+    }
+}
@@ -0,0 +1,149 @@
+use std::collections::HashMap;
+
+use crate::cell_value::CellValue;
+use crate::table::{Table, TableError};
+
+/// Appends each row's percentage of the column total
+///
+/// With partition columns the total is taken over the rows sharing their
+/// values. Empty cells count as nothing and get an empty share, as do
+/// rows of a partition totalling zero.
+///
+/// # Arguments
+/// * `table` - Table to extend
+/// * `column` - Numeric column to take shares of
+/// * `partition_by` - Columns whose values group rows into separate totals
+/// * `into` - Name of the new column
+/// * `decimals` - Digits after the decimal point of the percentages
+///
+/// # Returns
+/// * `Table` - The table with the percentage column appended
+pub fn add_share(
+    table: &Table,
+    column: &str,
+    partition_by: &[String],
+    into: &str,
+    decimals: usize,
+) -> Result<Table, TableError> {
+    let column_index = table
+        .column_index(column)
+        .ok_or_else(|| TableError::MissingColumn(column.to_string()))?;
+    let partition_indices = partition_by
+        .iter()
+        .map(|column| {
+            table
+                .column_index(column)
+                .ok_or_else(|| TableError::MissingColumn(column.clone()))
+        })
+        .collect::<Result<Vec<usize>, TableError>>()?;
+
+    let values = table
+        .rows()
+        .iter()
+        .enumerate()
+        .map(
+            |(row_index, row)| match CellValue::parse(&row[column_index]) {
+                CellValue::Empty => Ok(None),
+                CellValue::Number(number) => Ok(Some(number)),
+                _ => Err(TableError::InvalidCell {
+                    row_index,
+                    column: column.to_string(),
+                }),
+            },
+        )
+        .collect::<Result<Vec<Option<f64>>, TableError>>()?;
+    let partition = |row: &[String]| -> Vec<String> {
+        partition_indices
+            .iter()
+            .map(|index| row[*index].clone())
+            .collect()
+    };
+    let mut totals: HashMap<Vec<String>, f64> = HashMap::new();
+    for (row, value) in table.rows().iter().zip(&values) {
+        *totals.entry(partition(row)).or_default() += value.unwrap_or(0.0);
+    }
+
+    let shares = table
+        .rows()
+        .iter()
+        .zip(&values)
+        .map(|(row, value)| {
+            let total = totals[&partition(row)];
+            value
+                .filter(|_| total != 0.0)
+                .map_or(String::new(), |value| {
+                    format!("{:.*}", decimals, value / total * 100.0)
+                })
+        })
+        .collect();
+    table.append_column(into, shares)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sales(rows: &[[&str; 2]]) -> Table {
+        Table::with_header_and_data(
+            vec!["region".to_string(), "sales".to_string()],
+            rows.iter()
+                .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+                .collect(),
+        )
+        .unwrap()
+    }
+
+    fn shares(table: &Table, partition_by: &[String]) -> Vec<String> {
+        add_share(table, "sales", partition_by, "sales_pct", 1)
+            .unwrap()
+            .rows()
+            .iter()
+            .map(|row| row[2].clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_share_of_total() {
+        let table = sales(&[["north", "30"], ["north", "10"], ["south", "60"]]);
+        assert_eq!(shares(&table, &[]), ["30.0", "10.0", "60.0"]);
+    }
+
+    #[test]
+    fn test_share_per_partition() {
+        let table = sales(&[["north", "30"], ["north", "10"], ["south", "60"]]);
+        assert_eq!(
+            shares(&table, &["region".to_string()]),
+            ["75.0", "25.0", "100.0"]
+        );
+    }
+
+    #[test]
+    fn test_empty_cells_get_no_share() {
+        let table = sales(&[["north", "30"], ["south", ""]]);
+        assert_eq!(shares(&table, &[]), ["100.0", ""]);
+    }
+
+    #[test]
+    fn test_zero_total_gets_no_share() {
+        let table = sales(&[["north", "0"], ["south", "5"]]);
+        assert_eq!(shares(&table, &["region".to_string()]), ["", "100.0"]);
+    }
+
+    #[test]
+    fn test_share_needs_numbers() {
+        let table = sales(&[["north", "30"]]);
+        assert!(matches!(
+            add_share(&table, "region", &[], "pct", 1),
+            Err(TableError::InvalidCell { row_index: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn test_share_unknown_column() {
+        let table = sales(&[["north", "30"]]);
+        assert!(matches!(
+            add_share(&table, "sales", &["country".to_string()], "pct", 1),
+            Err(TableError::MissingColumn(column)) if column == "country"
+        ));
+    }
+}
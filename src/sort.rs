@@ -0,0 +1,121 @@
+use std::cmp::Ordering;
+
+use crate::cell_value::compare_cells;
+use crate::table::{Table, TableError};
+
+/// Sorts rows by the typed values of key columns
+///
+/// Cells are ordered by `compare_cells`, so numbers and IP addresses sort
+/// by value rather than as text. The sort is stable: rows with equal keys
+/// keep their input order.
+///
+/// # Arguments
+/// * `table` - Table to sort
+/// * `key_columns` - Columns to sort rows by, in priority order
+/// * `descending` - Reverse the order of the keys
+/// * `as_text` - Order cells as text instead of by type, for `--strict`
+///
+/// # Returns
+/// * `Table` - A copy of the table with sorted rows
+pub fn sort_rows(
+    table: &Table,
+    key_columns: &[String],
+    descending: bool,
+    as_text: bool,
+) -> Result<Table, TableError> {
+    let keys = key_columns
+        .iter()
+        .map(|column| {
+            table
+                .column_index(column)
+                .ok_or_else(|| TableError::MissingColumn(column.clone()))
+        })
+        .collect::<Result<Vec<usize>, TableError>>()?;
+
+    let mut rows = table.rows().to_vec();
+    rows.sort_by(|left, right| {
+        let ordering = keys
+            .iter()
+            .map(|index| {
+                if as_text {
+                    left[*index].cmp(&right[*index])
+                } else {
+                    compare_cells(&left[*index], &right[*index])
+                }
+            })
+            .find(|ordering| *ordering != Ordering::Equal)
+            .unwrap_or(Ordering::Equal);
+        if descending {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    });
+    table.with_rows(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hosts() -> Table {
+        Table::with_header_and_data(
+            vec!["host".to_string(), "ip".to_string()],
+            [
+                ("c", "10.0.0.10"),
+                ("a", "10.0.0.9"),
+                ("d", "2001:db8::1"),
+                ("b", "10.0.0.9"),
+            ]
+            .iter()
+            .map(|(host, ip)| vec![host.to_string(), ip.to_string()])
+            .collect(),
+        )
+        .unwrap()
+    }
+
+    fn host_names(table: &Table) -> Vec<String> {
+        table.rows().iter().map(|row| row[0].clone()).collect()
+    }
+
+    #[test]
+    fn test_sort_rows_by_ip() {
+        let sorted = sort_rows(&hosts(), &["ip".to_string()], false, false).unwrap();
+        assert_eq!(host_names(&sorted), vec!["a", "b", "c", "d"]);
+    }
+
+    #[test]
+    fn test_sort_descending() {
+        let sorted = sort_rows(&hosts(), &["ip".to_string()], true, false).unwrap();
+        assert_eq!(host_names(&sorted), vec!["d", "c", "a", "b"]);
+    }
+
+    #[test]
+    fn test_later_keys_break_ties() {
+        let key = ["ip".to_string(), "host".to_string()];
+        let sorted = sort_rows(&hosts(), &key, true, false).unwrap();
+        assert_eq!(host_names(&sorted), vec!["d", "c", "b", "a"]);
+    }
+
+    #[test]
+    fn test_sort_unknown_column() {
+        assert!(matches!(
+            sort_rows(&hosts(), &["port".to_string()], false, false),
+            Err(TableError::MissingColumn(column)) if column == "port"
+        ));
+    }
+
+    #[test]
+    fn test_sort_rows_as_text() {
+        let table = Table::with_header_and_data(
+            vec!["n".to_string()],
+            ["9", "10", "1.5"]
+                .iter()
+                .map(|n| vec![n.to_string()])
+                .collect(),
+        )
+        .unwrap();
+        let sorted = sort_rows(&table, &["n".to_string()], false, true).unwrap();
+        assert_eq!(sorted.rows(), &[vec!["1.5"], vec!["10"], vec!["9"]]);
+    }
+}
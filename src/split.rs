@@ -0,0 +1,321 @@
+use std::num::NonZeroUsize;
+
+use crate::table::{Table, TableError};
+
+/// Splits a table into consecutive parts of at most `rows_per_part` rows
+///
+/// Every part keeps the header of the original table. An empty table
+/// yields no parts.
+pub fn split_by_rows(table: &Table, rows_per_part: NonZeroUsize) -> Result<Vec<Table>, TableError> {
+    table
+        .rows()
+        .chunks(rows_per_part.get())
+        .map(|rows| table.with_rows(rows.to_vec()))
+        .collect()
+}
+
+/// Splits a table into one part per distinct value of a column
+///
+/// # Arguments
+/// * `table` - Table to split
+/// * `column_name` - Column whose values select the part
+///
+/// # Returns
+/// * `Vec<(String, Table)>` - Value and its rows, in order of first appearance
+pub fn split_by_column(
+    table: &Table,
+    column_name: &str,
+) -> Result<Vec<(String, Table)>, TableError> {
+    let column_index = table
+        .column_index(column_name)
+        .ok_or_else(|| TableError::MissingColumn(column_name.to_string()))?;
+
+    let mut groups: Vec<(String, Vec<Vec<String>>)> = Vec::new();
+    for row in table.rows() {
+        let value = &row[column_index];
+        match groups.iter_mut().find(|(group, _)| group == value) {
+            Some((_, rows)) => rows.push(row.clone()),
+            None => groups.push((value.clone(), vec![row.clone()])),
+        }
+    }
+
+    groups
+        .into_iter()
+        .map(|(value, rows)| Ok((value, table.with_rows(rows)?)))
+        .collect()
+}
+
+/// Randomly assigns rows to parts sized by `fractions`
+///
+/// Fractions are relative weights, so `0.8,0.2` and `4,1` are equivalent.
+/// The same seed always produces the same split. With `stratify_by`, each
+/// distinct value of that column is split separately so every part keeps
+/// the column's proportions. Rows keep their original order within a part.
+///
+/// # Arguments
+/// * `table` - Table to split
+/// * `fractions` - Relative size of each part
+/// * `seed` - Seed for the row shuffle
+/// * `stratify_by` - Column whose value proportions every part preserves
+///
+/// # Returns
+/// * `Vec<Table>` - One table per fraction
+pub fn split_random(
+    table: &Table,
+    fractions: &[f64],
+    seed: u64,
+    stratify_by: Option<&str>,
+) -> Result<Vec<Table>, TableError> {
+    if fractions.is_empty() || fractions.iter().any(|f| !f.is_finite() || *f <= 0.0) {
+        return Err(TableError::InvalidArgument(
+            "fractions must be positive numbers".to_string(),
+        ));
+    }
+
+    let total: f64 = fractions.iter().sum();
+    let mut rng = SplitMix64(seed);
+    let mut parts: Vec<Vec<usize>> = vec![Vec::new(); fractions.len()];
+
+    for rows in shuffled_strata(table, stratify_by, &mut rng)? {
+        let mut start = 0;
+        let mut cumulative = 0.0;
+        for (part, fraction) in parts.iter_mut().zip(fractions) {
+            cumulative += fraction;
+            let end = ((cumulative / total) * rows.len() as f64).round() as usize;
+            part.extend_from_slice(&rows[start..end.min(rows.len())]);
+            start = end.min(rows.len());
+        }
+    }
+
+    parts
+        .into_iter()
+        .map(|row_indices| keep_rows(table, row_indices))
+        .collect()
+}
+
+/// How many rows `sample_rows` keeps
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SampleSize {
+    /// This share of the rows, of each group when stratified
+    Fraction(f64),
+    /// This many rows, from each group when stratified
+    Rows(usize),
+}
+
+/// Keeps a random sample of rows
+///
+/// The same seed always draws the same sample. With `stratify_by`, each
+/// distinct value of that column is sampled separately, so a fraction
+/// keeps the column's proportions and a row count takes that many rows
+/// of every value. Rows keep their original order.
+///
+/// # Arguments
+/// * `table` - Table to sample
+/// * `size` - Share or number of rows to keep
+/// * `seed` - Seed for the row shuffle
+/// * `stratify_by` - Column whose values are sampled separately
+///
+/// # Returns
+/// * `Table` - The sampled rows
+pub fn sample_rows(
+    table: &Table,
+    size: SampleSize,
+    seed: u64,
+    stratify_by: Option<&str>,
+) -> Result<Table, TableError> {
+    if let SampleSize::Fraction(fraction) = size {
+        if !(0.0..=1.0).contains(&fraction) {
+            return Err(TableError::InvalidArgument(
+                "the sample fraction must be between 0 and 1".to_string(),
+            ));
+        }
+    }
+
+    let mut rng = SplitMix64(seed);
+    let mut kept = Vec::new();
+    for rows in shuffled_strata(table, stratify_by, &mut rng)? {
+        let count = match size {
+            SampleSize::Fraction(fraction) => (fraction * rows.len() as f64).round() as usize,
+            SampleSize::Rows(count) => count.min(rows.len()),
+        };
+        kept.extend_from_slice(&rows[..count]);
+    }
+    keep_rows(table, kept)
+}
+
+/// Row indices grouped by the value of `stratify_by`, or all in one group,
+/// each group shuffled
+fn shuffled_strata(
+    table: &Table,
+    stratify_by: Option<&str>,
+    rng: &mut SplitMix64,
+) -> Result<Vec<Vec<usize>>, TableError> {
+    let mut strata: Vec<Vec<usize>> = match stratify_by {
+        Some(column_name) => {
+            let column_index = table
+                .column_index(column_name)
+                .ok_or_else(|| TableError::MissingColumn(column_name.to_string()))?;
+            let mut groups: Vec<(&String, Vec<usize>)> = Vec::new();
+            for (row_index, row) in table.rows().iter().enumerate() {
+                let value = &row[column_index];
+                match groups.iter_mut().find(|(group, _)| *group == value) {
+                    Some((_, rows)) => rows.push(row_index),
+                    None => groups.push((value, vec![row_index])),
+                }
+            }
+            groups.into_iter().map(|(_, rows)| rows).collect()
+        }
+        None => vec![(0..table.row_count()).collect()],
+    };
+
+    for rows in &mut strata {
+        for i in (1..rows.len()).rev() {
+            let j = (rng.next() % (i as u64 + 1)) as usize;
+            rows.swap(i, j);
+        }
+    }
+    Ok(strata)
+}
+
+/// The rows at `row_indices`, in table order
+fn keep_rows(table: &Table, mut row_indices: Vec<usize>) -> Result<Table, TableError> {
+    row_indices.sort_unstable();
+    table.with_rows(
+        row_indices
+            .into_iter()
+            .map(|row_index| table.rows()[row_index].clone())
+            .collect(),
+    )
+}
+
+/// Small seeded generator, so a given `--seed` splits the same way in every release.
+pub(crate) struct SplitMix64(pub(crate) u64);
+
+impl SplitMix64 {
+    pub(crate) fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_table() -> Table {
+        let to_strings = |cells: &[&str]| cells.iter().map(|c| c.to_string()).collect();
+        Table::with_header_and_data(
+            to_strings(&["id", "region"]),
+            vec![
+                to_strings(&["1", "eu"]),
+                to_strings(&["2", "us"]),
+                to_strings(&["3", "eu"]),
+            ],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_split_by_rows() {
+        let parts = split_by_rows(&sample_table(), NonZeroUsize::new(2).unwrap()).unwrap();
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts[1].row_count(), 1);
+        assert_eq!(parts[1].get_value(0, "id").unwrap(), "3");
+    }
+
+    #[test]
+    fn test_split_by_column() {
+        let parts = split_by_column(&sample_table(), "region").unwrap();
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts[0].0, "eu");
+        assert_eq!(parts[0].1.row_count(), 2);
+        assert_eq!(parts[1].1.get_value(0, "id").unwrap(), "2");
+    }
+
+    fn eu_rows(table: &Table) -> usize {
+        (0..table.row_count())
+            .filter(|row| table.get_value(*row, "region").unwrap() == "eu")
+            .count()
+    }
+
+    #[test]
+    fn test_split_random_keeps_every_row() {
+        let parts = split_random(&sample_table(), &[0.5, 0.5], 42, None).unwrap();
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts[0].row_count() + parts[1].row_count(), 3);
+    }
+
+    #[test]
+    fn test_split_random_is_seeded() {
+        let parts = split_random(&sample_table(), &[0.5, 0.5], 42, Some("region")).unwrap();
+        let again = split_random(&sample_table(), &[0.5, 0.5], 42, Some("region")).unwrap();
+        assert_eq!(parts[0].rows(), again[0].rows());
+    }
+
+    #[test]
+    fn test_split_random_is_stratified() {
+        let parts = split_random(&sample_table(), &[0.5, 0.5], 42, Some("region")).unwrap();
+        assert_eq!(eu_rows(&parts[0]), 1);
+        assert_eq!(eu_rows(&parts[1]), 1);
+    }
+
+    #[test]
+    fn test_split_random_invalid_fractions() {
+        for fractions in [&[][..], &[0.5, 0.0], &[f64::NAN]] {
+            assert!(matches!(
+                split_random(&sample_table(), fractions, 42, None),
+                Err(TableError::InvalidArgument(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_split_random_unknown_column() {
+        assert!(matches!(
+            split_random(&sample_table(), &[1.0], 42, Some("city")),
+            Err(TableError::MissingColumn(column)) if column == "city"
+        ));
+    }
+
+    #[test]
+    fn test_sample_fraction() {
+        let sample = sample_rows(&sample_table(), SampleSize::Fraction(0.5), 7, None).unwrap();
+        assert_eq!(sample.row_count(), 2);
+    }
+
+    #[test]
+    fn test_sample_is_seeded() {
+        let sample = sample_rows(&sample_table(), SampleSize::Fraction(0.5), 7, None).unwrap();
+        let again = sample_rows(&sample_table(), SampleSize::Fraction(0.5), 7, None).unwrap();
+        assert_eq!(sample.rows(), again.rows());
+    }
+
+    #[test]
+    fn test_sample_rows_per_stratum() {
+        let sample = sample_rows(&sample_table(), SampleSize::Rows(1), 7, Some("region")).unwrap();
+        let mut regions: Vec<&str> = (0..sample.row_count())
+            .map(|row| sample.get_value(row, "region").unwrap().as_str())
+            .collect();
+        regions.sort();
+        assert_eq!(regions, ["eu", "us"]);
+    }
+
+    #[test]
+    fn test_sample_invalid_fraction() {
+        assert!(matches!(
+            sample_rows(&sample_table(), SampleSize::Fraction(1.5), 7, None),
+            Err(TableError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn test_sample_unknown_column() {
+        assert!(matches!(
+            sample_rows(&sample_table(), SampleSize::Rows(1), 7, Some("city")),
+            Err(TableError::MissingColumn(column)) if column == "city"
+        ));
+    }
+}
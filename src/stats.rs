@@ -0,0 +1,440 @@
+use std::{
+    cmp::Ordering,
+    collections::HashSet,
+    hash::{DefaultHasher, Hash, Hasher},
+    time::Duration,
+};
+
+use crate::aggregate::format_number;
+use crate::cell_value::{compare_cells, format_duration, CellValue};
+use crate::split::SplitMix64;
+use crate::table::{Table, TableError};
+
+/// Statistics computed for every column, in output order
+pub const STATISTICS: [&str; 7] = ["count", "empty", "distinct", "min", "max", "mean", "median"];
+
+/// Values `--approx` keeps per column to estimate the median from
+const RESERVOIR_SIZE: usize = 10_000;
+
+/// Bits of the hash selecting a HyperLogLog register; 2^12 registers
+/// give distinct counts within about 1.6%
+const HLL_PRECISION: u32 = 12;
+
+/// Summarizes each column of a table
+///
+/// `count` and `distinct` cover the non-empty cells, `min` and `max`
+/// order them by typed value, and `mean` and `median` are left empty
+/// unless every non-empty cell is a number, or every one a duration.
+///
+/// With `approx`, `distinct` is a HyperLogLog estimate and `median` the
+/// median of a random sample of at most 10,000 values, which keeps both
+/// cheap on huge columns. The sample is seeded, so repeated runs agree.
+///
+/// # Arguments
+/// * `table` - Table to summarize
+/// * `approx` - Estimate `distinct` and `median` instead of computing them
+///
+/// # Returns
+/// * `Table` - One row per column: its name, then the statistics
+pub fn summarize(table: &Table, approx: bool) -> Result<Table, TableError> {
+    let mut header = vec!["column".to_string()];
+    header.extend(STATISTICS.iter().map(|statistic| statistic.to_string()));
+    let rows = column_stats(table, approx)
+        .into_iter()
+        .map(|(column, values)| [vec![column], values].concat())
+        .collect();
+    Table::with_header_and_data(header, rows)
+}
+
+/// Lays the statistics of a table next to those of a baseline version
+///
+/// `delta` is the signed change of numeric statistics, `changed` for other
+/// values that differ, `added` or `removed` for columns only one version
+/// has, and empty when nothing changed, so drift stands out.
+///
+/// # Arguments
+/// * `table` - Current version of the table
+/// * `baseline` - Earlier version to compare against
+/// * `approx` - Estimate `distinct` and `median`, see `summarize`
+///
+/// # Returns
+/// * `Table` - One row per column and statistic: the baseline value, the
+///   current value and their delta
+pub fn compare_to_baseline(
+    table: &Table,
+    baseline: &Table,
+    approx: bool,
+) -> Result<Table, TableError> {
+    let current = column_stats(table, approx);
+    let previous = column_stats(baseline, approx);
+    let mut columns: Vec<&str> = current.iter().map(|(column, _)| column.as_str()).collect();
+    columns.extend(
+        previous
+            .iter()
+            .map(|(column, _)| column.as_str())
+            .filter(|column| !current.iter().any(|(other, _)| other == column)),
+    );
+    let find = |stats: &[(String, Vec<String>)], column: &str| -> Option<Vec<String>> {
+        stats
+            .iter()
+            .find(|(other, _)| other == column)
+            .map(|(_, values)| values.clone())
+    };
+
+    let mut rows = Vec::new();
+    for column in columns {
+        let (new, old) = (find(&current, column), find(&previous, column));
+        for (index, statistic) in STATISTICS.iter().enumerate() {
+            let value = |values: &Option<Vec<String>>| {
+                values
+                    .as_ref()
+                    .map_or(String::new(), |values| values[index].clone())
+            };
+            let (old_value, new_value) = (value(&old), value(&new));
+            let delta = match (&old, &new) {
+                (None, _) => "added".to_string(),
+                (_, None) => "removed".to_string(),
+                _ => delta(&old_value, &new_value),
+            };
+            rows.push(vec![
+                column.to_string(),
+                statistic.to_string(),
+                old_value,
+                new_value,
+                delta,
+            ]);
+        }
+    }
+    Table::with_header_and_data(
+        ["column", "statistic", "baseline", "current", "delta"]
+            .iter()
+            .map(|name| name.to_string())
+            .collect(),
+        rows,
+    )
+}
+
+/// Each column's name with its statistics; headerless columns are named
+/// `#1`, `#2`, and so on
+fn column_stats(table: &Table, approx: bool) -> Vec<(String, Vec<String>)> {
+    let header = table.header();
+    (0..table.column_count())
+        .map(|index| {
+            let name = header
+                .get(index)
+                .map_or_else(|| format!("#{}", index + 1), |name| name.to_string());
+            let cells: Vec<&str> = table
+                .rows()
+                .iter()
+                .map(|row| row[index].as_str())
+                .filter(|cell| CellValue::parse(cell) != CellValue::Empty)
+                .collect();
+            let numbers: Option<Vec<f64>> = cells
+                .iter()
+                .map(|cell| match CellValue::parse(cell) {
+                    CellValue::Number(number) => Some(number),
+                    _ => None,
+                })
+                .collect();
+            let durations: Option<Vec<Duration>> = cells
+                .iter()
+                .map(|cell| match CellValue::parse(cell) {
+                    CellValue::Duration(duration) => Some(duration),
+                    _ => None,
+                })
+                .collect();
+            let mean = match (numbers, durations) {
+                _ if cells.is_empty() => String::new(),
+                (Some(numbers), _) => {
+                    let mean = numbers.iter().sum::<f64>() / numbers.len() as f64;
+                    format_number((mean * 1e6).round() / 1e6)
+                }
+                (_, Some(durations)) => format_duration(
+                    durations
+                        .iter()
+                        .sum::<Duration>()
+                        .div_f64(durations.len() as f64),
+                ),
+                _ => String::new(),
+            };
+            let extreme = |ordering| {
+                cells
+                    .iter()
+                    .copied()
+                    .reduce(|best, cell| {
+                        if compare_cells(cell, best) == ordering {
+                            cell
+                        } else {
+                            best
+                        }
+                    })
+                    .unwrap_or_default()
+                    .to_string()
+            };
+            let distinct = if approx {
+                estimate_distinct(&cells)
+            } else {
+                cells.iter().collect::<HashSet<_>>().len()
+            };
+            let values = vec![
+                cells.len().to_string(),
+                (table.rows().len() - cells.len()).to_string(),
+                distinct.to_string(),
+                extreme(Ordering::Less),
+                extreme(Ordering::Greater),
+                mean,
+                median(&cells, approx),
+            ];
+            (name, values)
+        })
+        .collect()
+}
+
+/// The median of a column of numbers or of durations, or empty
+fn median(cells: &[&str], approx: bool) -> String {
+    let mut values: Vec<f64> = Vec::with_capacity(cells.len().min(RESERVOIR_SIZE));
+    let mut rng = SplitMix64(0);
+    let mut durations = None;
+    for (index, cell) in cells.iter().enumerate() {
+        let (value, is_duration) = match CellValue::parse(cell) {
+            CellValue::Number(number) => (number, false),
+            CellValue::Duration(duration) => (duration.as_secs_f64(), true),
+            _ => return String::new(),
+        };
+        if *durations.get_or_insert(is_duration) != is_duration {
+            return String::new();
+        }
+        // Reservoir sampling: the value replaces a random sampled one with
+        // probability RESERVOIR_SIZE / (index + 1).
+        if !approx || values.len() < RESERVOIR_SIZE {
+            values.push(value);
+        } else {
+            let slot = (rng.next() % (index as u64 + 1)) as usize;
+            if slot < RESERVOIR_SIZE {
+                values[slot] = value;
+            }
+        }
+    }
+    if values.is_empty() {
+        return String::new();
+    }
+
+    values.sort_by(f64::total_cmp);
+    let middle = values.len() / 2;
+    let median = if values.len().is_multiple_of(2) {
+        (values[middle - 1] + values[middle]) / 2.0
+    } else {
+        values[middle]
+    };
+    match durations {
+        Some(true) => format_duration(Duration::from_secs_f64(median)),
+        _ => format_number((median * 1e6).round() / 1e6),
+    }
+}
+
+/// Estimates the number of distinct cells with HyperLogLog
+fn estimate_distinct(cells: &[&str]) -> usize {
+    let registers_len = 1usize << HLL_PRECISION;
+    let mut registers = vec![0u8; registers_len];
+    for cell in cells {
+        let mut hasher = DefaultHasher::new();
+        cell.hash(&mut hasher);
+        let hash = hasher.finish();
+        let register = (hash >> (64 - HLL_PRECISION)) as usize;
+        // Position of the first set bit after the register bits, from 1
+        let rank = ((hash << HLL_PRECISION) | (1 << (HLL_PRECISION - 1))).leading_zeros() + 1;
+        registers[register] = registers[register].max(rank as u8);
+    }
+
+    let m = registers_len as f64;
+    let alpha = 0.7213 / (1.0 + 1.079 / m);
+    let sum: f64 = registers
+        .iter()
+        .map(|rank| 2f64.powi(-i32::from(*rank)))
+        .sum();
+    let estimate = alpha * m * m / sum;
+    let empty = registers.iter().filter(|rank| **rank == 0).count();
+    // Small counts are more accurate from the share of empty registers.
+    let estimate = if estimate <= 2.5 * m && empty > 0 {
+        m * (m / empty as f64).ln()
+    } else {
+        estimate
+    };
+    estimate.round() as usize
+}
+
+/// The change from one statistic value to another
+fn delta(old: &str, new: &str) -> String {
+    if old == new {
+        return String::new();
+    }
+    match (CellValue::parse(old), CellValue::parse(new)) {
+        (CellValue::Number(old), CellValue::Number(new)) => {
+            let change = ((new - old) * 1e6).round() / 1e6;
+            if change > 0.0 {
+                format!("+{}", format_number(change))
+            } else {
+                format_number(change)
+            }
+        }
+        (CellValue::Duration(old), CellValue::Duration(new)) => match new.cmp(&old) {
+            Ordering::Less => format!("-{}", format_duration(old - new)),
+            _ => format!("+{}", format_duration(new - old)),
+        },
+        _ => "changed".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(rows: &[[&str; 2]]) -> Table {
+        Table::with_header_and_data(
+            vec!["id".to_string(), "amount".to_string()],
+            rows.iter()
+                .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+                .collect(),
+        )
+        .unwrap()
+    }
+
+    fn amount_row(compared: &Table, statistic: &str) -> Vec<String> {
+        compared
+            .rows()
+            .iter()
+            .find(|row| row[0] == "amount" && row[1] == statistic)
+            .unwrap()
+            .clone()
+    }
+
+    fn large_table() -> Table {
+        let rows: Vec<[String; 2]> = (0..40_000)
+            .map(|index| [index.to_string(), (index % 20_000).to_string()])
+            .collect();
+        let rows: Vec<[&str; 2]> = rows
+            .iter()
+            .map(|[id, amount]| [id.as_str(), amount.as_str()])
+            .collect();
+        table(&rows)
+    }
+
+    #[test]
+    fn test_summarize_numbers() {
+        let current = table(&[["1", "10"], ["2", ""], ["3", "9.5"], ["4", "10"]]);
+        let summary = summarize(&current, false).unwrap();
+        assert_eq!(
+            summary.rows()[1],
+            ["amount", "3", "1", "2", "9.5", "10", "9.833333", "10"]
+        );
+    }
+
+    #[test]
+    fn test_summarize_text() {
+        let summary = summarize(&table(&[["1", "b"], ["2", "a"]]), false).unwrap();
+        assert_eq!(
+            summary.rows()[1],
+            ["amount", "2", "0", "2", "a", "b", "", ""]
+        );
+    }
+
+    #[test]
+    fn test_summarize_without_header() {
+        let headerless = Table::with_data(vec![vec!["1".to_string(), "x".to_string()]]).unwrap();
+        let summary = summarize(&headerless, false).unwrap();
+        assert_eq!(summary.rows()[0][0], "#1");
+        assert_eq!(summary.rows()[1][0], "#2");
+    }
+
+    #[test]
+    fn test_baseline_deltas() {
+        let current = table(&[["1", "10"], ["2", ""], ["3", "9.5"], ["4", "10"]]);
+        let baseline = table(&[["1", "10"], ["2", "7"], ["3", "x"]]);
+        let compared = compare_to_baseline(&current, &baseline, false).unwrap();
+        let deltas: Vec<(&str, &str)> = compared
+            .rows()
+            .iter()
+            .filter(|row| row[0] == "amount")
+            .map(|row| (row[1].as_str(), row[4].as_str()))
+            .collect();
+        assert_eq!(
+            deltas,
+            [
+                ("count", ""),
+                ("empty", "+1"),
+                ("distinct", "-1"),
+                ("min", "+2.5"),
+                ("max", "changed"),
+                ("mean", "changed"),
+                ("median", "changed")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_baseline_added_and_removed_columns() {
+        let current = table(&[["1", "10"]]);
+        let baseline = Table::with_header_and_data(
+            vec!["id".to_string(), "price".to_string()],
+            vec![vec!["1".to_string(), "5".to_string()]],
+        )
+        .unwrap();
+        let compared = compare_to_baseline(&current, &baseline, false).unwrap();
+        assert_eq!(amount_row(&compared, "count")[2..], ["", "1", "added"]);
+        let price = compared
+            .rows()
+            .iter()
+            .find(|row| row[0] == "price" && row[1] == "count")
+            .unwrap();
+        assert_eq!(price[2..], ["1", "", "removed"]);
+    }
+
+    #[test]
+    fn test_summarize_durations() {
+        let current = table(&[["1", "1h"], ["2", "PT30M"], ["3", "00:45:00"]]);
+        let summary = summarize(&current, false).unwrap();
+        assert_eq!(
+            summary.rows()[1],
+            ["amount", "3", "0", "3", "PT30M", "1h", "45m", "45m"]
+        );
+    }
+
+    #[test]
+    fn test_duration_deltas() {
+        let current = table(&[["1", "1h"], ["2", "PT30M"], ["3", "00:45:00"]]);
+        let baseline = table(&[["1", "2h"], ["2", "30m"]]);
+        let compared = compare_to_baseline(&current, &baseline, false).unwrap();
+        assert_eq!(amount_row(&compared, "mean")[2..], ["1h15m", "45m", "-30m"]);
+    }
+
+    #[test]
+    fn test_approximate_distinct() {
+        let summary = summarize(&large_table(), true).unwrap();
+        let distinct = summary.rows()[1][3].parse::<f64>().unwrap();
+        // Exact: 20,000 distinct amounts
+        assert!((distinct - 20_000.0).abs() < 20_000.0 * 0.05);
+    }
+
+    #[test]
+    fn test_approximate_median() {
+        let summary = summarize(&large_table(), true).unwrap();
+        let median = summary.rows()[1][7].parse::<f64>().unwrap();
+        // Exact: 9,999.5
+        assert!((median - 9_999.5).abs() < 20_000.0 * 0.02);
+    }
+
+    #[test]
+    fn test_approximation_is_repeatable() {
+        let table = large_table();
+        assert_eq!(
+            summarize(&table, true).unwrap().rows(),
+            summarize(&table, true).unwrap().rows()
+        );
+    }
+
+    #[test]
+    fn test_approximation_of_small_columns_is_exact() {
+        let small = summarize(&table(&[["1", "a"], ["2", "b"], ["3", "a"]]), true).unwrap();
+        assert_eq!(small.rows()[1][3], "2");
+    }
+}
@@ -1,9 +1,12 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, fmt};
+
+use crate::column_metadata::ColumnMetadata;
 
 #[derive(Debug)]
 pub struct Table {
     data: Vec<Vec<String>>,
     header_map: HashMap<String, usize>,
+    column_metadata: HashMap<String, ColumnMetadata>,
 }
 
 #[derive(Debug)]
@@ -16,15 +19,49 @@ pub enum TableError {
         header_len: usize,
     },
     InvalidRowIndex(usize),
-    InvalidTableSize
+    InvalidTableSize,
+    MissingColumn(String),
+    InvalidCell {
+        row_index: usize,
+        column: String,
+    },
+    InvalidArgument(String),
 }
 
+impl fmt::Display for TableError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TableError::EmptyHeader => write!(f, "table header is empty"),
+            TableError::DuplicateColumn(column) => write!(f, "duplicate column '{}'", column),
+            TableError::RowLengthMismatch {
+                row_index,
+                row_len,
+                header_len,
+            } => write!(
+                f,
+                "row {} has {} cells, expected {}",
+                row_index, row_len, header_len
+            ),
+            TableError::InvalidRowIndex(row_index) => write!(f, "invalid row index {}", row_index),
+            TableError::InvalidTableSize => write!(f, "unrecognized table format"),
+            TableError::MissingColumn(column) => write!(f, "missing column '{}'", column),
+            TableError::InvalidCell { row_index, column } => {
+                write!(f, "invalid value in row {}, column '{}'", row_index, column)
+            }
+            TableError::InvalidArgument(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for TableError {}
+
 impl Table {
     /// Creates a new empty table
     pub fn new() -> Self {
         Table {
             data: Vec::new(),
             header_map: HashMap::new(),
+            column_metadata: HashMap::new(),
         }
     }
 
@@ -55,7 +92,11 @@ impl Table {
             }
         }
 
-        Ok(Table { data, header_map })
+        Ok(Table {
+            data,
+            header_map,
+            column_metadata: HashMap::new(),
+        })
     }
 
     /// Creates a table with only data (no headers)
@@ -63,9 +104,116 @@ impl Table {
         Ok(Table {
             data,
             header_map: HashMap::new(),
+            column_metadata: HashMap::new(),
         })
     }
 
+    /// Creates a table with the same header as this one and the given rows
+    pub fn with_rows(&self, rows: Vec<Vec<String>>) -> Result<Self, TableError> {
+        let header = self.header();
+        if header.is_empty() {
+            return Table::with_data(rows);
+        }
+        let mut table =
+            Table::with_header_and_data(header.into_iter().map(String::from).collect(), rows)?;
+        table.column_metadata = self.column_metadata.clone();
+        Ok(table)
+    }
+
+    /// Creates a table with a column appended, holding one value per row
+    ///
+    /// A table without a header gets the column without a name.
+    pub fn append_column(&self, name: &str, values: Vec<String>) -> Result<Self, TableError> {
+        if values.len() != self.data.len() {
+            return Err(TableError::InvalidArgument(format!(
+                "{} values for a column of {} rows",
+                values.len(),
+                self.data.len()
+            )));
+        }
+        let rows = self
+            .data
+            .iter()
+            .zip(values)
+            .map(|(row, value)| {
+                let mut row = row.clone();
+                row.push(value);
+                row
+            })
+            .collect();
+
+        let header = self.header();
+        if header.is_empty() {
+            return Table::with_data(rows);
+        }
+        let mut header: Vec<String> = header.into_iter().map(String::from).collect();
+        header.push(name.to_string());
+        let mut table = Table::with_header_and_data(header, rows)?;
+        table.column_metadata = self.column_metadata.clone();
+        Ok(table)
+    }
+
+    /// Creates a table with the columns at the given positions, in that order
+    pub fn select_columns(&self, column_indices: &[usize]) -> Result<Self, TableError> {
+        let rows = self
+            .data
+            .iter()
+            .map(|row| {
+                column_indices
+                    .iter()
+                    .map(|index| row.get(*index).cloned().unwrap_or_default())
+                    .collect()
+            })
+            .collect();
+
+        let header = self.header();
+        if header.is_empty() {
+            return Table::with_data(rows);
+        }
+        let header = column_indices
+            .iter()
+            .map(|index| header.get(*index).copied().unwrap_or_default().to_string())
+            .collect();
+        let mut table = Table::with_header_and_data(header, rows)?;
+        table.column_metadata = self.column_metadata.clone();
+        table
+            .column_metadata
+            .retain(|column, _| table.header_map.contains_key(column));
+        Ok(table)
+    }
+
+    /// Renames a column, keeping its position
+    pub fn rename_column(&mut self, from: &str, to: &str) -> Result<(), TableError> {
+        if from == to {
+            return Ok(());
+        }
+        if self.header_map.contains_key(to) {
+            return Err(TableError::DuplicateColumn(to.to_string()));
+        }
+        let index = self
+            .header_map
+            .remove(from)
+            .ok_or_else(|| TableError::MissingColumn(from.to_string()))?;
+        self.header_map.insert(to.to_string(), index);
+        if let Some(metadata) = self.column_metadata.remove(from) {
+            self.column_metadata.insert(to.to_string(), metadata);
+        }
+        Ok(())
+    }
+
+    /// Attaches metadata to the named columns, ignoring names the table lacks
+    pub fn set_column_metadata(&mut self, metadata: HashMap<String, ColumnMetadata>) {
+        self.column_metadata = metadata
+            .into_iter()
+            .filter(|(column, _)| self.header_map.contains_key(column))
+            .collect();
+    }
+
+    /// Gets the metadata of a column, if any was attached
+    pub fn column_metadata(&self, column_name: &str) -> Option<&ColumnMetadata> {
+        self.column_metadata.get(column_name)
+    }
+
     /// Adds a new row to the table
     pub fn add_row(&mut self, row: Vec<String>) -> Result<(), TableError> {
         if !self.header_map.is_empty() && self.header_map.len() != row.len() {
@@ -84,6 +232,11 @@ impl Table {
         self.data.get(row_index)
     }
 
+    /// Returns all data rows
+    pub fn rows(&self) -> &[Vec<String>] {
+        &self.data
+    }
+
     /// Returns the number of rows in the table
     pub fn row_count(&self) -> usize {
         self.data.len()
@@ -96,6 +249,18 @@ impl Table {
             .max(self.data.first().map_or(0, |row| row.len()))
     }
 
+    /// Returns the column names ordered by position
+    pub fn header(&self) -> Vec<&str> {
+        let mut columns: Vec<(&String, &usize)> = self.header_map.iter().collect();
+        columns.sort_by_key(|(_, index)| **index);
+        columns.into_iter().map(|(name, _)| name.as_str()).collect()
+    }
+
+    /// Gets the position of a column by name
+    pub fn column_index(&self, column_name: &str) -> Option<usize> {
+        self.header_map.get(column_name).copied()
+    }
+
     /// Gets a value by row index and column name
     pub fn get_value(&self, row_index: usize, column_name: &str) -> Option<&String> {
         let column_index = self.header_map.get(column_name)?;
@@ -126,4 +291,32 @@ mod tests {
         let row = vec!["1".to_string(), "2".to_string()];
         assert!(table.add_row(row).is_ok());
     }
+
+    #[test]
+    fn test_append_column() {
+        let table = Table::with_header_and_data(
+            vec!["id".to_string()],
+            vec![vec!["1".to_string()], vec!["2".to_string()]],
+        )
+        .unwrap();
+        let table = table
+            .append_column("name", vec!["a".to_string(), "b".to_string()])
+            .unwrap();
+        assert_eq!(table.header(), vec!["id", "name"]);
+        assert_eq!(table.rows()[1], vec!["2", "b"]);
+    }
+
+    #[test]
+    fn test_append_column_without_header() {
+        let table = Table::with_data(vec![vec!["1".to_string()]]).unwrap();
+        let table = table.append_column("name", vec!["a".to_string()]).unwrap();
+        assert!(table.header().is_empty());
+        assert_eq!(table.rows()[0], vec!["1", "a"]);
+    }
+
+    #[test]
+    fn test_append_column_length_mismatch() {
+        let table = Table::with_data(vec![vec!["1".to_string()]]).unwrap();
+        assert!(table.append_column("name", Vec::new()).is_err());
+    }
 }
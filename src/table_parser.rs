@@ -1,19 +1,105 @@
+use std::{fmt, sync::LazyLock};
+
+use clap::ValueEnum;
+use memchr::{memchr, memchr2, memchr3};
 use regex::Regex;
 
 use crate::table::{Table, TableError};
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum TableType {
     AsciiTable,
-    CsvTable,
+    CsvTable {
+        delimiter: char,
+        quote: Option<char>,
+    },
+    KeyValueTable,
+    PrometheusTable,
     Unknown,
 }
 
+impl fmt::Display for TableType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            TableType::AsciiTable => "ascii",
+            TableType::CsvTable { .. } => "csv",
+            TableType::KeyValueTable => "key-value",
+            TableType::PrometheusTable => "prometheus",
+            TableType::Unknown => "unknown",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Formats `--from` can force, bypassing `deduct_table_type`
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum InputFormat {
+    Ascii,
+    Csv,
+    KeyValue,
+    Prometheus,
+}
+
+impl InputFormat {
+    /// Returns the table type for data known to be in this format
+    ///
+    /// The CSV delimiter and quote are still sniffed; data that no
+    /// delimiter splits consistently, such as a single column, is read as
+    /// comma-separated.
+    pub fn table_type(self, data: &str) -> TableType {
+        match self {
+            InputFormat::Ascii => TableType::AsciiTable,
+            InputFormat::KeyValue => TableType::KeyValueTable,
+            InputFormat::Prometheus => TableType::PrometheusTable,
+            InputFormat::Csv => {
+                let lines: Vec<&str> = data.lines().collect();
+                let (delimiter, quote) = sniff_delimiter(data, &lines, DELIMITER_CANDIDATES)
+                    .unwrap_or_else(|| {
+                        let sample = &lines[..lines.len().min(DELIMITER_SAMPLE_LINES)];
+                        (',', sniff_quote(sample, ','))
+                    });
+                TableType::CsvTable { delimiter, quote }
+            }
+        }
+    }
+}
+
+/// Returns the CSV table type for data with a known delimiter, sniffing
+/// only the quote character
+pub fn delimited_table_type(data: &str, delimiter: char) -> TableType {
+    let lines: Vec<&str> = data.lines().take(DELIMITER_SAMPLE_LINES).collect();
+    TableType::CsvTable {
+        delimiter,
+        quote: sniff_quote(&lines, delimiter),
+    }
+}
+
+/// Parses a delimiter given on the command line
+///
+/// Accepts a single character, or `\t`, `tab` and `space` for the
+/// characters that are awkward to pass through a shell.
+pub fn parse_delimiter(value: &str) -> Result<char, TableError> {
+    match value {
+        "\\t" | "tab" => Ok('\t'),
+        "space" => Ok(' '),
+        _ => {
+            let mut chars = value.chars();
+            match (chars.next(), chars.next()) {
+                (Some(delimiter), None) if delimiter != '\n' && delimiter != '\r' => Ok(delimiter),
+                _ => Err(TableError::InvalidArgument(format!(
+                    "delimiter must be a single character, '\\t', 'tab' or 'space', not '{}'",
+                    value
+                ))),
+            }
+        }
+    }
+}
+
 /// Определяет тип таблицы на основе входных данных
-/// 
+///
 /// # Arguments
 /// * `data` - Строка с данными таблицы
-/// 
+///
 /// # Returns
 /// * `TableType` - Определенный тип таблицы
 pub fn deduct_table_type(data: &str) -> TableType {
@@ -21,61 +107,383 @@ pub fn deduct_table_type(data: &str) -> TableType {
         return TableType::Unknown;
     }
 
+    if parse_prometheus_samples(data).is_some() {
+        return TableType::PrometheusTable;
+    }
+
     let lines: Vec<&str> = data.lines().collect();
 
-    if lines.len() < 3 {
-        if !lines.is_empty() && lines[0].contains(',') {
-            return TableType::CsvTable;
-        }
-        return TableType::Unknown;
+    if lines.len() >= 3 && is_ascii_table(&lines) {
+        return TableType::AsciiTable;
+    }
+
+    if let Some((delimiter, quote)) = sniff_delimiter(data, &lines, DELIMITER_CANDIDATES) {
+        return TableType::CsvTable { delimiter, quote };
     }
 
-    let separator_regex = Regex::new(r"^\+[-]+\+$").unwrap();
-    let content_regex = Regex::new(r"^\|.*\|$").unwrap();
+    // Logfmt lines split evenly on spaces too, so key/value records are
+    // ruled out before falling back to space-separated columns.
+    if parse_key_value_records(data).is_some() {
+        return TableType::KeyValueTable;
+    }
+
+    if let Some((delimiter, quote)) = sniff_delimiter(data, &lines, &[' ']) {
+        return TableType::CsvTable { delimiter, quote };
+    }
 
-    let is_ascii_table = {
-        let has_borders = separator_regex.is_match(lines.first().unwrap())
-            && separator_regex.is_match(lines.last().unwrap());
+    TableType::Unknown
+}
 
-        let has_row_separators = lines
+/// Delimiters tried by `deduct_table_type`, in order of preference.
+/// Space is only considered once everything else has been ruled out.
+pub const DELIMITER_CANDIDATES: &[char] = &[',', '\t', ';', '|'];
+
+/// Number of leading lines or records used to score delimiter candidates.
+const DELIMITER_SAMPLE_LINES: usize = 100;
+
+/// Quote characters recognized around delimited fields, in order of preference.
+pub const QUOTE_CANDIDATES: &[char] = &['"', '\''];
+
+/// Share of sampled records that must have as many fields as the first one
+/// for a delimiter to count as consistent. Leaves room for a few malformed
+/// rows, which parsing then reports or skips.
+const MIN_CONSISTENT_SHARE: f64 = 0.75;
+
+/// Picks the delimiter that splits the sampled records into the same number
+/// of fields as the first record, preferring the one producing the most
+/// columns. Candidates that yield a single column, or whose field counts
+/// disagree for more than a quarter of the records, are rejected.
+/// Records are split with the quote character sniffed for each delimiter,
+/// so quoted cells spanning several lines don't break consistency.
+fn sniff_delimiter(
+    data: &str,
+    lines: &[&str],
+    candidates: &[char],
+) -> Option<(char, Option<char>)> {
+    let sample = &lines[..lines.len().min(DELIMITER_SAMPLE_LINES)];
+
+    let mut best: Option<(char, Option<char>, usize)> = None;
+    for delimiter in candidates {
+        let quote = sniff_quote(sample, *delimiter);
+        let field_counts: Vec<usize> = split_records(data, *delimiter, quote)
+            .into_iter()
+            .take(DELIMITER_SAMPLE_LINES)
+            .map(|record| split_delimited(record, *delimiter, quote).len())
+            .collect();
+        let Some(&columns) = field_counts.first() else {
+            continue;
+        };
+
+        let matching = field_counts
             .iter()
-            .enumerate()
-            .filter(|(index, _)| index % 2 == 1)
-            .all(|(_, line)| separator_regex.is_match(line));
+            .filter(|count| **count == columns)
+            .count();
+        let is_consistent = matching as f64 / field_counts.len() as f64 >= MIN_CONSISTENT_SHARE;
+        if is_consistent
+            && columns > 1
+            && best.is_none_or(|(_, _, best_columns)| columns > best_columns)
+        {
+            best = Some((*delimiter, quote, columns));
+        }
+    }
 
-        let has_valid_content = lines
+    best.map(|(delimiter, quote, _)| (delimiter, quote))
+}
+
+/// Picks the quote character that wraps the most fields, if any field is
+/// quoted at all. Rows may mix quoted and bare fields freely.
+fn sniff_quote(lines: &[&str], delimiter: char) -> Option<char> {
+    let mut best: Option<(char, usize)> = None;
+    for quote in QUOTE_CANDIDATES {
+        let quoted_fields = lines
             .iter()
-            .enumerate()
-            .filter(|(index, _)| index % 2 == 0)
-            .all(|(_, line)| content_regex.is_match(line));
+            .flat_map(|line| split_raw_fields(line, delimiter, Some(*quote)))
+            .filter(|field| {
+                let field = field.trim();
+                field.len() >= 2 && field.starts_with(*quote) && field.ends_with(*quote)
+            })
+            .count();
+
+        if quoted_fields > 0 && best.is_none_or(|(_, best_count)| quoted_fields > best_count) {
+            best = Some((*quote, quoted_fields));
+        }
+    }
+
+    best.map(|(quote, _)| quote)
+}
+
+/// Splits delimited text into records at line breaks outside quoted
+/// fields, so quoted cells may span lines. A trailing `\r` is dropped
+/// from each record.
+pub(crate) fn split_records(data: &str, delimiter: char, quote: Option<char>) -> Vec<&str> {
+    match byte_delimiters(delimiter, quote) {
+        Some((delimiter, quote)) => scan_records(data, delimiter, quote),
+        None => split_records_by_char(data, delimiter, quote),
+    }
+}
 
-        has_borders && has_row_separators && has_valid_content
+/// The delimiter and quote as bytes, when `memchr` can look for them: both
+/// ASCII and the delimiter not a space, which stands for any whitespace
+fn byte_delimiters(delimiter: char, quote: Option<char>) -> Option<(u8, Option<u8>)> {
+    let byte = |c: char| u8::try_from(c).ok().filter(u8::is_ascii);
+    let quote = match quote {
+        Some(quote) => Some(byte(quote)?),
+        None => None,
     };
+    Some((
+        byte(delimiter).filter(|delimiter| *delimiter != b' ')?,
+        quote,
+    ))
+}
 
-    if is_ascii_table {
-        return TableType::AsciiTable;
+/// Index of the quote closing a quoted field whose content starts at
+/// `start`, skipping doubled quotes; `None` when it runs to the end.
+fn closing_quote(bytes: &[u8], start: usize, quote: u8) -> Option<usize> {
+    let mut from = start;
+    loop {
+        let index = from + memchr(quote, &bytes[from..])?;
+        if bytes.get(index + 1) == Some(&quote) {
+            from = index + 2;
+        } else {
+            return Some(index);
+        }
     }
+}
 
-    let is_csv = {
-        let has_commas = lines.iter().all(|line| line.contains(','));
+/// `split_records` jumping between line breaks, delimiters and quotes with
+/// `memchr` instead of visiting every character.
+fn scan_records(data: &str, delimiter: u8, quote: Option<u8>) -> Vec<&str> {
+    let bytes = data.as_bytes();
+    let next_special = |from: usize| {
+        let rest = &bytes[from..];
+        match quote {
+            Some(quote) => memchr3(b'\n', delimiter, quote, rest),
+            None => memchr2(b'\n', delimiter, rest),
+        }
+        .map(|index| from + index)
+    };
+    let mut records = Vec::new();
+    let mut record_start = 0;
+    let mut position = 0;
+    let mut at_field_start = true;
 
-        let first_line_columns = lines
-            .first()
-            .map(|line| line.matches(',').count() + 1)
-            .unwrap_or(0);
+    while let Some(index) = next_special(position) {
+        if !data[position..index].chars().all(char::is_whitespace) {
+            at_field_start = false;
+        }
+        position = index + 1;
+        match bytes[index] {
+            b'\n' => {
+                let record = &data[record_start..index];
+                records.push(record.strip_suffix('\r').unwrap_or(record));
+                record_start = index + 1;
+                at_field_start = true;
+            }
+            byte if byte == delimiter => at_field_start = true,
+            // A quote opening a field; others are literal.
+            quote if at_field_start => {
+                position =
+                    closing_quote(bytes, index + 1, quote).map_or(bytes.len(), |end| end + 1);
+                at_field_start = false;
+            }
+            _ => at_field_start = false,
+        }
+    }
 
-        let consistent_columns = lines
-            .iter()
-            .all(|line| line.matches(',').count() + 1 == first_line_columns);
+    if record_start < data.len() {
+        let record = &data[record_start..];
+        records.push(record.strip_suffix('\r').unwrap_or(record));
+    }
+
+    records
+}
+
+fn split_records_by_char(data: &str, delimiter: char, quote: Option<char>) -> Vec<&str> {
+    let mut records = Vec::new();
+    let mut record_start = 0;
+    let mut in_quotes = false;
+    let mut at_field_start = true;
+    let mut chars = data.char_indices().peekable();
+
+    while let Some((index, c)) = chars.next() {
+        if in_quotes {
+            if Some(c) == quote {
+                if chars.peek().map(|(_, next)| *next) == quote {
+                    chars.next();
+                } else {
+                    in_quotes = false;
+                }
+            }
+            continue;
+        }
+
+        if c == '\n' {
+            let record = &data[record_start..index];
+            records.push(record.strip_suffix('\r').unwrap_or(record));
+            record_start = index + 1;
+            at_field_start = true;
+        } else if c == delimiter || (delimiter == ' ' && c.is_whitespace()) {
+            at_field_start = true;
+        } else if at_field_start && Some(c) == quote {
+            in_quotes = true;
+            at_field_start = false;
+        } else if !c.is_whitespace() {
+            at_field_start = false;
+        }
+    }
+
+    if record_start < data.len() {
+        let record = &data[record_start..];
+        records.push(record.strip_suffix('\r').unwrap_or(record));
+    }
+
+    records
+}
+
+/// Splits a delimited line into trimmed, unquoted fields.
+///
+/// A field whose first non-blank character is `quote` runs to the matching
+/// closing quote, may contain the delimiter, and uses a doubled quote as a
+/// literal one. A space delimiter treats runs of whitespace as a single
+/// separator, as in aligned command output.
+pub(crate) fn split_delimited(line: &str, delimiter: char, quote: Option<char>) -> Vec<String> {
+    split_raw_fields(line, delimiter, quote)
+        .into_iter()
+        .map(|field| unquote_field(field, quote))
+        .collect()
+}
+
+/// Splits a line at delimiters outside quotes, keeping fields verbatim.
+pub(crate) fn split_raw_fields(line: &str, delimiter: char, quote: Option<char>) -> Vec<&str> {
+    match byte_delimiters(delimiter, quote) {
+        Some((delimiter, quote)) => scan_raw_fields(line, delimiter, quote),
+        None => split_raw_fields_by_char(line, delimiter, quote),
+    }
+}
+
+/// `split_raw_fields` finding delimiters and closing quotes with `memchr`.
+fn scan_raw_fields(line: &str, delimiter: u8, quote: Option<u8>) -> Vec<&str> {
+    let bytes = line.as_bytes();
+    let mut fields = Vec::new();
+    let mut field_start = 0;
 
-        has_commas && consistent_columns && first_line_columns > 1
+    loop {
+        let rest = &line[field_start..];
+        let blank = rest.len()
+            - rest
+                .trim_start_matches(|c: char| c.is_whitespace() && c != char::from(delimiter))
+                .len();
+        let mut search_start = field_start;
+        if let Some(quote) = quote.filter(|quote| bytes.get(field_start + blank) == Some(quote)) {
+            match closing_quote(bytes, field_start + blank + 1, quote) {
+                Some(end) => search_start = end + 1,
+                None => break,
+            }
+        }
+        match memchr(delimiter, &bytes[search_start..]) {
+            Some(index) => {
+                fields.push(&line[field_start..search_start + index]);
+                field_start = search_start + index + 1;
+            }
+            None => break,
+        }
+    }
+    fields.push(&line[field_start..]);
+
+    fields
+}
+
+fn split_raw_fields_by_char(line: &str, delimiter: char, quote: Option<char>) -> Vec<&str> {
+    let is_separator = |c: char| {
+        if delimiter == ' ' {
+            c.is_whitespace()
+        } else {
+            c == delimiter
+        }
     };
 
-    if is_csv {
-        return TableType::CsvTable;
+    let line = if delimiter == ' ' { line.trim() } else { line };
+    if delimiter == ' ' && line.is_empty() {
+        return Vec::new();
     }
 
-    TableType::Unknown
+    let mut fields = Vec::new();
+    let mut field_start = 0;
+    let mut in_quotes = false;
+    let mut at_field_start = true;
+    let mut chars = line.char_indices().peekable();
+
+    while let Some((index, c)) = chars.next() {
+        if in_quotes {
+            if Some(c) == quote {
+                if chars.peek().map(|(_, next)| *next) == quote {
+                    chars.next();
+                } else {
+                    in_quotes = false;
+                }
+            }
+        } else if is_separator(c) {
+            fields.push(&line[field_start..index]);
+            if delimiter == ' ' {
+                while chars.next_if(|(_, next)| next.is_whitespace()).is_some() {}
+            }
+            field_start = chars.peek().map_or(line.len(), |(next, _)| *next);
+            at_field_start = true;
+            continue;
+        } else if at_field_start && Some(c) == quote {
+            in_quotes = true;
+        }
+
+        if !c.is_whitespace() {
+            at_field_start = false;
+        }
+    }
+    fields.push(&line[field_start..]);
+
+    fields
+}
+
+/// Trims a raw field and strips its quotes, collapsing doubled quotes.
+pub(crate) fn unquote_field(field: &str, quote: Option<char>) -> String {
+    let field = field.trim();
+    let Some(quote) = quote else {
+        return field.to_string();
+    };
+
+    match field.strip_prefix(quote) {
+        Some(inner) => {
+            let inner = inner.strip_suffix(quote).unwrap_or(inner);
+            if !inner.contains(quote) {
+                return inner.to_string();
+            }
+            let doubled: String = [quote, quote].iter().collect();
+            inner.replace(&doubled, &quote.to_string())
+        }
+        None => field.to_string(),
+    }
+}
+
+static ASCII_BORDER: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\+([-=]+\+)+$").unwrap());
+static ASCII_ROW: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\|.*\|$").unwrap());
+
+/// Checks for a grid framed by `+---+` borders with `| a | b |` rows in
+/// between. Border lines may separate every row or only the header.
+fn is_ascii_table(lines: &[&str]) -> bool {
+    let lines: Vec<&str> = lines
+        .iter()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .collect();
+    let (Some(first), Some(last)) = (lines.first(), lines.last()) else {
+        return false;
+    };
+
+    ASCII_BORDER.is_match(first)
+        && ASCII_BORDER.is_match(last)
+        && lines
+            .iter()
+            .all(|line| ASCII_BORDER.is_match(line) || ASCII_ROW.is_match(line))
+        && lines.iter().any(|line| ASCII_ROW.is_match(line))
 }
 
 pub fn parse_table(
@@ -85,53 +493,539 @@ pub fn parse_table(
 ) -> Result<Table, TableError> {
     match table_type {
         TableType::AsciiTable => parse_ascii_table(data, first_line_is_header),
-        TableType::CsvTable => parse_csv_table(data, first_line_is_header),
+        TableType::CsvTable { delimiter, quote } => {
+            parse_csv_table(data, delimiter, quote, first_line_is_header)
+        }
+        TableType::KeyValueTable => parse_key_value_table(data),
+        TableType::PrometheusTable => parse_prometheus_table(data),
         TableType::Unknown => Err(TableError::InvalidTableSize),
     }
 }
 
-fn parse_csv_table(data: &str, first_line_is_header: bool) -> Result<Table, TableError> {
-    let mut lines: Vec<Vec<String>> = data
-        .lines()
-        .map(|line| line.split(',').map(|s| s.trim().to_string()).collect())
+fn build_table(
+    mut lines: Vec<Vec<String>>,
+    first_line_is_header: bool,
+) -> Result<Table, TableError> {
+    if !first_line_is_header {
+        return Table::with_data(lines);
+    }
+    if lines.is_empty() {
+        return Err(TableError::EmptyHeader);
+    }
+
+    let header = lines.remove(0);
+    Table::with_header_and_data(header, lines)
+}
+
+/// Decides whether the first line of the data is a header row
+///
+/// Key/value and Prometheus tables always carry their column names; for
+/// ASCII and CSV tables the first two rows are run through
+/// `first_line_is_header`.
+pub fn detect_header(table_type: TableType, data: &str) -> bool {
+    match table_type {
+        TableType::KeyValueTable | TableType::PrometheusTable => true,
+        TableType::Unknown => false,
+        TableType::AsciiTable | TableType::CsvTable { .. } => {
+            match parse_table(table_type, data, false) {
+                Ok(table) => {
+                    let rows: Vec<Vec<String>> = (0..table.row_count().min(2))
+                        .filter_map(|row_index| table.get(row_index).cloned())
+                        .collect();
+                    first_line_is_header(&rows)
+                }
+                Err(_) => false,
+            }
+        }
+    }
+}
+
+fn parse_csv_table(
+    data: &str,
+    delimiter: char,
+    quote: Option<char>,
+    first_line_is_header: bool,
+) -> Result<Table, TableError> {
+    let lines = csv_rows(data, delimiter, quote)
+        .into_iter()
+        .map(|(_, row)| row)
         .collect();
 
-    let result = if first_line_is_header {
-        let header = lines.remove(0);
-        Table::with_header_and_data(header, lines)?
+    build_table(lines, first_line_is_header)
+}
+
+fn parse_ascii_table(data: &str, first_line_is_header: bool) -> Result<Table, TableError> {
+    let lines = ascii_rows(data).into_iter().map(|(_, row)| row).collect();
+
+    build_table(lines, first_line_is_header)
+}
+
+/// Splits CSV data into rows, keeping each row's source text.
+fn csv_rows(data: &str, delimiter: char, quote: Option<char>) -> Vec<(&str, Vec<String>)> {
+    split_records(data, delimiter, quote)
+        .into_iter()
+        .map(|record| (record, split_delimited(record, delimiter, quote)))
+        .collect()
+}
+
+/// Splits an ASCII table into content rows, keeping each row's source text.
+fn ascii_rows(data: &str) -> Vec<(&str, Vec<String>)> {
+    data.lines()
+        .map(str::trim)
+        .filter(|line| line.starts_with('|'))
+        .map(|line| {
+            let inner = &line[1..];
+            let inner = inner.strip_suffix('|').unwrap_or(inner);
+            (
+                line,
+                inner.split('|').map(|s| s.trim().to_string()).collect(),
+            )
+        })
+        .collect()
+}
+
+/// A data row rejected by `parse_table_lenient`
+#[derive(Debug)]
+pub struct RowError {
+    /// Position among all data rows of the input, rejected ones included
+    pub row_index: usize,
+    /// The row as it appeared in the input
+    pub raw: String,
+    pub error: TableError,
+}
+
+/// Parses a table like `parse_table`, but skips rows that don't fit
+///
+/// For CSV and ASCII tables, rows whose cell count differs from the header
+/// (or from the first row when there is no header) are collected as
+/// `RowError`s instead of failing the whole parse. Key/value and Prometheus
+/// input is parsed as a whole and never yields row errors.
+///
+/// # Returns
+/// * `(Table, Vec<RowError>)` - The rows that parsed, and the ones that didn't
+pub fn parse_table_lenient(
+    table_type: TableType,
+    data: &str,
+    first_line_is_header: bool,
+) -> Result<(Table, Vec<RowError>), TableError> {
+    let mut rows = match table_type {
+        TableType::AsciiTable => ascii_rows(data),
+        TableType::CsvTable { delimiter, quote } => csv_rows(data, delimiter, quote),
+        _ => {
+            return Ok((
+                parse_table(table_type, data, first_line_is_header)?,
+                Vec::new(),
+            ))
+        }
+    };
+
+    let header = if first_line_is_header {
+        if rows.is_empty() {
+            return Err(TableError::EmptyHeader);
+        }
+        Some(rows.remove(0).1)
     } else {
-        Table::with_data(lines)?
+        None
     };
 
-    Ok(result)
+    let expected = match (&header, rows.first()) {
+        (Some(header), _) => header.len(),
+        (None, Some((_, row))) => row.len(),
+        (None, None) => 0,
+    };
+
+    let mut data_rows = Vec::new();
+    let mut errors = Vec::new();
+    for (row_index, (raw, row)) in rows.into_iter().enumerate() {
+        if row.len() == expected {
+            data_rows.push(row);
+        } else {
+            errors.push(RowError {
+                row_index,
+                raw: raw.to_string(),
+                error: TableError::RowLengthMismatch {
+                    row_index,
+                    row_len: row.len(),
+                    header_len: expected,
+                },
+            });
+        }
+    }
+
+    let table = match header {
+        Some(header) => Table::with_header_and_data(header, data_rows)?,
+        None => Table::with_data(data_rows)?,
+    };
+
+    Ok((table, errors))
 }
 
-fn parse_ascii_table(data: &str, first_line_is_header: bool) -> Result<Table, TableError> {
-    let mut lines: Vec<Vec<String>> = data
-        .lines()
-        .enumerate()
-        .filter(|(index, _)| index % 2 == 0)
-        .map(|(_, line)| {
-            line.split('|')
-                .take(line.len() - 1)
-                .skip(1)
-                .map(|s| s.trim().to_string())
+/// Parses key/value records into a table whose columns are the union of all keys.
+///
+/// Two layouts are recognized:
+/// * logfmt, one record per line: `level=info msg="started" port=8080`
+/// * blocks of `Key: value` or `KEY=value` lines separated by blank lines,
+///   as printed by `kubectl describe` or found in `.env` files
+///
+/// Columns keep the order in which keys are first seen; keys missing from
+/// a record are left empty. The key row is always used as the header.
+fn parse_key_value_table(data: &str) -> Result<Table, TableError> {
+    let records = parse_key_value_records(data).ok_or(TableError::InvalidTableSize)?;
+
+    let mut header: Vec<String> = Vec::new();
+    for record in &records {
+        for (key, _) in record {
+            if !header.contains(key) {
+                header.push(key.clone());
+            }
+        }
+    }
+
+    let rows = records
+        .into_iter()
+        .map(|record| {
+            header
+                .iter()
+                .map(|column| {
+                    record
+                        .iter()
+                        .rev()
+                        .find(|(key, _)| key == column)
+                        .map(|(_, value)| value.clone())
+                        .unwrap_or_default()
+                })
                 .collect()
         })
         .collect();
 
-    let result = if first_line_is_header {
-        let header = lines.remove(0);
-        Table::with_header_and_data(header, lines)?
+    Table::with_header_and_data(header, rows)
+}
+
+/// Splits key/value input into records, or `None` if any line doesn't fit
+/// the format.
+///
+/// When every line carries a single pair the input is read as blocks
+/// separated by blank lines, otherwise each line is its own logfmt record.
+fn parse_key_value_records(data: &str) -> Option<Vec<Vec<(String, String)>>> {
+    let mut lines = Vec::new();
+    for line in data.lines() {
+        if line.trim().is_empty() {
+            lines.push(None);
+        } else if line.starts_with(char::is_whitespace) {
+            lines.push(Some(KeyValueLine::Continuation(line.trim())));
+        } else if let Some(pair) = parse_colon_pair(line) {
+            lines.push(Some(KeyValueLine::Pairs(vec![pair])));
+        } else {
+            lines.push(Some(KeyValueLine::Pairs(parse_logfmt_line(line)?)));
+        }
+    }
+
+    let is_block_layout = lines.iter().flatten().all(|line| match line {
+        KeyValueLine::Pairs(pairs) => pairs.len() == 1,
+        KeyValueLine::Continuation(_) => true,
+    });
+
+    let mut records: Vec<Vec<(String, String)>> = Vec::new();
+    let mut current: Vec<(String, String)> = Vec::new();
+
+    for line in lines {
+        match line {
+            None => {
+                if !current.is_empty() {
+                    records.push(std::mem::take(&mut current));
+                }
+            }
+            Some(KeyValueLine::Continuation(text)) => {
+                // Indented lines only make sense as part of a block value.
+                let (_, value) = current.last_mut().filter(|_| is_block_layout)?;
+                if !value.is_empty() {
+                    value.push(' ');
+                }
+                value.push_str(text);
+            }
+            Some(KeyValueLine::Pairs(pairs)) => {
+                current.extend(pairs);
+                if !is_block_layout {
+                    records.push(std::mem::take(&mut current));
+                }
+            }
+        }
+    }
+
+    if !current.is_empty() {
+        records.push(current);
+    }
+
+    if records.is_empty() {
+        return None;
+    }
+
+    Some(records)
+}
+
+enum KeyValueLine<'a> {
+    Pairs(Vec<(String, String)>),
+    Continuation(&'a str),
+}
+
+/// Parses a `Key: value` line, where the key may contain spaces.
+fn parse_colon_pair(line: &str) -> Option<(String, String)> {
+    static COLON_REGEX: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"^([A-Za-z_][\w .\-/]*?):(?:\s+(.*))?$").unwrap());
+    let captures = COLON_REGEX.captures(line)?;
+
+    let key = captures[1].trim().to_string();
+    let value = captures
+        .get(2)
+        .map_or(String::new(), |value| value.as_str().trim().to_string());
+
+    Some((key, value))
+}
+
+/// Parses a logfmt line (`key=value key2="quoted value"`) into pairs.
+fn parse_logfmt_line(line: &str) -> Option<Vec<(String, String)>> {
+    static KEY_REGEX: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"^[A-Za-z_][\w.\-/]*$").unwrap());
+    let mut pairs = Vec::new();
+    let mut chars = line.trim().chars().peekable();
+
+    while chars.peek().is_some() {
+        let mut key = String::new();
+        while let Some(c) = chars.next_if(|c| *c != '=' && !c.is_whitespace()) {
+            key.push(c);
+        }
+        if chars.next() != Some('=') || !KEY_REGEX.is_match(&key) {
+            return None;
+        }
+
+        let mut value = String::new();
+        if chars.peek() == Some(&'"') {
+            chars.next();
+            let mut closed = false;
+            while let Some(c) = chars.next() {
+                match c {
+                    '\\' => value.extend(chars.next()),
+                    '"' => {
+                        closed = true;
+                        break;
+                    }
+                    _ => value.push(c),
+                }
+            }
+            if !closed || chars.peek().is_some_and(|c| !c.is_whitespace()) {
+                return None;
+            }
+        } else {
+            while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+                value.push(c);
+            }
+        }
+
+        pairs.push((key, value));
+
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+    }
+
+    if pairs.is_empty() {
+        return None;
+    }
+
+    Some(pairs)
+}
+
+/// Column names reserved for the sample itself in Prometheus tables.
+pub const PROMETHEUS_NAME_COLUMN: &str = "name";
+pub const PROMETHEUS_VALUE_COLUMN: &str = "value";
+pub const PROMETHEUS_TIMESTAMP_COLUMN: &str = "timestamp";
+/// Prefix given to labels that clash with the reserved column names.
+pub const PROMETHEUS_LABEL_PREFIX: &str = "label_";
+
+/// Whether a label's column gets the `label_` prefix: reserved names, and
+/// labels that would otherwise read as a prefixed one, so `label_name`
+/// becomes `label_label_name` next to `name` as `label_name`
+fn is_prefixed_label(label: &str) -> bool {
+    [
+        PROMETHEUS_NAME_COLUMN,
+        PROMETHEUS_VALUE_COLUMN,
+        PROMETHEUS_TIMESTAMP_COLUMN,
+    ]
+    .contains(&label)
+        || label
+            .strip_prefix(PROMETHEUS_LABEL_PREFIX)
+            .is_some_and(is_prefixed_label)
+}
+
+/// The column a Prometheus label is read into
+pub fn prometheus_column_name(label: &str) -> String {
+    if is_prefixed_label(label) {
+        format!("{}{}", PROMETHEUS_LABEL_PREFIX, label)
     } else {
-        Table::with_data(lines)?
-    };
+        label.to_string()
+    }
+}
+
+/// The label a column is written as, undoing `prometheus_column_name`
+pub fn prometheus_label(column: &str) -> &str {
+    match column.strip_prefix(PROMETHEUS_LABEL_PREFIX) {
+        Some(label) if is_prefixed_label(label) => label,
+        _ => column,
+    }
+}
+
+/// Parses Prometheus exposition text into a table.
+///
+/// Every sample becomes a row with a `name` column, one column per label
+/// (in first-seen order), a `value` column and, if any sample carries one,
+/// a `timestamp` column. `# HELP`/`# TYPE` comments are skipped. Labels that
+/// clash with the reserved column names get a `label_` prefix, see
+/// `prometheus_column_name`.
+fn parse_prometheus_table(data: &str) -> Result<Table, TableError> {
+    let samples = parse_prometheus_samples(data).ok_or(TableError::InvalidTableSize)?;
+
+    let mut labels: Vec<String> = Vec::new();
+    for sample in &samples {
+        for (label, _) in &sample.labels {
+            if !labels.contains(label) {
+                labels.push(label.clone());
+            }
+        }
+    }
+    let has_timestamps = samples.iter().any(|sample| sample.timestamp.is_some());
+
+    let mut header = vec![PROMETHEUS_NAME_COLUMN.to_string()];
+    header.extend(labels.iter().map(|label| prometheus_column_name(label)));
+    header.push(PROMETHEUS_VALUE_COLUMN.to_string());
+    if has_timestamps {
+        header.push(PROMETHEUS_TIMESTAMP_COLUMN.to_string());
+    }
+
+    let rows = samples
+        .into_iter()
+        .map(|sample| {
+            let mut row = vec![sample.name];
+            row.extend(labels.iter().map(|label| {
+                sample
+                    .labels
+                    .iter()
+                    .find(|(key, _)| key == label)
+                    .map(|(_, value)| value.clone())
+                    .unwrap_or_default()
+            }));
+            row.push(sample.value);
+            if has_timestamps {
+                row.push(sample.timestamp.unwrap_or_default());
+            }
+            row
+        })
+        .collect();
+
+    Table::with_header_and_data(header, rows)
+}
 
-    Ok(result)
+struct PrometheusSample {
+    name: String,
+    labels: Vec<(String, String)>,
+    value: String,
+    timestamp: Option<String>,
+}
+
+/// Reads all samples from Prometheus exposition text, or `None` if a line
+/// is neither a comment nor a valid sample.
+fn parse_prometheus_samples(data: &str) -> Option<Vec<PrometheusSample>> {
+    static SAMPLE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(r"^([a-zA-Z_:][a-zA-Z0-9_:]*)(\{.*\})?\s+(\S+)(?:\s+(-?\d+))?$").unwrap()
+    });
+
+    let mut samples = Vec::new();
+    for line in data.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let captures = SAMPLE_REGEX.captures(line)?;
+        let value = captures[3].to_string();
+        if value.parse::<f64>().is_err() {
+            return None;
+        }
+
+        let labels = match captures.get(2) {
+            Some(labels) => parse_prometheus_labels(labels.as_str())?,
+            None => Vec::new(),
+        };
+
+        samples.push(PrometheusSample {
+            name: captures[1].to_string(),
+            labels,
+            value,
+            timestamp: captures
+                .get(4)
+                .map(|timestamp| timestamp.as_str().to_string()),
+        });
+    }
+
+    if samples.is_empty() {
+        return None;
+    }
+
+    Some(samples)
+}
+
+/// Parses a `{label="value",...}` block, unescaping `\\`, `\"` and `\n`.
+fn parse_prometheus_labels(block: &str) -> Option<Vec<(String, String)>> {
+    static LABEL_REGEX: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"^[a-zA-Z_][a-zA-Z0-9_]*$").unwrap());
+    let mut labels = Vec::new();
+    let mut chars = block
+        .strip_prefix('{')?
+        .strip_suffix('}')?
+        .chars()
+        .peekable();
+
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        if chars.peek().is_none() {
+            break;
+        }
+
+        let mut label = String::new();
+        while let Some(c) = chars.next_if(|c| *c != '=' && !c.is_whitespace()) {
+            label.push(c);
+        }
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        if !LABEL_REGEX.is_match(&label) || chars.next() != Some('=') {
+            return None;
+        }
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        if chars.next() != Some('"') {
+            return None;
+        }
+
+        let mut value = String::new();
+        loop {
+            match chars.next()? {
+                '\\' => match chars.next()? {
+                    'n' => value.push('\n'),
+                    c => value.push(c),
+                },
+                '"' => break,
+                c => value.push(c),
+            }
+        }
+        labels.push((label, value));
+
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        match chars.next() {
+            Some(',') => continue,
+            None => break,
+            Some(_) => return None,
+        }
+    }
+
+    Some(labels)
 }
 
 /// heuristics to detect if first line is header or not
-pub fn first_line_is_header(lines: &Vec<Vec<String>>) -> bool {
+pub fn first_line_is_header(lines: &[Vec<String>]) -> bool {
     if lines.len() < 2 {
         return false;
     }
@@ -159,3 +1053,305 @@ pub fn first_line_is_header(lines: &Vec<Vec<String>>) -> bool {
             || header.chars().all(|c| c.is_uppercase())
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    const ALL_TABLE_TYPES: &[TableType] = &[
+        TableType::AsciiTable,
+        TableType::CsvTable {
+            delimiter: ',',
+            quote: Some('"'),
+        },
+        TableType::CsvTable {
+            delimiter: ' ',
+            quote: Some('\''),
+        },
+        TableType::KeyValueTable,
+        TableType::PrometheusTable,
+        TableType::Unknown,
+    ];
+
+    proptest! {
+        // The memchr scanners must split exactly like the character loops.
+        #[test]
+        fn test_scanners_match_char_loops(data in "([,;\t \"'a\r\n\u{a0}é]{0,6}){0,16}") {
+            for (delimiter, quote) in [(',', Some('"')), (';', Some('\'')), ('\t', None)] {
+                prop_assert_eq!(
+                    split_records(&data, delimiter, quote),
+                    split_records_by_char(&data, delimiter, quote)
+                );
+                prop_assert_eq!(
+                    split_raw_fields(&data, delimiter, quote),
+                    split_raw_fields_by_char(&data, delimiter, quote)
+                );
+            }
+        }
+
+        // Parsing untrusted input must fail with an error, never a panic.
+        #[test]
+        fn test_arbitrary_input_never_panics(bytes in proptest::collection::vec(any::<u8>(), 0..512)) {
+            let data = String::from_utf8_lossy(&bytes);
+            let detected = deduct_table_type(&data);
+            let _ = detect_header(detected, &data);
+            for table_type in ALL_TABLE_TYPES.iter().copied().chain([detected]) {
+                let _ = parse_table(table_type, &data, true);
+                let _ = parse_table(table_type, &data, false);
+            }
+        }
+
+        #[test]
+        fn test_table_like_input_never_panics(data in "([|+,;\t \"'=:{}#a-z0-9-]{0,20}\n?){0,12}") {
+            let detected = deduct_table_type(&data);
+            let _ = detect_header(detected, &data);
+            for table_type in ALL_TABLE_TYPES.iter().copied().chain([detected]) {
+                let _ = parse_table(table_type, &data, true);
+                let _ = parse_table(table_type, &data, false);
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_ascii_table() {
+        let data =
+            "+----+-------+\n| id | name  |\n+----+-------+\n| 1  | alice |\n+----+-------+\n";
+        let table = parse_table(TableType::AsciiTable, data, true).unwrap();
+        assert_eq!(table.header(), vec!["id", "name"]);
+        assert_eq!(table.get_value(0, "name").unwrap(), "alice");
+    }
+
+    #[test]
+    fn test_detect_ascii_tables() {
+        let data = "+----+-------+\n| id | name  |\n+----+-------+\n| 1  | alice |\n| 2  | bob   |\n+----+-------+\n";
+        assert_eq!(deduct_table_type(data), TableType::AsciiTable);
+        assert!(detect_header(TableType::AsciiTable, data));
+    }
+
+    #[test]
+    fn test_single_column_is_unknown() {
+        assert_eq!(deduct_table_type("name\nalice\nbob\n"), TableType::Unknown);
+    }
+
+    #[test]
+    fn test_forced_csv_reads_single_column() {
+        assert_eq!(
+            InputFormat::Csv.table_type("name\nalice\nbob\n"),
+            TableType::CsvTable {
+                delimiter: ',',
+                quote: None
+            }
+        );
+    }
+
+    const RAGGED_CSV: &str = "id,name\n1,alice\n2,bob,extra\n3,carol\n4\n";
+
+    const PLAIN_CSV: TableType = TableType::CsvTable {
+        delimiter: ',',
+        quote: None,
+    };
+
+    #[test]
+    fn test_strict_parse_rejects_bad_rows() {
+        assert!(matches!(
+            parse_table(PLAIN_CSV, RAGGED_CSV, true),
+            Err(TableError::RowLengthMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_lenient_parse_keeps_good_rows() {
+        let (table, _) = parse_table_lenient(PLAIN_CSV, RAGGED_CSV, true).unwrap();
+        assert_eq!(table.row_count(), 2);
+        assert_eq!(table.get_value(1, "name").unwrap(), "carol");
+    }
+
+    #[test]
+    fn test_lenient_parse_collects_bad_rows() {
+        let (_, errors) = parse_table_lenient(PLAIN_CSV, RAGGED_CSV, true).unwrap();
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].row_index, 1);
+        assert_eq!(errors[0].raw, "2,bob,extra");
+        assert_eq!(errors[1].raw, "4");
+    }
+
+    #[test]
+    fn test_empty_input_with_header_is_an_error() {
+        for table_type in ALL_TABLE_TYPES {
+            assert!(parse_table(*table_type, "", true).is_err());
+        }
+    }
+
+    #[test]
+    fn test_sniff_delimiters() {
+        let detect = |data: &str| match deduct_table_type(data) {
+            TableType::CsvTable { delimiter, .. } => Some(delimiter),
+            _ => None,
+        };
+
+        assert_eq!(detect("a,b,c\n1,2,3\n"), Some(','));
+        assert_eq!(detect("a\tb\n1\t2\n"), Some('\t'));
+        assert_eq!(detect("price;qty;note\n1,5;2;x\n2,0;1;y\n"), Some(';'));
+        assert_eq!(detect("id|name\n1|alice\n"), Some('|'));
+        assert_eq!(detect("PID   CMD\n1     init\n42    bash\n"), Some(' '));
+        assert_eq!(detect("single\ncolumn\n"), None);
+    }
+
+    #[test]
+    fn test_parse_delimiter() {
+        assert_eq!(parse_delimiter(";").unwrap(), ';');
+    }
+
+    #[test]
+    fn test_parse_escaped_delimiter() {
+        assert_eq!(parse_delimiter("\\t").unwrap(), '\t');
+    }
+
+    #[test]
+    fn test_invalid_delimiter() {
+        assert!(matches!(
+            parse_delimiter(";;"),
+            Err(TableError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn test_forced_delimiter() {
+        let data = "a;b\n\"1;2\";3\n";
+        let table_type = delimited_table_type(data, ';');
+        assert_eq!(
+            table_type,
+            TableType::CsvTable {
+                delimiter: ';',
+                quote: Some('"')
+            }
+        );
+        let table = parse_table(table_type, data, true).unwrap();
+        assert_eq!(table.get_value(0, "a").unwrap(), "1;2");
+    }
+
+    const MIXED_QUOTING: &str =
+        "id,name,city\n1,\"Smith, John\",Oslo\n2,Jane,\"Paris\"\n3, \"a \"\"b\"\"\" ,Rome\n";
+
+    #[test]
+    fn test_sniff_double_quotes() {
+        assert_eq!(
+            deduct_table_type(MIXED_QUOTING),
+            TableType::CsvTable {
+                delimiter: ',',
+                quote: Some('"')
+            }
+        );
+    }
+
+    #[test]
+    fn test_mixed_quoting() {
+        let table = parse_table(deduct_table_type(MIXED_QUOTING), MIXED_QUOTING, true).unwrap();
+        assert_eq!(table.get_value(0, "name").unwrap(), "Smith, John");
+        assert_eq!(table.get_value(1, "city").unwrap(), "Paris");
+    }
+
+    #[test]
+    fn test_escaped_quotes_and_padding() {
+        let table = parse_table(deduct_table_type(MIXED_QUOTING), MIXED_QUOTING, true).unwrap();
+        assert_eq!(table.get_value(2, "name").unwrap(), "a \"b\"");
+    }
+
+    #[test]
+    fn test_sniff_single_quotes() {
+        assert_eq!(
+            deduct_table_type("id;note\n1;'a;b'\n2;plain\n"),
+            TableType::CsvTable {
+                delimiter: ';',
+                quote: Some('\'')
+            }
+        );
+    }
+
+    const LOGFMT: &str = "level=info msg=\"server started\" port=8080\nlevel=warn msg=retry\n";
+
+    const KEY_VALUE_BLOCKS: &str = "Name: web-1\nStatus: Running\nLabels: app=web\n        tier=front\n\nName: web-2\nNode: node-b\n";
+
+    #[test]
+    fn test_detect_logfmt_lines() {
+        assert!(matches!(
+            deduct_table_type(LOGFMT),
+            TableType::KeyValueTable
+        ));
+    }
+
+    #[test]
+    fn test_parse_logfmt_lines() {
+        let table = parse_table(TableType::KeyValueTable, LOGFMT, true).unwrap();
+        assert_eq!(table.row_count(), 2);
+        assert_eq!(table.column_count(), 3);
+        assert_eq!(table.get_value(0, "msg").unwrap(), "server started");
+    }
+
+    #[test]
+    fn test_logfmt_missing_keys_are_empty() {
+        let table = parse_table(TableType::KeyValueTable, LOGFMT, true).unwrap();
+        assert_eq!(table.get_value(1, "port").unwrap(), "");
+    }
+
+    #[test]
+    fn test_detect_key_value_blocks() {
+        assert!(matches!(
+            deduct_table_type(KEY_VALUE_BLOCKS),
+            TableType::KeyValueTable
+        ));
+    }
+
+    #[test]
+    fn test_parse_key_value_blocks() {
+        let table = parse_table(TableType::KeyValueTable, KEY_VALUE_BLOCKS, true).unwrap();
+        assert_eq!(table.row_count(), 2);
+        assert_eq!(table.column_count(), 4);
+        assert_eq!(table.get_value(1, "Node").unwrap(), "node-b");
+        assert_eq!(table.get_value(1, "Status").unwrap(), "");
+    }
+
+    #[test]
+    fn test_key_value_continuation_lines() {
+        let table = parse_table(TableType::KeyValueTable, KEY_VALUE_BLOCKS, true).unwrap();
+        assert_eq!(table.get_value(0, "Labels").unwrap(), "app=web tier=front");
+    }
+
+    const PROMETHEUS: &str = "# HELP http_requests_total Total requests.\n\
+                              # TYPE http_requests_total counter\n\
+                              http_requests_total{method=\"post\",code=\"200\"} 1027 1395066363000\n\
+                              http_requests_total{method=\"get\",name=\"a, b\"} 3\n\
+                              process_open_fds 12.5\n";
+
+    #[test]
+    fn test_detect_prometheus_metrics() {
+        assert!(matches!(
+            deduct_table_type(PROMETHEUS),
+            TableType::PrometheusTable
+        ));
+    }
+
+    #[test]
+    fn test_parse_prometheus_metrics() {
+        let table = parse_table(TableType::PrometheusTable, PROMETHEUS, true).unwrap();
+        assert_eq!(table.row_count(), 3);
+        assert_eq!(table.column_count(), 6);
+        assert_eq!(table.get_value(0, "code").unwrap(), "200");
+        assert_eq!(table.get_value(2, "value").unwrap(), "12.5");
+    }
+
+    #[test]
+    fn test_prometheus_label_named_name() {
+        let table = parse_table(TableType::PrometheusTable, PROMETHEUS, true).unwrap();
+        assert_eq!(table.get_value(1, "label_name").unwrap(), "a, b");
+        assert_eq!(table.get_value(2, "name").unwrap(), "process_open_fds");
+    }
+
+    #[test]
+    fn test_prometheus_missing_timestamp() {
+        let table = parse_table(TableType::PrometheusTable, PROMETHEUS, true).unwrap();
+        assert_eq!(table.get_value(2, "timestamp").unwrap(), "");
+    }
+}
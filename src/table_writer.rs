@@ -0,0 +1,796 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use clap::ValueEnum;
+
+use crate::table::{Table, TableError};
+use crate::table_parser::{
+    prometheus_label, RowError, TableType, PROMETHEUS_NAME_COLUMN, PROMETHEUS_TIMESTAMP_COLUMN,
+    PROMETHEUS_VALUE_COLUMN,
+};
+
+/// Formats tables can be written in with `--to`
+#[derive(Debug, Clone, Copy, PartialEq, Default, ValueEnum)]
+pub enum OutputFormat {
+    #[default]
+    Csv,
+    Ascii,
+    Prometheus,
+    /// Arrow IPC stream with typed columns, for Arrow-aware consumers
+    #[cfg(feature = "arrow")]
+    ArrowStream,
+}
+
+/// Settings writers may honour
+#[derive(Debug, Clone, PartialEq)]
+pub struct WriteOptions {
+    /// Field delimiter of delimited output
+    pub delimiter: char,
+    /// Row set apart after the data, one cell per column
+    pub footer: Option<Vec<String>>,
+    /// ANSI SGR parameters of highlighted cells by row and column index,
+    /// shown by the ASCII writer
+    pub highlighted: HashMap<(usize, usize), String>,
+    /// Rows shown by the ASCII writer between border lines, each after
+    /// the number of data rows it comes with
+    pub subtotals: Vec<(usize, Vec<String>)>,
+}
+
+impl Default for WriteOptions {
+    fn default() -> Self {
+        WriteOptions {
+            delimiter: ',',
+            footer: None,
+            highlighted: HashMap::new(),
+            subtotals: Vec::new(),
+        }
+    }
+}
+
+/// Renders a table as bytes, text for every format but Arrow
+pub type TableWriter = fn(&Table, &WriteOptions) -> Result<Vec<u8>, TableError>;
+
+impl OutputFormat {
+    /// Looks up the writer for this format
+    ///
+    /// A footer follows a blank line in delimited output, a border in an
+    /// ASCII grid and comes as `# footer` comments in Prometheus text, so
+    /// readers of the data rows don't take it for one. Arrow streams
+    /// carry it in the schema's `footer` metadata.
+    pub fn writer(self) -> TableWriter {
+        match self {
+            OutputFormat::Csv => |table, options| {
+                let mut output = write_delimited(table, options.delimiter);
+                if let Some(footer) = &options.footer {
+                    output.push('\n');
+                    push_delimited_record(
+                        &mut output,
+                        footer.iter().map(String::as_str),
+                        options.delimiter,
+                    );
+                }
+                Ok(output.into_bytes())
+            },
+            OutputFormat::Ascii => {
+                |table, options| Ok(write_ascii_with(table, options).into_bytes())
+            }
+            OutputFormat::Prometheus => |table, options| {
+                let mut output = write_prometheus(table)?;
+                for cell in options.footer.iter().flatten() {
+                    if !cell.is_empty() {
+                        output.push_str(&format!("# footer {}\n", cell.replace('\n', " ")));
+                    }
+                }
+                Ok(output.into_bytes())
+            },
+            #[cfg(feature = "arrow")]
+            OutputFormat::ArrowStream => |table, options| {
+                crate::arrow_stream::write_arrow_stream(table, options.footer.as_deref())
+            },
+        }
+    }
+
+    /// File extension of this format, without the dot
+    pub fn extension(self) -> &'static str {
+        match self {
+            OutputFormat::Csv => "csv",
+            OutputFormat::Ascii => "txt",
+            OutputFormat::Prometheus => "prom",
+            #[cfg(feature = "arrow")]
+            OutputFormat::ArrowStream => "arrows",
+        }
+    }
+
+    /// The format an output file's extension names, if it names one
+    ///
+    /// `.txt` is left out, since text files are often meant as CSV.
+    pub fn from_extension(path: &Path) -> Option<OutputFormat> {
+        match path.extension()?.to_str()? {
+            "csv" => Some(OutputFormat::Csv),
+            "prom" => Some(OutputFormat::Prometheus),
+            #[cfg(feature = "arrow")]
+            "arrows" => Some(OutputFormat::ArrowStream),
+            _ => None,
+        }
+    }
+
+    /// The format that writes back a table read as `table_type`, with the
+    /// delimiter of delimited input; `None` if no writer matches it
+    pub fn matching(table_type: &TableType) -> Option<(OutputFormat, Option<char>)> {
+        match table_type {
+            TableType::CsvTable { delimiter, .. } => Some((OutputFormat::Csv, Some(*delimiter))),
+            TableType::AsciiTable => Some((OutputFormat::Ascii, None)),
+            TableType::PrometheusTable => Some((OutputFormat::Prometheus, None)),
+            TableType::KeyValueTable | TableType::Unknown => None,
+        }
+    }
+}
+
+/// A file given to `--output`, with the format named for it
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutputTarget {
+    pub path: PathBuf,
+    pub format: Option<OutputFormat>,
+}
+
+impl OutputTarget {
+    /// Parses `PATH[:FORMAT]`
+    pub fn parse(value: &str) -> Result<Self, TableError> {
+        if let Some((path, format)) = value.rsplit_once(':') {
+            if let Ok(format) = OutputFormat::from_str(format, true) {
+                if path.is_empty() {
+                    return Err(TableError::InvalidArgument(format!(
+                        "'{}' names no file",
+                        value
+                    )));
+                }
+                return Ok(OutputTarget {
+                    path: PathBuf::from(path),
+                    format: Some(format),
+                });
+            }
+        }
+        Ok(OutputTarget {
+            path: PathBuf::from(value),
+            format: None,
+        })
+    }
+}
+
+/// Renders a table as CSV
+///
+/// Fields are quoted only when they contain a comma, quote or line break,
+/// or start or end with whitespace the parser would otherwise trim. Every
+/// record ends with `\n`, so equal tables always produce identical bytes.
+pub fn write_csv(table: &Table) -> String {
+    write_delimited(table, ',')
+}
+
+/// Renders a table as delimited text, quoting like `write_csv`
+///
+/// # Arguments
+/// * `table` - Table to render
+/// * `delimiter` - Field delimiter, e.g. `;` or `\t`
+///
+/// # Returns
+/// * `String` - One record per line
+pub fn write_delimited(table: &Table, delimiter: char) -> String {
+    let mut output = String::new();
+
+    let header = table.header();
+    if !header.is_empty() {
+        push_delimited_record(&mut output, header, delimiter);
+    }
+    for row in table.rows() {
+        push_delimited_record(&mut output, row.iter().map(String::as_str), delimiter);
+    }
+
+    output
+}
+
+/// Renders a table as an ASCII grid for reading in a terminal
+///
+/// Columns are padded to their widest cell and the header, if any, is
+/// set off by a border line. The grid reads back as an ASCII table, but
+/// it is meant for people: line breaks in cells become spaces, and cells
+/// with pipes or outer whitespace don't survive a round trip.
+pub fn write_ascii(table: &Table) -> String {
+    write_ascii_with(table, &WriteOptions::default())
+}
+
+/// Renders a table like `write_ascii`, with subtotal and footer rows
+/// between border lines and highlighted cells wrapped in ANSI escapes
+pub fn write_ascii_with(table: &Table, options: &WriteOptions) -> String {
+    let header: Vec<String> = table.header().into_iter().map(ascii_cell).collect();
+    let rows: Vec<Vec<String>> = table
+        .rows()
+        .iter()
+        .map(|row| row.iter().map(|cell| ascii_cell(cell)).collect())
+        .collect();
+    let footer: Option<Vec<String>> = options
+        .footer
+        .as_ref()
+        .map(|footer| footer.iter().map(|cell| ascii_cell(cell)).collect());
+    let subtotals: Vec<(usize, Vec<String>)> = options
+        .subtotals
+        .iter()
+        .map(|(end, row)| (*end, row.iter().map(|cell| ascii_cell(cell)).collect()))
+        .collect();
+
+    let mut widths = vec![0; table.column_count()];
+    let summaries = subtotals.iter().map(|(_, row)| row).chain(&footer);
+    for row in std::iter::once(&header).chain(&rows).chain(summaries) {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    if widths.is_empty() {
+        return String::new();
+    }
+
+    let border: String = widths
+        .iter()
+        .map(|width| format!("+{}", "-".repeat(width + 2)))
+        .chain(["+\n".to_string()])
+        .collect();
+    // Data rows pass their index to look up highlighted cells
+    let push_row = |output: &mut String, row: &[String], row_index: Option<usize>| {
+        for (column_index, (cell, width)) in row.iter().zip(&widths).enumerate() {
+            let padding = " ".repeat(width - cell.chars().count());
+            match row_index
+                .and_then(|row_index| options.highlighted.get(&(row_index, column_index)))
+            {
+                Some(style) => {
+                    output.push_str(&format!("| \x1b[{}m{}\x1b[0m{} ", style, cell, padding))
+                }
+                None => output.push_str(&format!("| {}{} ", cell, padding)),
+            }
+        }
+        output.push_str("|\n");
+    };
+
+    let mut output = border.clone();
+    if !header.is_empty() {
+        push_row(&mut output, &header, None);
+        output.push_str(&border);
+    }
+    let mut subtotals = subtotals.iter().peekable();
+    let mut after_subtotal = false;
+    for (row_index, row) in rows.iter().enumerate() {
+        if after_subtotal {
+            output.push_str(&border);
+            after_subtotal = false;
+        }
+        push_row(&mut output, row, Some(row_index));
+        while let Some((_, subtotal)) = subtotals.next_if(|(end, _)| *end == row_index + 1) {
+            output.push_str(&border);
+            push_row(&mut output, subtotal, None);
+            after_subtotal = true;
+        }
+    }
+    // Only a table without rows has subtotals left, its total
+    for (_, subtotal) in subtotals {
+        if !output.ends_with(&border) {
+            output.push_str(&border);
+        }
+        push_row(&mut output, subtotal, None);
+    }
+    output.push_str(&border);
+    if let Some(footer) = &footer {
+        push_row(&mut output, footer, None);
+        output.push_str(&border);
+    }
+    output
+}
+
+fn ascii_cell(cell: &str) -> String {
+    cell.replace("\r\n", " ").replace(['\n', '\r'], " ")
+}
+
+/// Renders rows rejected by lenient parsing as CSV for triage
+///
+/// Each rejected row becomes a record with the input `file`, its data
+/// `row` index, the `error` reason and the `raw` source text, which can
+/// be fixed and re-ingested as is.
+pub fn write_rejected_rows<'a>(
+    rejected: impl IntoIterator<Item = (&'a str, &'a RowError)>,
+) -> String {
+    let mut output = String::new();
+    push_csv_record(&mut output, ["file", "row", "error", "raw"]);
+
+    for (file, row_error) in rejected {
+        let row = row_error.row_index.to_string();
+        let error = row_error.error.to_string();
+        push_csv_record(
+            &mut output,
+            [file, row.as_str(), error.as_str(), row_error.raw.as_str()],
+        );
+    }
+
+    output
+}
+
+fn push_csv_record<'a>(output: &mut String, fields: impl IntoIterator<Item = &'a str>) {
+    push_delimited_record(output, fields, ',');
+}
+
+fn push_delimited_record<'a>(
+    output: &mut String,
+    fields: impl IntoIterator<Item = &'a str>,
+    delimiter: char,
+) {
+    let fields: Vec<String> = fields
+        .into_iter()
+        .map(|field| quote_field(field, delimiter))
+        .collect();
+    output.push_str(&fields.join(&delimiter.to_string()));
+    output.push('\n');
+}
+
+fn quote_field(field: &str, delimiter: char) -> String {
+    let has_outer_whitespace =
+        field.starts_with(char::is_whitespace) || field.ends_with(char::is_whitespace);
+
+    if has_outer_whitespace || field.contains([delimiter, '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Renders a table as Prometheus exposition text
+///
+/// The table must have `name` and `value` columns; an optional `timestamp`
+/// column is passed through and every other column becomes a label. Empty
+/// label values are omitted, as Prometheus treats them as absent. Samples
+/// are grouped by metric name in order of first appearance.
+///
+/// # Arguments
+/// * `table` - Table with name/label/value columns
+///
+/// # Returns
+/// * `String` - Exposition text, one sample per line
+pub fn write_prometheus(table: &Table) -> Result<String, TableError> {
+    let name_index = table
+        .column_index(PROMETHEUS_NAME_COLUMN)
+        .ok_or_else(|| TableError::MissingColumn(PROMETHEUS_NAME_COLUMN.to_string()))?;
+    let value_index = table
+        .column_index(PROMETHEUS_VALUE_COLUMN)
+        .ok_or_else(|| TableError::MissingColumn(PROMETHEUS_VALUE_COLUMN.to_string()))?;
+    let timestamp_index = table.column_index(PROMETHEUS_TIMESTAMP_COLUMN);
+
+    let labels: Vec<(usize, String)> = table
+        .header()
+        .into_iter()
+        .enumerate()
+        .filter(|(index, _)| {
+            *index != name_index && *index != value_index && Some(*index) != timestamp_index
+        })
+        .map(|(index, column)| (index, prometheus_label_name(column)))
+        .collect();
+
+    let mut groups: Vec<(String, Vec<String>)> = Vec::new();
+
+    for row_index in 0..table.row_count() {
+        let row = table
+            .get(row_index)
+            .ok_or(TableError::InvalidRowIndex(row_index))?;
+
+        let value = &row[value_index];
+        if value.parse::<f64>().is_err() {
+            return Err(TableError::InvalidCell {
+                row_index,
+                column: PROMETHEUS_VALUE_COLUMN.to_string(),
+            });
+        }
+
+        let name = prometheus_metric_name(&row[name_index]);
+        if name.is_empty() {
+            return Err(TableError::InvalidCell {
+                row_index,
+                column: PROMETHEUS_NAME_COLUMN.to_string(),
+            });
+        }
+
+        let label_pairs: Vec<String> = labels
+            .iter()
+            .filter(|(index, _)| !row[*index].is_empty())
+            .map(|(index, label)| format!("{}=\"{}\"", label, escape_label_value(&row[*index])))
+            .collect();
+
+        let mut line = name.clone();
+        if !label_pairs.is_empty() {
+            line.push_str(&format!("{{{}}}", label_pairs.join(",")));
+        }
+        line.push(' ');
+        line.push_str(value);
+        if let Some(timestamp) = timestamp_index.map(|index| &row[index]) {
+            if !timestamp.is_empty() {
+                line.push(' ');
+                line.push_str(timestamp);
+            }
+        }
+
+        match groups.iter_mut().find(|(group, _)| *group == name) {
+            Some((_, lines)) => lines.push(line),
+            None => groups.push((name, vec![line])),
+        }
+    }
+
+    let mut output = String::new();
+    for line in groups.into_iter().flat_map(|(_, lines)| lines) {
+        output.push_str(&line);
+        output.push('\n');
+    }
+
+    Ok(output)
+}
+
+/// Maps a column name to a label name, undoing the prefix the parser adds
+/// to labels that clash with reserved columns.
+fn prometheus_label_name(column: &str) -> String {
+    sanitize_identifier(prometheus_label(column), false)
+}
+
+fn prometheus_metric_name(name: &str) -> String {
+    sanitize_identifier(name.trim(), true)
+}
+
+/// Replaces characters that aren't valid in a metric or label name with `_`.
+fn sanitize_identifier(name: &str, allow_colon: bool) -> String {
+    let mut result: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || (allow_colon && c == ':') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+
+    if result.starts_with(|c: char| c.is_ascii_digit()) {
+        result.insert(0, '_');
+    }
+
+    result
+}
+
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::table_parser::{deduct_table_type, parse_table, TableType};
+
+    const CSV: TableType = TableType::CsvTable {
+        delimiter: ',',
+        quote: Some('"'),
+    };
+
+    /// Cells mixing delimiters, quotes, line breaks, padding and Unicode.
+    fn cell() -> impl Strategy<Value = String> {
+        prop_oneof![
+            "(?s).{0,12}",
+            "[ ,\"\r\n\t'|;a-zé😀]{0,8}",
+            Just(String::new()),
+        ]
+    }
+
+    /// Tables with a unique header and rows matching its width.
+    fn table() -> impl Strategy<Value = Table> {
+        (1..5usize)
+            .prop_flat_map(|columns| {
+                (
+                    proptest::collection::hash_set(cell(), columns),
+                    proptest::collection::vec(proptest::collection::vec(cell(), columns), 0..6),
+                )
+            })
+            .prop_map(|(header, rows)| {
+                Table::with_header_and_data(header.into_iter().collect(), rows).unwrap()
+            })
+    }
+
+    /// Tables shaped like parsed metrics: one name, label columns with
+    /// non-empty values, a numeric value and optional timestamps, with rows
+    /// already grouped by metric name.
+    fn metrics_table() -> impl Strategy<Value = Table> {
+        (
+            // Labels may carry the prefix the parser gives reserved names
+            proptest::collection::hash_set("(label_){0,2}(name|value|[a-z_][a-z0-9_]{0,6})", 0..3),
+            any::<bool>(),
+        )
+            .prop_flat_map(|(labels, has_timestamps)| {
+                let labels: Vec<String> = labels
+                    .into_iter()
+                    .filter(|label| !["name", "value", "timestamp"].contains(&label.as_str()))
+                    .collect();
+                let row = (
+                    "[a-z_:][a-z0-9_:]{0,6}",
+                    proptest::collection::vec("(?s).{1,8}", labels.len()),
+                    any::<f64>().prop_filter("finite", |value| value.is_finite()),
+                    any::<i64>(),
+                );
+                (
+                    Just(labels),
+                    Just(has_timestamps),
+                    proptest::collection::vec(row, 1..5),
+                )
+            })
+            .prop_map(|(labels, has_timestamps, mut rows)| {
+                rows.sort_by(|left, right| left.0.cmp(&right.0));
+
+                let mut header = vec!["name".to_string()];
+                header.extend(labels);
+                header.push("value".to_string());
+                if has_timestamps {
+                    header.push("timestamp".to_string());
+                }
+
+                let rows = rows
+                    .into_iter()
+                    .map(|(name, label_values, value, timestamp)| {
+                        let mut row = vec![name];
+                        row.extend(label_values);
+                        row.push(value.to_string());
+                        if has_timestamps {
+                            row.push(timestamp.to_string());
+                        }
+                        row
+                    })
+                    .collect();
+
+                Table::with_header_and_data(header, rows).unwrap()
+            })
+    }
+
+    fn assert_same_table(left: &Table, right: &Table) {
+        assert_eq!(left.header(), right.header());
+        assert_eq!(left.rows(), right.rows());
+    }
+
+    proptest! {
+        #[test]
+        fn test_delimited_round_trip(table in table(), delimiter in prop::sample::select(vec![',', ';', '\t', '|'])) {
+            let output = write_delimited(&table, delimiter);
+            let parsed = parse_table(
+                TableType::CsvTable { delimiter, quote: Some('"') },
+                &output,
+                true,
+            )
+            .unwrap();
+            assert_same_table(&parsed, &table);
+        }
+
+        #[test]
+        fn test_csv_round_trip(table in table()) {
+            let parsed = parse_table(CSV, &write_csv(&table), true).unwrap();
+            assert_same_table(&parsed, &table);
+        }
+
+        #[test]
+        fn test_prometheus_round_trip(table in metrics_table()) {
+            let rendered = write_prometheus(&table).unwrap();
+            let parsed = parse_table(TableType::PrometheusTable, &rendered, true).unwrap();
+            assert_same_table(&parsed, &table);
+        }
+    }
+
+    #[test]
+    fn test_ascii_round_trip() {
+        let table = Table::with_header_and_data(
+            vec!["id".to_string(), "name".to_string()],
+            vec![
+                vec!["1".to_string(), "alice".to_string()],
+                vec!["22".to_string(), "".to_string()],
+            ],
+        )
+        .unwrap();
+
+        let output = write_ascii(&table);
+        assert!(output.starts_with("+----+-------+\n| id | name  |\n+----+-------+\n"));
+
+        let table_type = deduct_table_type(&output);
+        assert_eq!(table_type, TableType::AsciiTable);
+        assert_eq!(
+            OutputFormat::matching(&table_type),
+            Some((OutputFormat::Ascii, None))
+        );
+        let parsed = parse_table(table_type, &output, true).unwrap();
+        assert_eq!(parsed.header(), table.header());
+        assert_eq!(parsed.rows(), table.rows());
+    }
+
+    #[test]
+    fn test_write_prometheus_round_trip() {
+        let data = "up{job=\"api\",name=\"a \\\"b\\\"\"} 1 1700000000000\n\
+                    up{job=\"db\"} 0\n\
+                    queue_depth 12\n";
+        let table = parse_table(TableType::PrometheusTable, data, true).unwrap();
+
+        assert_eq!(write_prometheus(&table).unwrap(), data);
+    }
+
+    #[test]
+    fn test_writers_set_footer_apart() {
+        let table = parse_table(
+            TableType::PrometheusTable,
+            "up{job=\"api\"} 1\nup{job=\"db\"} 0\n",
+            true,
+        )
+        .unwrap();
+        let options = WriteOptions {
+            footer: Some(vec![
+                "".to_string(),
+                "".to_string(),
+                "sum(value) = 1".to_string(),
+            ]),
+            ..WriteOptions::default()
+        };
+        let render = |format: OutputFormat| {
+            String::from_utf8(format.writer()(&table, &options).unwrap()).unwrap()
+        };
+
+        assert_eq!(
+            render(OutputFormat::Csv),
+            "name,job,value\nup,api,1\nup,db,0\n\n,,sum(value) = 1\n"
+        );
+        assert!(render(OutputFormat::Ascii).ends_with(
+            "+------+-----+----------------+\n\
+             |      |     | sum(value) = 1 |\n\
+             +------+-----+----------------+\n"
+        ));
+        assert_eq!(
+            render(OutputFormat::Prometheus),
+            "up{job=\"api\"} 1\nup{job=\"db\"} 0\n# footer sum(value) = 1\n"
+        );
+        let reparsed = parse_table(
+            TableType::PrometheusTable,
+            &render(OutputFormat::Prometheus),
+            true,
+        );
+        assert_eq!(reparsed.unwrap().rows(), table.rows());
+    }
+
+    #[test]
+    fn test_ascii_highlights() {
+        let table = Table::with_header_and_data(
+            vec!["status".to_string(), "amount".to_string()],
+            vec![vec!["FAILED".to_string(), "-5".to_string()]],
+        )
+        .unwrap();
+        let options = WriteOptions {
+            highlighted: HashMap::from([((0, 1), "31".to_string())]),
+            ..WriteOptions::default()
+        };
+
+        assert_eq!(
+            write_ascii_with(&table, &options),
+            "+--------+--------+\n\
+             | status | amount |\n\
+             +--------+--------+\n\
+             | FAILED | \x1b[31m-5\x1b[0m     |\n\
+             +--------+--------+\n"
+        );
+    }
+
+    #[test]
+    fn test_ascii_subtotals() {
+        let table = Table::with_header_and_data(
+            vec!["region".to_string(), "amount".to_string()],
+            vec![
+                vec!["north".to_string(), "1".to_string()],
+                vec!["south".to_string(), "5".to_string()],
+            ],
+        )
+        .unwrap();
+        let subtotal = |label: &str, sum: &str| vec![label.to_string(), sum.to_string()];
+        let options = WriteOptions {
+            subtotals: vec![
+                (1, subtotal("subtotal north", "1")),
+                (2, subtotal("subtotal south", "5")),
+                (2, subtotal("total", "6")),
+            ],
+            ..WriteOptions::default()
+        };
+
+        assert_eq!(
+            write_ascii_with(&table, &options),
+            "+----------------+--------+\n\
+             | region         | amount |\n\
+             +----------------+--------+\n\
+             | north          | 1      |\n\
+             +----------------+--------+\n\
+             | subtotal north | 1      |\n\
+             +----------------+--------+\n\
+             | south          | 5      |\n\
+             +----------------+--------+\n\
+             | subtotal south | 5      |\n\
+             +----------------+--------+\n\
+             | total          | 6      |\n\
+             +----------------+--------+\n"
+        );
+    }
+
+    #[test]
+    fn test_prometheus_prefixed_labels_stay_apart() {
+        let data = "up{name=\"a\",label_name=\"b\",label_label_value=\"c\"} 1\n";
+        let table = parse_table(TableType::PrometheusTable, data, true).unwrap();
+
+        assert_eq!(
+            table.header(),
+            [
+                "name",
+                "label_name",
+                "label_label_name",
+                "label_label_label_value",
+                "value"
+            ]
+        );
+        assert_eq!(write_prometheus(&table).unwrap(), data);
+    }
+
+    #[test]
+    fn test_write_prometheus_requires_value_column() {
+        let table = Table::with_header_and_data(
+            vec!["name".to_string(), "status".to_string()],
+            vec![vec!["check_ok".to_string(), "1".to_string()]],
+        )
+        .unwrap();
+
+        assert!(matches!(
+            write_prometheus(&table),
+            Err(TableError::MissingColumn(column)) if column == "value"
+        ));
+    }
+
+    #[test]
+    fn test_output_format_from_extension() {
+        assert_eq!(
+            OutputFormat::from_extension(Path::new("out.prom")),
+            Some(OutputFormat::Prometheus)
+        );
+        assert_eq!(OutputFormat::from_extension(Path::new("out.txt")), None);
+    }
+
+    #[test]
+    fn test_output_target_without_format() {
+        assert_eq!(OutputTarget::parse("out.prom").unwrap().format, None);
+    }
+
+    #[test]
+    fn test_output_target_with_format() {
+        assert_eq!(
+            OutputTarget::parse("out.txt:ascii").unwrap(),
+            OutputTarget {
+                path: PathBuf::from("out.txt"),
+                format: Some(OutputFormat::Ascii)
+            }
+        );
+    }
+
+    #[test]
+    fn test_output_target_keeps_drive_letter() {
+        assert_eq!(
+            OutputTarget::parse("C:/data/out.csv").unwrap().path,
+            PathBuf::from("C:/data/out.csv")
+        );
+    }
+
+    #[test]
+    fn test_output_target_without_path() {
+        assert!(matches!(
+            OutputTarget::parse(":csv"),
+            Err(TableError::InvalidArgument(_))
+        ));
+    }
+}
@@ -0,0 +1,273 @@
+use chrono::{DateTime, FixedOffset, LocalResult, NaiveDateTime, Offset, TimeDelta, TimeZone};
+use chrono_tz::Tz;
+use clap::ValueEnum;
+
+use crate::table::{Table, TableError};
+
+/// Local timestamp layouts accepted without an offset, tried in order
+const NAIVE_FORMATS: &[&str] = &[
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M",
+    "%Y-%m-%d %H:%M",
+];
+
+/// What to do with a local time that occurs twice, when clocks go back
+#[derive(Debug, Clone, Copy, PartialEq, Default, ValueEnum)]
+pub enum AmbiguousPolicy {
+    /// Fail on the first ambiguous time
+    #[default]
+    Error,
+    /// Take the first occurrence, before the clocks go back
+    Earliest,
+    /// Take the second occurrence, after the clocks go back
+    Latest,
+}
+
+/// What to do with a local time that is skipped, when clocks go forward
+#[derive(Debug, Clone, Copy, PartialEq, Default, ValueEnum)]
+pub enum NonexistentPolicy {
+    /// Fail on the first skipped time
+    #[default]
+    Error,
+    /// Move the time forward by the length of the gap
+    ShiftForward,
+}
+
+/// Parses an IANA time zone name such as `Europe/Berlin` or `UTC`
+pub fn parse_zone(name: &str) -> Result<Tz, TableError> {
+    name.parse()
+        .map_err(|_| TableError::InvalidArgument(format!("unknown time zone '{}'", name)))
+}
+
+/// Converts a timestamp column from one time zone to another
+///
+/// Timestamps without an offset are read as local time in `from`;
+/// timestamps with one (RFC 3339, e.g. `2024-01-01T10:00:00Z`) are
+/// already absolute and `from` is ignored for them. Converted values keep
+/// the input's date/time separator and fractional seconds and carry the
+/// offset, e.g. `2024-07-01 12:00:00+02:00`. Empty cells stay empty.
+///
+/// # Arguments
+/// * `table` - Table to convert
+/// * `column_name` - Column holding timestamps
+/// * `from` - Zone of timestamps without an offset
+/// * `to` - Zone to convert to
+/// * `ambiguous` - Handling of local times that occur twice in `from`
+/// * `nonexistent` - Handling of local times skipped in `from`
+///
+/// # Returns
+/// * `Table` - Copy of the table with the column converted
+pub fn convert_timezone(
+    table: &Table,
+    column_name: &str,
+    from: Tz,
+    to: Tz,
+    ambiguous: AmbiguousPolicy,
+    nonexistent: NonexistentPolicy,
+) -> Result<Table, TableError> {
+    let column_index = table
+        .column_index(column_name)
+        .ok_or_else(|| TableError::MissingColumn(column_name.to_string()))?;
+
+    let rows = table
+        .rows()
+        .iter()
+        .enumerate()
+        .map(|(row_index, row)| {
+            let mut row = row.clone();
+            let cell = &mut row[column_index];
+            if cell.trim().is_empty() {
+                return Ok(row);
+            }
+
+            let invalid = || TableError::InvalidCell {
+                row_index,
+                column: column_name.to_string(),
+            };
+            let instant = resolve(cell.trim(), from, ambiguous, nonexistent).ok_or_else(invalid)?;
+            let separator = if cell.contains('T') { "T" } else { " " };
+            *cell = instant
+                .with_timezone(&to)
+                .format(&format!("%Y-%m-%d{}%H:%M:%S%.f%:z", separator))
+                .to_string();
+            Ok(row)
+        })
+        .collect::<Result<Vec<_>, TableError>>()?;
+
+    table.with_rows(rows)
+}
+
+/// Turns a timestamp into an instant, reading offset-less ones in `zone`.
+fn resolve(
+    value: &str,
+    zone: Tz,
+    ambiguous: AmbiguousPolicy,
+    nonexistent: NonexistentPolicy,
+) -> Option<DateTime<FixedOffset>> {
+    if let Ok(instant) = DateTime::parse_from_rfc3339(value) {
+        return Some(instant);
+    }
+
+    let naive = NAIVE_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())?;
+    let local = match zone.from_local_datetime(&naive) {
+        LocalResult::Single(local) => local,
+        LocalResult::Ambiguous(earliest, latest) => match ambiguous {
+            AmbiguousPolicy::Error => return None,
+            AmbiguousPolicy::Earliest => earliest,
+            AmbiguousPolicy::Latest => latest,
+        },
+        LocalResult::None => match nonexistent {
+            NonexistentPolicy::Error => return None,
+            NonexistentPolicy::ShiftForward => {
+                // Reading the time with the offset in effect before the gap
+                // lands it past the gap by exactly the gap's length.
+                let before = zone
+                    .from_local_datetime(&(naive - TimeDelta::days(1)))
+                    .earliest()?;
+                let utc = naive - before.offset().fix();
+                zone.from_utc_datetime(&utc)
+            }
+        },
+    };
+    Some(local.fixed_offset())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn convert(
+        values: &[&str],
+        ambiguous: AmbiguousPolicy,
+        nonexistent: NonexistentPolicy,
+    ) -> Result<Vec<String>, TableError> {
+        let table = Table::with_header_and_data(
+            vec!["created_at".to_string()],
+            values.iter().map(|value| vec![value.to_string()]).collect(),
+        )
+        .unwrap();
+        let berlin = parse_zone("Europe/Berlin").unwrap();
+        let converted = convert_timezone(
+            &table,
+            "created_at",
+            berlin,
+            Tz::UTC,
+            ambiguous,
+            nonexistent,
+        )?;
+        Ok(converted.rows().iter().map(|row| row[0].clone()).collect())
+    }
+
+    fn strict(values: &[&str]) -> Result<Vec<String>, TableError> {
+        convert(values, AmbiguousPolicy::Error, NonexistentPolicy::Error)
+    }
+
+    #[test]
+    fn test_local_times() {
+        assert_eq!(
+            strict(&["2024-07-01 12:00:00", "2024-01-01 12:00"]).unwrap(),
+            vec!["2024-07-01 10:00:00+00:00", "2024-01-01 11:00:00+00:00"]
+        );
+    }
+
+    #[test]
+    fn test_keeps_separator_and_fraction() {
+        assert_eq!(
+            strict(&["2024-01-01T12:00:00.5"]).unwrap(),
+            vec!["2024-01-01T11:00:00.500+00:00"]
+        );
+    }
+
+    #[test]
+    fn test_offsets_ignore_source_zone() {
+        assert_eq!(
+            strict(&["2024-07-01T12:00:00+02:00", "2024-07-01T12:00:00Z"]).unwrap(),
+            vec!["2024-07-01T10:00:00+00:00", "2024-07-01T12:00:00+00:00"]
+        );
+    }
+
+    #[test]
+    fn test_empty_cells_stay() {
+        assert_eq!(strict(&["", " "]).unwrap(), vec!["", " "]);
+    }
+
+    #[test]
+    fn test_ambiguous_time_fails() {
+        // 02:30 happens twice on 2024-10-27 in Berlin
+        assert!(matches!(
+            strict(&["2024-07-01 12:00", "2024-10-27 02:30"]),
+            Err(TableError::InvalidCell { row_index: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn test_ambiguous_time_policies() {
+        let resolve =
+            |ambiguous| convert(&["2024-10-27 02:30"], ambiguous, NonexistentPolicy::Error);
+        assert_eq!(
+            resolve(AmbiguousPolicy::Earliest).unwrap(),
+            vec!["2024-10-27 00:30:00+00:00"]
+        );
+        assert_eq!(
+            resolve(AmbiguousPolicy::Latest).unwrap(),
+            vec!["2024-10-27 01:30:00+00:00"]
+        );
+    }
+
+    #[test]
+    fn test_nonexistent_time_fails() {
+        // 02:30 never happens on 2024-03-31 in Berlin
+        assert!(matches!(
+            strict(&["2024-03-31 02:30"]),
+            Err(TableError::InvalidCell { row_index: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn test_nonexistent_time_shifts_forward() {
+        assert_eq!(
+            convert(
+                &["2024-03-31 02:30"],
+                AmbiguousPolicy::Error,
+                NonexistentPolicy::ShiftForward
+            )
+            .unwrap(),
+            vec!["2024-03-31 01:30:00+00:00"]
+        );
+    }
+
+    #[test]
+    fn test_invalid_timestamp() {
+        assert!(matches!(
+            strict(&["yesterday"]),
+            Err(TableError::InvalidCell { row_index: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn test_unknown_zone() {
+        assert!(matches!(
+            parse_zone("Mars/Olympus"),
+            Err(TableError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn test_unknown_column() {
+        let table = Table::with_data(vec![vec!["2024-01-01 00:00".to_string()]]).unwrap();
+        assert!(matches!(
+            convert_timezone(
+                &table,
+                "created_at",
+                Tz::UTC,
+                Tz::UTC,
+                AmbiguousPolicy::Error,
+                NonexistentPolicy::Error
+            ),
+            Err(TableError::MissingColumn(_))
+        ));
+    }
+}
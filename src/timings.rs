@@ -0,0 +1,160 @@
+use std::{
+    fmt::Write,
+    fs,
+    time::{Duration, Instant},
+};
+
+/// Wall time and memory high-water mark of one pipeline stage
+#[derive(Debug)]
+pub struct StageTiming {
+    pub stage: String,
+    pub elapsed: Duration,
+    /// Peak resident set size in KiB at the end of the stage, if known
+    pub peak_rss_kib: Option<u64>,
+}
+
+/// Collects per-stage timings for `--timings`
+///
+/// A disabled collector still runs the stages but records nothing, so
+/// callers don't need to branch on the flag.
+#[derive(Debug, Default)]
+pub struct Timings {
+    enabled: bool,
+    stages: Vec<StageTiming>,
+}
+
+impl Timings {
+    pub fn new(enabled: bool) -> Self {
+        Timings {
+            enabled,
+            stages: Vec::new(),
+        }
+    }
+
+    /// Runs a stage and records how long it took
+    ///
+    /// # Arguments
+    /// * `stage` - Stage label shown in the report
+    /// * `run` - The work done by the stage
+    ///
+    /// # Returns
+    /// * `T` - Whatever the stage returned
+    pub fn time<T>(&mut self, stage: impl Into<String>, run: impl FnOnce() -> T) -> T {
+        if !self.enabled {
+            return run();
+        }
+
+        let start = Instant::now();
+        let result = run();
+        self.stages.push(StageTiming {
+            stage: stage.into(),
+            elapsed: start.elapsed(),
+            peak_rss_kib: peak_rss_kib(),
+        });
+        result
+    }
+
+    pub fn stages(&self) -> &[StageTiming] {
+        &self.stages
+    }
+
+    /// Formats the recorded stages as an aligned table, or `None` if disabled
+    pub fn report(&self) -> Option<String> {
+        if !self.enabled {
+            return None;
+        }
+
+        let width = self
+            .stages
+            .iter()
+            .map(|timing| timing.stage.len())
+            .max()
+            .unwrap_or(0)
+            .max("total".len());
+
+        let mut report = String::new();
+        // Writing to a String never fails.
+        for timing in &self.stages {
+            let peak = timing.peak_rss_kib.map_or("n/a".to_string(), format_kib);
+            writeln!(
+                report,
+                "{:<width$}  {:>10.3} ms  peak {}",
+                timing.stage,
+                timing.elapsed.as_secs_f64() * 1000.0,
+                peak
+            )
+            .unwrap();
+        }
+        let total: Duration = self.stages.iter().map(|timing| timing.elapsed).sum();
+        writeln!(
+            report,
+            "{:<width$}  {:>10.3} ms",
+            "total",
+            total.as_secs_f64() * 1000.0
+        )
+        .unwrap();
+        Some(report)
+    }
+}
+
+/// Reads the process's resident set high-water mark (`VmHWM`) on Linux.
+fn peak_rss_kib() -> Option<u64> {
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmHWM:"))?
+        .trim()
+        .trim_end_matches("kB")
+        .trim()
+        .parse()
+        .ok()
+}
+
+fn format_kib(kib: u64) -> String {
+    if kib >= 1024 {
+        format!("{:.1} MiB", kib as f64 / 1024.0)
+    } else {
+        format!("{} KiB", kib)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disabled_timings_record_nothing() {
+        let mut disabled = Timings::new(false);
+        assert_eq!(disabled.time("parse", || 2 + 2), 4);
+        assert!(disabled.stages().is_empty());
+        assert_eq!(disabled.report(), None);
+    }
+
+    #[test]
+    fn test_stages_in_order() {
+        let mut enabled = Timings::new(true);
+        assert_eq!(enabled.time("read", || "value"), "value");
+        enabled.time("parse", || ());
+        let stages: Vec<&str> = enabled
+            .stages()
+            .iter()
+            .map(|timing| timing.stage.as_str())
+            .collect();
+        assert_eq!(stages, vec!["read", "parse"]);
+    }
+
+    #[test]
+    fn test_report_ends_with_total() {
+        let mut enabled = Timings::new(true);
+        enabled.time("read", || ());
+        let report = enabled.report().unwrap();
+        assert!(report.starts_with("read "));
+        assert!(report.lines().last().unwrap().starts_with("total"));
+    }
+
+    #[test]
+    fn test_format_kib() {
+        assert_eq!(format_kib(512), "512 KiB");
+        assert_eq!(format_kib(1536), "1.5 MiB");
+    }
+}
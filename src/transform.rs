@@ -0,0 +1,178 @@
+use std::sync::LazyLock;
+
+use clap::ValueEnum;
+use regex::Regex;
+
+use crate::table::{Table, TableError};
+
+static EMAIL: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"^[A-Za-z0-9!#$%&'*+/=?^_`{|}~-]+(?:\.[A-Za-z0-9!#$%&'*+/=?^_`{|}~-]+)*@(?:[A-Za-z0-9](?:[A-Za-z0-9-]{0,61}[A-Za-z0-9])?\.)+[A-Za-z]{2,63}$",
+    )
+    .unwrap()
+});
+static URL: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"^[A-Za-z][A-Za-z0-9+.-]*://(?:[^/?#@]*@)?(\[[^\]]*\]|[^/?#:]+)(?::[0-9]*)?([^?#]*)",
+    )
+    .unwrap()
+});
+
+/// A computed column derived from one source column
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Transform {
+    /// Domain of an email address, or host of a URL, lowercased
+    ExtractDomain,
+    /// `true` if the cell is a well-formed email address, else `false`
+    IsValidEmail,
+    /// Host of a URL, lowercased
+    UrlHost,
+    /// Path of a URL, `/` when it has none
+    UrlPath,
+}
+
+impl Transform {
+    /// Computes the derived value of a cell; cells the transform does not
+    /// apply to give an empty value
+    pub fn apply(&self, cell: &str) -> String {
+        let cell = cell.trim();
+        match self {
+            Transform::ExtractDomain => match cell.rsplit_once('@') {
+                Some((_, domain)) if EMAIL.is_match(cell) => domain.to_lowercase(),
+                _ => Transform::UrlHost.apply(cell),
+            },
+            Transform::IsValidEmail => EMAIL.is_match(cell).to_string(),
+            Transform::UrlHost => URL
+                .captures(cell)
+                .map_or(String::new(), |captures| captures[1].to_lowercase()),
+            Transform::UrlPath => {
+                URL.captures(cell)
+                    .map_or(String::new(), |captures| match &captures[2] {
+                        "" => "/".to_string(),
+                        path => path.to_string(),
+                    })
+            }
+        }
+    }
+
+    /// Default name of the computed column, e.g. `email_domain`
+    pub fn column_name(&self, source: &str) -> String {
+        let suffix = match self {
+            Transform::ExtractDomain => "domain",
+            Transform::IsValidEmail => "is_valid_email",
+            Transform::UrlHost => "host",
+            Transform::UrlPath => "path",
+        };
+        format!("{}_{}", source, suffix)
+    }
+}
+
+/// Appends a column computed from another one
+///
+/// # Arguments
+/// * `table` - Table to extend
+/// * `column_name` - Source column
+/// * `transform` - Transform applied to each cell of the source column
+/// * `into` - Name of the new column
+///
+/// # Returns
+/// * `Table` - The table with the computed column appended
+pub fn derive_column(
+    table: &Table,
+    column_name: &str,
+    transform: Transform,
+    into: &str,
+) -> Result<Table, TableError> {
+    let column_index = table
+        .column_index(column_name)
+        .ok_or_else(|| TableError::MissingColumn(column_name.to_string()))?;
+
+    let values = table
+        .rows()
+        .iter()
+        .map(|row| transform.apply(&row[column_index]))
+        .collect();
+    table.append_column(into, values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_domain_of_email() {
+        assert_eq!(
+            Transform::ExtractDomain.apply("Jane.Doe@Example.COM"),
+            "example.com"
+        );
+    }
+
+    #[test]
+    fn test_extract_domain_of_url() {
+        assert_eq!(
+            Transform::ExtractDomain.apply("https://user@Shop.example.org:8080/a"),
+            "shop.example.org"
+        );
+        assert_eq!(Transform::ExtractDomain.apply("n/a"), "");
+    }
+
+    #[test]
+    fn test_valid_emails() {
+        assert_eq!(Transform::IsValidEmail.apply("a.b+tag@mail.co"), "true");
+        assert_eq!(Transform::IsValidEmail.apply(" a@mail.co "), "true");
+    }
+
+    #[test]
+    fn test_invalid_emails() {
+        for invalid in ["a..b@mail.co", "a@b", "@mail.co", "a b@mail.co", ""] {
+            assert_eq!(
+                Transform::IsValidEmail.apply(invalid),
+                "false",
+                "{}",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn test_url_host() {
+        assert_eq!(
+            Transform::UrlHost.apply("http://[::1]:3000/health"),
+            "[::1]"
+        );
+        assert_eq!(Transform::UrlHost.apply("example.org/docs"), "");
+    }
+
+    #[test]
+    fn test_url_path() {
+        assert_eq!(
+            Transform::UrlPath.apply("https://example.org/docs/a?q=1#top"),
+            "/docs/a"
+        );
+        assert_eq!(Transform::UrlPath.apply("https://example.org"), "/");
+        assert_eq!(Transform::UrlPath.apply("example.org/docs"), "");
+    }
+
+    #[test]
+    fn test_derive_column() {
+        let table = Table::with_header_and_data(
+            vec!["email".to_string()],
+            vec![vec!["a@Mail.co".to_string()]],
+        )
+        .unwrap();
+        let transform = Transform::ExtractDomain;
+        let derived =
+            derive_column(&table, "email", transform, &transform.column_name("email")).unwrap();
+        assert_eq!(derived.header(), vec!["email", "email_domain"]);
+        assert_eq!(derived.rows()[0][1], "mail.co");
+    }
+
+    #[test]
+    fn test_derive_unknown_column() {
+        let table = Table::with_data(vec![vec!["a@mail.co".to_string()]]).unwrap();
+        assert!(matches!(
+            derive_column(&table, "email", Transform::UrlHost, "host"),
+            Err(TableError::MissingColumn(column)) if column == "email"
+        ));
+    }
+}
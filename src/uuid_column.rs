@@ -0,0 +1,169 @@
+use uuid::Uuid;
+
+use crate::checksum::FIELD_SEPARATOR;
+use crate::table::{Table, TableError};
+
+/// How the values of a UUID column are generated
+#[derive(Debug, Clone, PartialEq)]
+pub enum UuidKind {
+    /// Random, version 4
+    Random,
+    /// Time-ordered, version 7, increasing down the column
+    TimeOrdered,
+    /// Name-based, version 5: the same namespace and fields always give
+    /// the same UUID
+    Named {
+        namespace: Uuid,
+        columns: Vec<String>,
+    },
+}
+
+/// Parses a UUID namespace: `dns`, `url`, `oid`, `x500` or a UUID
+pub fn parse_namespace(value: &str) -> Result<Uuid, TableError> {
+    match value.to_lowercase().as_str() {
+        "dns" => Ok(Uuid::NAMESPACE_DNS),
+        "url" => Ok(Uuid::NAMESPACE_URL),
+        "oid" => Ok(Uuid::NAMESPACE_OID),
+        "x500" => Ok(Uuid::NAMESPACE_X500),
+        _ => Uuid::parse_str(value).map_err(|_| {
+            TableError::InvalidArgument(format!(
+                "invalid namespace '{}', expected dns, url, oid, x500 or a UUID",
+                value
+            ))
+        }),
+    }
+}
+
+/// Appends a column of generated UUIDs
+///
+/// Name-based UUIDs hash the selected fields in order, joined by
+/// `FIELD_SEPARATOR`.
+///
+/// # Arguments
+/// * `table` - Table to extend
+/// * `name` - Name of the new column
+/// * `kind` - How the UUIDs are generated
+///
+/// # Returns
+/// * `Table` - The table with the UUID column appended
+pub fn add_uuid_column(table: &Table, name: &str, kind: &UuidKind) -> Result<Table, TableError> {
+    let indices = match kind {
+        UuidKind::Named { columns, .. } => columns
+            .iter()
+            .map(|column| {
+                table
+                    .column_index(column)
+                    .ok_or_else(|| TableError::MissingColumn(column.clone()))
+            })
+            .collect::<Result<Vec<usize>, TableError>>()?,
+        _ => Vec::new(),
+    };
+
+    let uuids = table
+        .rows()
+        .iter()
+        .map(|row| {
+            let uuid = match kind {
+                UuidKind::Random => Uuid::new_v4(),
+                UuidKind::TimeOrdered => Uuid::now_v7(),
+                UuidKind::Named { namespace, .. } => {
+                    let fields: Vec<&[u8]> =
+                        indices.iter().map(|index| row[*index].as_bytes()).collect();
+                    Uuid::new_v5(namespace, &fields.join(&FIELD_SEPARATOR))
+                }
+            };
+            uuid.to_string()
+        })
+        .collect();
+    table.append_column(name, uuids)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn uuids(kind: &UuidKind) -> Vec<String> {
+        let table = Table::with_header_and_data(
+            vec!["host".to_string()],
+            ["python.org", "python.org", "rust-lang.org"]
+                .iter()
+                .map(|host| vec![host.to_string()])
+                .collect(),
+        )
+        .unwrap();
+        add_uuid_column(&table, "id", kind)
+            .unwrap()
+            .rows()
+            .iter()
+            .map(|row| row[1].clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_named_uuids() {
+        let named = uuids(&UuidKind::Named {
+            namespace: parse_namespace("DNS").unwrap(),
+            columns: vec!["host".to_string()],
+        });
+        assert_eq!(named[0], "886313e1-3b8a-5372-9b90-0c9aee199e5d");
+        assert_eq!(named[0], named[1]);
+        assert_ne!(named[1], named[2]);
+    }
+
+    #[test]
+    fn test_random_uuids() {
+        let random = uuids(&UuidKind::Random);
+        assert_ne!(random[0], random[1]);
+        assert!(random
+            .iter()
+            .all(|uuid| Uuid::parse_str(uuid).unwrap().get_version_num() == 4));
+    }
+
+    #[test]
+    fn test_time_ordered_uuids() {
+        let ordered = uuids(&UuidKind::TimeOrdered);
+        assert!(ordered.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn test_named_namespace() {
+        assert_eq!(parse_namespace("url").unwrap(), Uuid::NAMESPACE_URL);
+    }
+
+    #[test]
+    fn test_namespace_by_uuid() {
+        assert_eq!(
+            parse_namespace("6ba7b810-9dad-11d1-80b4-00c04fd430c8").unwrap(),
+            Uuid::NAMESPACE_DNS
+        );
+    }
+
+    #[test]
+    fn test_unknown_namespace() {
+        assert!(matches!(
+            parse_namespace("example"),
+            Err(TableError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn test_named_uuid_unknown_column() {
+        let table = Table::with_header_and_data(vec!["host".to_string()], Vec::new()).unwrap();
+        let kind = UuidKind::Named {
+            namespace: Uuid::NAMESPACE_DNS,
+            columns: vec!["domain".to_string()],
+        };
+        assert!(matches!(
+            add_uuid_column(&table, "id", &kind),
+            Err(TableError::MissingColumn(column)) if column == "domain"
+        ));
+    }
+
+    #[test]
+    fn test_uuid_column_without_header() {
+        let table = Table::with_data(vec![vec!["a".to_string()]]).unwrap();
+        let table = add_uuid_column(&table, "id", &UuidKind::Random).unwrap();
+        assert!(table.header().is_empty());
+        assert!(Uuid::parse_str(&table.rows()[0][1]).is_ok());
+    }
+}
@@ -0,0 +1,108 @@
+use std::{
+    collections::HashSet,
+    convert::Infallible,
+    env,
+    ffi::OsString,
+    io::{self, IsTerminal, Write},
+    path::{self, Path, PathBuf},
+    process::Command,
+    sync::mpsc,
+    thread,
+    time::Duration,
+};
+
+use notify::{
+    event::{EventKind, ModifyKind},
+    RecursiveMode, Watcher,
+};
+
+/// Quiet time after a change before re-running, so a file written in
+/// several steps is read once
+const SETTLE: Duration = Duration::from_millis(200);
+
+/// Re-runs this invocation without `--watch` whenever one of `paths` changes
+///
+/// Each run is a child process, so a failed run, e.g. on a half-written
+/// file, reports its error and watching goes on. The terminal is cleared
+/// before each run, and runs don't page their output. Parent directories
+/// are watched rather than the files, since editors often replace a file
+/// instead of writing into it.
+///
+/// # Arguments
+/// * `paths` - Input files to watch
+///
+/// # Returns
+/// * `notify::Error` - Only returns when watching fails
+pub fn watch(paths: &[PathBuf]) -> notify::Result<Infallible> {
+    let watched = paths
+        .iter()
+        .map(path::absolute)
+        .collect::<io::Result<HashSet<PathBuf>>>()?;
+    let (sender, receiver) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(sender)?;
+    let directories: HashSet<&Path> = watched.iter().filter_map(|path| path.parent()).collect();
+    for directory in directories {
+        watcher.watch(directory, RecursiveMode::NonRecursive)?;
+    }
+
+    let program = env::current_exe()?;
+    let arguments = child_arguments(env::args_os().skip(1));
+    let names: Vec<String> = paths
+        .iter()
+        .map(|path| path.display().to_string())
+        .collect();
+    loop {
+        let mut stdout = io::stdout();
+        if stdout.is_terminal() {
+            write!(stdout, "\x1b[2J\x1b[H").and_then(|()| stdout.flush())?;
+        }
+        Command::new(&program)
+            .args(&arguments)
+            .env_remove("TABLES_WATCH")
+            .env("TABLES_NO_PAGER", "true")
+            .status()?;
+        eprintln!(
+            "watching {} for changes, press Ctrl-C to stop",
+            names.join(", ")
+        );
+
+        loop {
+            let event = receiver
+                .recv()
+                .map_err(|_| notify::Error::generic("file watcher stopped"))??;
+            // Reads by the run itself raise access events, which must not
+            // trigger another run
+            let changed = match event.kind {
+                EventKind::Modify(ModifyKind::Metadata(_)) => false,
+                EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_) => true,
+                _ => false,
+            };
+            if changed && event.paths.iter().any(|path| watched.contains(path)) {
+                break;
+            }
+        }
+        thread::sleep(SETTLE);
+        while receiver.try_recv().is_ok() {}
+    }
+}
+
+/// The command line of each run: this one without `--watch`
+fn child_arguments(arguments: impl Iterator<Item = OsString>) -> Vec<OsString> {
+    arguments.filter(|argument| argument != "--watch").collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_child_arguments() {
+        let arguments = ["--watch", "diff", "a.csv", "b.csv", "--to", "ascii"]
+            .iter()
+            .map(OsString::from);
+        assert_eq!(
+            child_arguments(arguments),
+            vec!["diff", "a.csv", "b.csv", "--to", "ascii"]
+        );
+    }
+}